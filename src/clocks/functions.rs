@@ -1,7 +1,7 @@
 //! The impls and functions
 //!
 use chrono::Local;
-use std::{sync::mpsc::channel, time::Instant};
+use std::{collections::BTreeMap, sync::mpsc::channel, time::Instant};
 use scraper::{Html, Selector};
 use log::*;
 use anyhow::Result;
@@ -70,6 +70,22 @@ impl AllClocks {
         }
         Ok(allclocks)
     }
+    /// The tablet server hostname_port by server uuid.
+    /// The server column of `/tablet-server-clocks` holds the tablet server hostname_port and uuid.
+    pub fn tablet_server_uuids(&self) -> BTreeMap<String, String>
+    {
+        self.clocks
+            .iter()
+            .filter_map(|clock| {
+                let mut server = clock.server.split_whitespace();
+                match (server.next(), server.next())
+                {
+                    (Some(hostname_port), Some(uuid)) => Some((uuid.to_string(), hostname_port.to_string())),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
     fn read_http(
         host: &str,
         port: &str,
@@ -365,6 +381,9 @@ mod tests {
         assert_eq!(result[0].cloud, "local");
         assert_eq!(result[0].region, "local");
         assert_eq!(result[0].zone, "local1");
+
+        let allclocks = AllClocks { clocks: result };
+        assert_eq!(allclocks.tablet_server_uuids()["f54d6bef7e87407597df67ba7ea59892"], "yb-3.local:9000");
     }
 
     #[tokio::test]
@@ -0,0 +1,382 @@
+//! The impls and functions
+//!
+use std::{collections::{BTreeMap, BTreeSet}, fs};
+use colored::*;
+use anyhow::{bail, Context, Result};
+use crate::snapshot;
+use crate::masters::Masters;
+use crate::tablet_servers::AllTabletServers;
+use crate::versions::AllVersions;
+use crate::gflags::AllGFlags;
+use crate::cluster_config::AllSysClusterConfigEntryPB;
+use crate::entities::AllEntities;
+use crate::cluster_spec::{AllSpecDrift, ClusterNode, ClusterSpec, ClusterState, SpecDrift};
+use crate::Opts;
+
+impl ClusterSpec {
+    /// Read and parse the cluster spec file, which must be YAML, whatever the file extension.
+    pub fn read_file(
+        spec_file: &str,
+    ) -> Result<ClusterSpec>
+    {
+        let spec = fs::read_to_string(spec_file)
+            .with_context(|| format!("Cannot read file: {}", spec_file))?;
+        ClusterSpec::parse_spec(&spec)
+            .with_context(|| format!("Error parsing cluster spec: {}", spec_file))
+    }
+    fn parse_spec(
+        spec: &str,
+    ) -> Result<ClusterSpec>
+    {
+        Ok(serde_yaml::from_str(spec)?)
+    }
+}
+
+/// A version in the spec with a build number (2.17.2.0-b216) must match the version and build number,
+/// a version without a build number (2.17.2.0) must match the version number only.
+fn version_matches(
+    expected: &str,
+    found: &str,
+) -> bool
+{
+    if expected.contains("-b")
+    {
+        found == expected
+    }
+    else
+    {
+        found.split("-b").next() == Some(expected)
+    }
+}
+
+impl ClusterState {
+    pub fn new() -> Self { Default::default() }
+    /// Combine the data of the different endpoints into the cluster state.
+    pub fn new_from_data(
+        masters: &Masters,
+        alltabletservers: &AllTabletServers,
+        allversions: AllVersions,
+        allgflags: AllGFlags,
+        allsysclusterconfigentrypb: &AllSysClusterConfigEntryPB,
+        allentities: &AllEntities,
+    ) -> ClusterState
+    {
+        let mut clusterstate = ClusterState::new();
+
+        // every master returns all masters, and every master returns all tablet servers.
+        for master in &masters.masters
+        {
+            let Some(registration) = master.registration.as_ref() else { continue };
+            let Some(http_address) = registration.http_addresses.as_ref().and_then(|http_addresses| http_addresses.first()) else { continue };
+            clusterstate.nodes
+                .entry(format!("{}:{}", http_address.host, http_address.port))
+                .or_insert(ClusterNode {
+                    role: "master".to_string(),
+                    zone: registration.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_zone.clone()).unwrap_or_default(),
+                    ..Default::default()
+                });
+        }
+        for tabletserver in &alltabletservers.tabletservers
+        {
+            let Some(hostname_port) = tabletserver.tablet_server_hostname_port.clone() else { continue };
+            clusterstate.nodes
+                .entry(hostname_port)
+                .or_insert(ClusterNode {
+                    role: "tserver".to_string(),
+                    zone: tabletserver.zone.clone(),
+                    status: tabletserver.status.clone(),
+                });
+        }
+        for version in allversions.versions
+        {
+            let Some(hostname_port) = version.hostname_port else { continue };
+            clusterstate.versions.insert(hostname_port, format!("{}-b{}", version.version_number, version.build_number));
+        }
+        clusterstate.gflags = allgflags;
+        clusterstate.replication_factor = allsysclusterconfigentrypb.sysclusterconfigentrypb
+            .first()
+            .and_then(|cluster_config| cluster_config.replication_info.as_ref())
+            .and_then(|replication_info| replication_info.live_replicas.as_ref())
+            .and_then(|live_replicas| live_replicas.num_replicas);
+        clusterstate.user_table_count = allentities.entities
+            .iter()
+            .find(|entities| !entities.tables.is_empty())
+            .map(|entities| entities.user_table_count());
+
+        clusterstate
+    }
+    pub fn read_snapshot(
+        snapshot_number: &String,
+    ) -> Result<ClusterState>
+    {
+        let mut masters = Masters::new();
+        masters.masters = snapshot::read_snapshot_json(snapshot_number, "masters")?;
+        let mut alltabletservers = AllTabletServers::new();
+        alltabletservers.tabletservers = snapshot::read_snapshot_json(snapshot_number, "tablet_servers")?;
+        let mut allversions = AllVersions::new();
+        allversions.versions = snapshot::read_snapshot_json(snapshot_number, "versions")?;
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags = snapshot::read_snapshot_json(snapshot_number, "gflags")?;
+        let mut allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::new();
+        allsysclusterconfigentrypb.sysclusterconfigentrypb = snapshot::read_snapshot_json(snapshot_number, "cluster-config")?;
+        let mut allentities = AllEntities::new();
+        allentities.entities = snapshot::read_snapshot_json(snapshot_number, "entities")?;
+
+        Ok(ClusterState::new_from_data(&masters, &alltabletservers, allversions, allgflags, &allsysclusterconfigentrypb, &allentities))
+    }
+    pub async fn read_http(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> ClusterState
+    {
+        let masters = Masters::read_masters(hosts, ports, parallel).await;
+        let alltabletservers = AllTabletServers::read_tabletservers(hosts, ports, parallel).await;
+        let allversions = AllVersions::read_versions(hosts, ports, parallel).await;
+        let allgflags = AllGFlags::read_gflags(hosts, ports, parallel).await;
+        let allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::read_cluster_config(hosts, ports, parallel).await;
+        let allentities = AllEntities::read_entities(hosts, ports, parallel).await;
+
+        ClusterState::new_from_data(&masters, &alltabletservers, allversions, allgflags, &allsysclusterconfigentrypb, &allentities)
+    }
+}
+
+impl AllSpecDrift {
+    pub fn new() -> Self { Default::default() }
+    fn add(
+        &mut self,
+        category: &str,
+        subject: &str,
+        expected: &str,
+        found: &str,
+    )
+    {
+        self.drifts.push(SpecDrift {
+            category: category.to_string(),
+            subject: subject.to_string(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        });
+    }
+    /// Compare the cluster state with the cluster spec.
+    pub fn check(
+        clusterspec: &ClusterSpec,
+        clusterstate: &ClusterState,
+    ) -> AllSpecDrift
+    {
+        let mut allspecdrift = AllSpecDrift::new();
+
+        // nodes
+        for node in &clusterspec.nodes
+        {
+            match clusterstate.nodes.get(&node.hostname_port)
+            {
+                None => allspecdrift.add("node", &node.hostname_port, &node.role, "(absent)"),
+                Some(found) => {
+                    if found.role != node.role
+                    {
+                        allspecdrift.add("node", &node.hostname_port, &node.role, &found.role);
+                    }
+                    if let Some(zone) = node.zone.as_ref().filter(|zone| **zone != found.zone)
+                    {
+                        allspecdrift.add("zone", &node.hostname_port, zone, &found.zone);
+                    }
+                    if found.role == "tserver"
+                        && found.status != "ALIVE"
+                    {
+                        allspecdrift.add("status", &node.hostname_port, "ALIVE", &found.status);
+                    }
+                },
+            }
+        }
+        if !clusterspec.nodes.is_empty()
+        {
+            let spec_nodes: BTreeSet<_> = clusterspec.nodes.iter().map(|node| &node.hostname_port).collect();
+            for (hostname_port, found) in clusterstate.nodes
+                .iter()
+                .filter(|(hostname_port, _)| !spec_nodes.contains(hostname_port))
+            {
+                allspecdrift.add("node", hostname_port, "(absent)", &found.role);
+            }
+        }
+
+        // versions: the version of a node overrides the cluster version.
+        if !clusterspec.nodes.is_empty()
+        {
+            for node in &clusterspec.nodes
+            {
+                let Some(expected) = node.version.as_ref().or(clusterspec.version.as_ref()) else { continue };
+                match clusterstate.versions.get(&node.hostname_port)
+                {
+                    None => allspecdrift.add("version", &node.hostname_port, expected, "(unknown)"),
+                    Some(found) if !version_matches(expected, found) => allspecdrift.add("version", &node.hostname_port, expected, found),
+                    Some(_) => {},
+                }
+            }
+        }
+        else if let Some(expected) = clusterspec.version.as_ref()
+        {
+            for (hostname_port, found) in clusterstate.versions
+                .iter()
+                .filter(|(_, found)| !version_matches(expected, found))
+            {
+                allspecdrift.add("version", hostname_port, expected, found);
+            }
+        }
+
+        // gflags: a server of the role of the gflag that provided gflags, but not the gflag, is reported as absent.
+        let roles = clusterstate.gflags.roles();
+        let gflag_values: BTreeMap<(&String, &String), &String> = clusterstate.gflags.gflags
+            .iter()
+            .filter_map(|gflag| gflag.hostname_port.as_ref().map(|hostname_port| ((hostname_port, &gflag.name), &gflag.value)))
+            .collect();
+        for specgflag in &clusterspec.gflags
+        {
+            for (hostname_port, _) in roles
+                .iter()
+                .filter(|(_, role)| specgflag.role.as_ref().map(|spec_role| spec_role == *role).unwrap_or(true))
+            {
+                match gflag_values.get(&(hostname_port, &specgflag.name))
+                {
+                    None => allspecdrift.add("gflag", &format!("{} {}", hostname_port, specgflag.name), &specgflag.value, "(absent)"),
+                    Some(value) if **value != specgflag.value => allspecdrift.add("gflag", &format!("{} {}", hostname_port, specgflag.name), &specgflag.value, value),
+                    Some(_) => {},
+                }
+            }
+        }
+
+        // replication factor
+        if let Some(expected) = clusterspec.replication_factor
+        {
+            if clusterstate.replication_factor != Some(expected)
+            {
+                allspecdrift.add("replication_factor", "cluster", &expected.to_string(), &clusterstate.replication_factor.map(|found| found.to_string()).unwrap_or_else(|| "(not set)".to_string()));
+            }
+        }
+
+        // tables
+        if let Some(tables) = clusterspec.tables.as_ref()
+        {
+            let expected = format!("{}..{}", tables.min.map(|min| min.to_string()).unwrap_or_default(), tables.max.map(|max| max.to_string()).unwrap_or_default());
+            match clusterstate.user_table_count
+            {
+                None => allspecdrift.add("tables", "user tables", &expected, "(unknown)"),
+                Some(user_table_count) if tables.min.map(|min| user_table_count < min).unwrap_or(false)
+                    || tables.max.map(|max| user_table_count > max).unwrap_or(false) => allspecdrift.add("tables", "user tables", &expected, &user_table_count.to_string()),
+                Some(_) => {},
+            }
+        }
+
+        allspecdrift
+    }
+    pub fn print(
+        &self,
+    )
+    {
+        if self.drifts.is_empty()
+        {
+            println!("{}", "The cluster matches the cluster spec.".green());
+            return;
+        }
+        println!("{:20} {:50} {:30} {:30}", "category", "subject", "expected", "found");
+        for drift in &self.drifts
+        {
+            println!("{:20} {:50} {:30} {:30}", drift.category, drift.subject, drift.expected.green(), drift.found.red());
+        }
+    }
+}
+
+pub async fn check_spec(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let clusterspec = ClusterSpec::read_file(options.check_spec.as_ref().unwrap())?;
+
+    let clusterstate = match &options.spec_snapshot
+    {
+        Some(snapshot_number) => ClusterState::read_snapshot(snapshot_number)?,
+        None => ClusterState::read_http(&hosts, &ports, parallel).await,
+    };
+
+    let allspecdrift = AllSpecDrift::check(&clusterspec, &clusterstate);
+    allspecdrift.print();
+
+    if !allspecdrift.drifts.is_empty()
+    {
+        bail!("{} difference(s) found between the cluster spec and the cluster", allspecdrift.drifts.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gflags::GFlag;
+
+    #[test]
+    fn unit_check_cluster_spec() {
+        let spec = r#"
+nodes:
+  - hostname_port: 192.168.66.80:7000
+    role: master
+    zone: local1
+  - hostname_port: 192.168.66.80:9000
+    role: tserver
+    zone: local1
+  - hostname_port: 192.168.66.81:9000
+    role: tserver
+    zone: local2
+    version: 2.17.2.0
+version: 2.17.1.0
+replication_factor: 3
+gflags:
+  - name: ysql_enable_auth
+    role: tserver
+    value: "true"
+tables:
+  max: 10
+"#;
+        let clusterspec = ClusterSpec::parse_spec(spec).unwrap();
+        assert_eq!(clusterspec.nodes.len(), 3);
+
+        let mut clusterstate = ClusterState::new();
+        clusterstate.nodes.insert("192.168.66.80:7000".to_string(), ClusterNode { role: "master".to_string(), zone: "local1".to_string(), ..Default::default() });
+        clusterstate.nodes.insert("192.168.66.80:9000".to_string(), ClusterNode { role: "tserver".to_string(), zone: "local3".to_string(), status: "ALIVE".to_string() });
+        clusterstate.nodes.insert("192.168.66.82:9000".to_string(), ClusterNode { role: "tserver".to_string(), zone: "local2".to_string(), status: "ALIVE".to_string() });
+        clusterstate.versions.insert("192.168.66.80:7000".to_string(), "2.17.1.0-b239".to_string());
+        clusterstate.versions.insert("192.168.66.80:9000".to_string(), "2.17.0.0-b24".to_string());
+        for (hostname_port, log_filename) in [("192.168.66.80:7000", "yb-master"), ("192.168.66.80:9000", "yb-tserver")] {
+            clusterstate.gflags.gflags.push(GFlag { hostname_port: Some(hostname_port.to_string()), name: "log_filename".to_string(), value: log_filename.to_string(), ..Default::default() });
+            clusterstate.gflags.gflags.push(GFlag { hostname_port: Some(hostname_port.to_string()), name: "ysql_enable_auth".to_string(), value: "false".to_string(), ..Default::default() });
+        }
+        // a tserver that provided gflags, but not the spec gflag, is reported as absent.
+        clusterstate.gflags.gflags.push(GFlag { hostname_port: Some("192.168.66.82:9000".to_string()), name: "log_filename".to_string(), value: "yb-tserver".to_string(), ..Default::default() });
+        // a gflag without hostname:port is skipped.
+        clusterstate.gflags.gflags.push(GFlag { hostname_port: None, name: "ysql_enable_auth".to_string(), value: "false".to_string(), ..Default::default() });
+        clusterstate.replication_factor = Some(3);
+        clusterstate.user_table_count = Some(12);
+
+        let allspecdrift = AllSpecDrift::check(&clusterspec, &clusterstate);
+        let drifts: Vec<_> = allspecdrift.drifts.iter().map(|drift| (drift.category.as_str(), drift.subject.as_str(), drift.found.as_str())).collect();
+        assert_eq!(drifts, vec![
+            ("zone", "192.168.66.80:9000", "local3"),
+            ("node", "192.168.66.81:9000", "(absent)"),
+            ("node", "192.168.66.82:9000", "tserver"),
+            ("version", "192.168.66.80:9000", "2.17.0.0-b24"),
+            ("version", "192.168.66.81:9000", "(unknown)"),
+            ("gflag", "192.168.66.80:9000 ysql_enable_auth", "false"),
+            ("gflag", "192.168.66.82:9000 ysql_enable_auth", "(absent)"),
+            ("tables", "user tables", "12"),
+        ]);
+
+        // without a user table count, the tables check is reported as unknown.
+        clusterstate.user_table_count = None;
+        let allspecdrift = AllSpecDrift::check(&clusterspec, &clusterstate);
+        assert!(allspecdrift.drifts.iter().any(|drift| drift.category == "tables" && drift.found == "(unknown)"));
+        assert!(version_matches("2.17.1.0-b239", "2.17.1.0-b239"));
+        assert!(!version_matches("2.17.1.0-b216", "2.17.1.0-b239"));
+    }
+}
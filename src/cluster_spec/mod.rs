@@ -0,0 +1,48 @@
+//! Module for checking the cluster against its intended deployment, described in a YAML cluster spec (`--check-spec <file>`).
+//!
+//! The cluster spec describes the expected cluster:
+//! ```yaml
+//! nodes:
+//!   - hostname_port: 192.168.66.80:7000
+//!     role: master
+//!     zone: local1
+//!   - hostname_port: 192.168.66.80:9000
+//!     role: tserver
+//!     zone: local1
+//! version: 2.17.2.0-b216
+//! replication_factor: 3
+//! gflags:
+//!   - name: ysql_enable_auth
+//!     role: tserver
+//!     value: "true"
+//! tables:
+//!   min: 1
+//!   max: 500
+//! ```
+//! The hostname_port of a node is the http address of the master or tablet server.
+//! The version is the version number (2.17.2.0), or the version number and build number (2.17.2.0-b216),
+//! and can be set per node, overriding the cluster version.
+//! Only YAML is supported for the cluster spec.
+//! All sections are optional: a section that is not set is not checked.
+//!
+//! The check uses the data of:
+//! - masters (`/api/v1/masters`): the master nodes and zones.
+//! - tablet_servers (`/api/v1/tablet-servers`): the tablet server nodes, zones and status.
+//! - versions (`/api/v1/version`): the version per server.
+//! - gflags (`/varz`): the gflag values.
+//! - cluster_config (`/api/v1/cluster-config`): the replication factor.
+//! - entities (`/dump-entities`): the number of user tables.
+//!
+//! Every difference is printed, and yb_stats exits with an error if any difference is found.
+//! A value that the spec requires but that could not be found is a difference too:
+//! a gflag that a server of the role does not provide is reported as `(absent)`,
+//! and a version or the number of user tables that could not be read is reported as `(unknown)`.
+//!
+//! The cluster spec functionality is called from:
+//! - [crate] -> [check_spec] (check a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
@@ -0,0 +1,75 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+use crate::gflags::AllGFlags;
+
+/// The root struct for deserializing the cluster spec (YAML).
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ClusterSpec {
+    #[serde(default)]
+    pub nodes: Vec<SpecNode>,
+    /// The expected version of all servers: the version_number (2.17.2.0), or the version_number and build_number (2.17.2.0-b216).
+    pub version: Option<String>,
+    pub replication_factor: Option<i32>,
+    #[serde(default)]
+    pub gflags: Vec<SpecGFlag>,
+    pub tables: Option<SpecRange>,
+}
+/// A master or tablet server node.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SpecNode {
+    /// The http hostname:port of the server.
+    pub hostname_port: String,
+    /// master or tserver
+    pub role: String,
+    pub zone: Option<String>,
+    /// The expected version_number of this server, overriding the cluster version.
+    pub version: Option<String>,
+}
+/// A required gflag value, optionally only for a role (master or tserver).
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SpecGFlag {
+    pub name: String,
+    pub value: String,
+    pub role: Option<String>,
+}
+/// An inclusive range, where both ends are optional.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SpecRange {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+/// A single difference between the cluster spec and the cluster.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SpecDrift {
+    /// The area of the spec: node, zone, status, version, gflag, replication_factor, tables.
+    pub category: String,
+    /// What is checked, such as the hostname:port or gflag name.
+    pub subject: String,
+    pub expected: String,
+    pub found: String,
+}
+/// A master or tablet server as found in the cluster.
+#[derive(Debug, Default)]
+pub struct ClusterNode {
+    pub role: String,
+    pub zone: String,
+    /// The status of a tablet server (ALIVE, DEAD), empty for a master.
+    pub status: String,
+}
+/// The cluster data that is checked against the cluster spec.
+#[derive(Debug, Default)]
+pub struct ClusterState {
+    /// BTreeMap with hostname_port and the node.
+    pub nodes: BTreeMap<String, ClusterNode>,
+    /// BTreeMap with hostname_port and the version_number-bbuild_number.
+    pub versions: BTreeMap<String, String>,
+    pub gflags: AllGFlags,
+    pub replication_factor: Option<i32>,
+    pub user_table_count: Option<usize>,
+}
+/// Wrapper struct for holding the differences.
+#[derive(Debug, Default)]
+pub struct AllSpecDrift {
+    pub drifts: Vec<SpecDrift>,
+}
@@ -0,0 +1,312 @@
+//! The impls and functions
+//!
+use std::{cmp::Ordering, collections::{BTreeMap, BTreeSet}, fs};
+use regex::Regex;
+use colored::*;
+use anyhow::{Context, Result};
+use crate::utility;
+use crate::snapshot;
+use crate::gflags::AllGFlags;
+use crate::loglines::{AllLogLines, LogLine};
+use crate::versions::AllVersions;
+use crate::diagnose::{AllSignatureMatches, Signature, SignatureMatch};
+use crate::Opts;
+
+/// The number of evidence log lines per server that is printed without --details-enable.
+const EVIDENCE_LOG_LINES: usize = 3;
+
+/// Read the signatures that are shipped with yb_stats, and add the signatures from the JSON file.
+/// A signature in the file with the same name as a shipped signature replaces it.
+pub fn read_signatures(
+    signatures_file: &Option<String>,
+) -> Result<Vec<Signature>>
+{
+    let mut signatures: Vec<Signature> = serde_json::from_str(include_str!("signatures.json"))
+        .with_context(|| "Error parsing the shipped signatures")?;
+    if let Some(signatures_file) = signatures_file
+    {
+        let file_signatures = fs::read_to_string(signatures_file)
+            .with_context(|| format!("Cannot read file: {}", signatures_file))?;
+        let file_signatures: Vec<Signature> = serde_json::from_str(&file_signatures)
+            .with_context(|| format!("Error parsing signatures: {}", signatures_file))?;
+        for file_signature in file_signatures
+        {
+            signatures.retain(|signature| signature.name != file_signature.name);
+            signatures.push(file_signature);
+        }
+    }
+    Ok(signatures)
+}
+
+/// Compare two version numbers, such as 2.17.0.0, numerically per component.
+pub fn compare_versions(
+    a: &str,
+    b: &str,
+) -> Ordering
+{
+    let components = |version: &str| -> Vec<u64> {
+        version.split(['.', '-'])
+            .map_while(|component| component.parse().ok())
+            .collect()
+    };
+    components(a).cmp(&components(b))
+}
+
+impl Signature {
+    /// True if the signature has no conditions, such a signature never matches.
+    fn has_no_conditions(&self) -> bool
+    {
+        self.log_pattern.is_none()
+            && self.version_min.is_none()
+            && self.version_max.is_none()
+            && self.gflags.is_empty()
+    }
+    /// Evaluate the signature for a single server, and return the evidence if all conditions match.
+    fn evaluate(
+        &self,
+        log_pattern: &Option<Regex>,
+        loglines: &[&LogLine],
+        version: Option<&String>,
+        gflags: &BTreeMap<&str, &str>,
+    ) -> Result<Option<Vec<String>>>
+    {
+        let mut evidence: Vec<String> = Vec::new();
+
+        if let Some(log_pattern) = log_pattern
+        {
+            let matching_loglines: Vec<&&LogLine> = loglines.iter().filter(|logline| log_pattern.is_match(&logline.message)).collect();
+            if matching_loglines.is_empty()
+            {
+                return Ok(None);
+            }
+            for logline in matching_loglines
+            {
+                evidence.push(format!("log: {} {} {} {}", logline.timestamp, logline.severity, logline.sourcefile_nr, logline.message.lines().next().unwrap_or_default().trim()));
+            }
+        }
+
+        if self.version_min.is_some() || self.version_max.is_some()
+        {
+            let Some(version) = version else { return Ok(None) };
+            if self.version_min.as_ref().map(|version_min| compare_versions(version, version_min).is_lt()).unwrap_or(false)
+                || self.version_max.as_ref().map(|version_max| compare_versions(version, version_max).is_gt()).unwrap_or(false)
+            {
+                return Ok(None);
+            }
+            evidence.push(format!("version: {}", version));
+        }
+
+        for gflag in &self.gflags
+        {
+            let value_regex = Regex::new(&gflag.value)
+                .with_context(|| format!("Invalid gflag value regex in signature {}: {}", self.name, gflag.value))?;
+            match gflags.get(gflag.name.as_str())
+            {
+                Some(value) if value_regex.is_match(value) => evidence.push(format!("gflag: {}={}", gflag.name, value)),
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Some(evidence))
+    }
+}
+
+impl AllSignatureMatches {
+    pub fn new() -> Self { Default::default() }
+    /// Match the signatures per server with the log lines, versions and gflags.
+    pub fn diagnose(
+        allloglines: &AllLogLines,
+        allversions: &AllVersions,
+        allgflags: &AllGFlags,
+        signatures: &[Signature],
+    ) -> Result<AllSignatureMatches>
+    {
+        let mut allsignaturematches = AllSignatureMatches::new();
+
+        let mut loglines: BTreeMap<&str, Vec<&LogLine>> = BTreeMap::new();
+        for logline in &allloglines.loglines
+        {
+            loglines.entry(logline.hostname_port.as_deref().expect("no hostname:port set")).or_default().push(logline);
+        }
+        let versions: BTreeMap<&str, &String> = allversions.versions
+            .iter()
+            .map(|version| (version.hostname_port.as_deref().expect("hostname:port should be set"), &version.version_number))
+            .collect();
+        let mut gflags: BTreeMap<&str, BTreeMap<&str, &str>> = BTreeMap::new();
+        for gflag in &allgflags.gflags
+        {
+            gflags.entry(gflag.hostname_port.as_deref().expect("hostname:port should be set")).or_default().insert(&gflag.name, &gflag.value);
+        }
+        let servers: BTreeSet<&str> = loglines.keys().chain(versions.keys()).chain(gflags.keys()).copied().collect();
+        let no_loglines: Vec<&LogLine> = Vec::new();
+        let no_gflags: BTreeMap<&str, &str> = BTreeMap::new();
+
+        for signature in signatures.iter().filter(|signature| !signature.has_no_conditions())
+        {
+            let log_pattern = signature.log_pattern
+                .as_ref()
+                .map(|log_pattern| Regex::new(log_pattern).with_context(|| format!("Invalid log pattern in signature {}: {}", signature.name, log_pattern)))
+                .transpose()?;
+            let mut signaturematch = SignatureMatch {
+                name: signature.name.clone(),
+                title: signature.title.clone(),
+                remediation: signature.remediation.clone(),
+                ..Default::default()
+            };
+            for server in &servers
+            {
+                if let Some(evidence) = signature.evaluate(
+                    &log_pattern,
+                    loglines.get(server).unwrap_or(&no_loglines),
+                    versions.get(server).copied(),
+                    gflags.get(server).unwrap_or(&no_gflags),
+                )?
+                {
+                    signaturematch.evidence.insert(server.to_string(), evidence);
+                }
+            }
+            if !signaturematch.evidence.is_empty()
+            {
+                allsignaturematches.matches.push(signaturematch);
+            }
+        }
+        Ok(allsignaturematches)
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        if self.matches.is_empty()
+        {
+            println!("{}", "No known issue signatures matched.".green());
+            return;
+        }
+        for signaturematch in &self.matches
+        {
+            let evidence: Vec<(&String, &Vec<String>)> = signaturematch.evidence
+                .iter()
+                .filter(|(hostname_port, _)| hostname_filter.is_match(hostname_port))
+                .collect();
+            if evidence.is_empty()
+            {
+                continue;
+            }
+            println!("{}", "-".repeat(120));
+            println!("{} {}", signaturematch.name.red(), signaturematch.title);
+            for (hostname_port, lines) in evidence
+            {
+                println!("  {}", hostname_port);
+                let log_lines = lines.iter().filter(|line| line.starts_with("log: ")).count();
+                let mut shown_log_lines = 0;
+                for line in lines
+                {
+                    if line.starts_with("log: ")
+                    {
+                        shown_log_lines += 1;
+                        if shown_log_lines > EVIDENCE_LOG_LINES && !*details_enable
+                        {
+                            continue;
+                        }
+                    }
+                    println!("    {}", line);
+                }
+                if log_lines > EVIDENCE_LOG_LINES && !*details_enable
+                {
+                    println!("    ... {} more log lines (use --details-enable to show all)", log_lines - EVIDENCE_LOG_LINES);
+                }
+            }
+            println!("  remediation: {}", signaturematch.remediation);
+        }
+    }
+}
+
+pub async fn print_diagnose(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let signatures = read_signatures(&options.diagnose_signatures)?;
+
+    let (allloglines, allversions, allgflags) = match options.diagnose.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allloglines = AllLogLines::new();
+            allloglines.loglines = snapshot::read_snapshot_json(snapshot_number, "loglines")?;
+            let mut allversions = AllVersions::new();
+            allversions.versions = snapshot::read_snapshot_json(snapshot_number, "versions")?;
+            let mut allgflags = AllGFlags::new();
+            allgflags.gflags = snapshot::read_snapshot_json(snapshot_number, "gflags")?;
+            (allloglines, allversions, allgflags)
+        },
+        None => {
+            (
+                AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await,
+                AllVersions::read_versions(&hosts, &ports, parallel).await,
+                AllGFlags::read_gflags(&hosts, &ports, parallel).await,
+            )
+        },
+    };
+
+    let allsignaturematches = AllSignatureMatches::diagnose(&allloglines, &allversions, &allgflags, &signatures)?;
+    allsignaturematches.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gflags::GFlag;
+    use crate::versions::Version;
+    use crate::diagnose::SignatureGFlag;
+
+    #[test]
+    fn unit_diagnose_signatures() {
+        // the shipped signatures must parse.
+        assert!(!read_signatures(&None).unwrap().is_empty());
+        assert!(compare_versions("2.17.10.0", "2.17.9.0").is_gt());
+        assert!(compare_versions("2.12.0.0-b1", "2.12.99.99").is_lt());
+
+        let mut allloglines = AllLogLines::new();
+        for hostname_port in ["192.168.66.80:9000", "192.168.66.81:9000"]
+        {
+            allloglines.loglines.push(LogLine {
+                hostname_port: Some(hostname_port.to_string()),
+                severity: "W".to_string(),
+                timestamp: "2023-02-03T11:13:22Z".parse().unwrap(),
+                sourcefile_nr: "log.cc:1234".to_string(),
+                message: "Time spent Fsync log took a long time: real 1.234s".to_string(),
+                ..Default::default()
+            });
+        }
+        let mut allversions = AllVersions::new();
+        allversions.versions.push(Version { hostname_port: Some("192.168.66.80:9000".to_string()), version_number: "2.14.1.0".to_string(), ..Default::default() });
+        allversions.versions.push(Version { hostname_port: Some("192.168.66.81:9000".to_string()), version_number: "2.17.0.0".to_string(), ..Default::default() });
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags.push(GFlag { hostname_port: Some("192.168.66.80:9000".to_string()), name: "durable_wal_write".to_string(), value: "true".to_string(), ..Default::default() });
+
+        let signatures = vec![Signature {
+            name: "slow-fsync-old-version".to_string(),
+            title: "Slow WAL fsync".to_string(),
+            log_pattern: Some("Fsync log took a long time".to_string()),
+            version_min: Some("2.14.0.0".to_string()),
+            version_max: Some("2.16.99.99".to_string()),
+            gflags: vec![SignatureGFlag { name: "durable_wal_write".to_string(), value: "^true$".to_string() }],
+            remediation: "Check the disk.".to_string(),
+        }];
+        let allsignaturematches = AllSignatureMatches::diagnose(&allloglines, &allversions, &allgflags, &signatures).unwrap();
+        assert_eq!(allsignaturematches.matches.len(), 1);
+        let evidence = &allsignaturematches.matches[0].evidence;
+        assert_eq!(evidence.len(), 1);
+        assert_eq!(evidence["192.168.66.80:9000"], vec![
+            "log: 2023-02-03 11:13:22 UTC W log.cc:1234 Time spent Fsync log took a long time: real 1.234s".to_string(),
+            "version: 2.14.1.0".to_string(),
+            "gflag: durable_wal_write=true".to_string(),
+        ]);
+    }
+}
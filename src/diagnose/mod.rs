@@ -0,0 +1,28 @@
+//! Module for matching a snapshot or the live cluster with known issue signatures (`--diagnose`).
+//!
+//! A signature is a log message pattern, a version range and a gflag combination, which all must match on a server.
+//! The signatures are shipped with yb_stats (`src/diagnose/signatures.json`),
+//! and can be extended or overridden (by signature name) with a JSON file (`--diagnose-signatures <file>`):
+//! ```json
+//! [
+//!   {
+//!     "name": "slow-wal-fsync",
+//!     "title": "Slow WAL fsync",
+//!     "log_pattern": "Fsync log took a long time",
+//!     "version_min": "2.14.0.0",
+//!     "version_max": "2.16.99.99",
+//!     "gflags": [ { "name": "durable_wal_write", "value": "^true$" } ],
+//!     "remediation": "The disk that holds the WAL is slow."
+//!   }
+//! ]
+//! ```
+//! The log_pattern and the gflag values are regexes, the version range is inclusive, and every condition is optional.
+//!
+//! The diagnose functionality is called from:
+//! - [crate] -> [print_diagnose] (diagnose a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
@@ -0,0 +1,45 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+
+/// A known issue signature.
+///
+/// The shipped signatures are in `src/diagnose/signatures.json`, additional signatures are read from the `--diagnose-signatures` JSON file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Signature {
+    pub name: String,
+    pub title: String,
+    /// Regex for a log message.
+    #[serde(default)]
+    pub log_pattern: Option<String>,
+    /// The lowest matching version, inclusive.
+    #[serde(default)]
+    pub version_min: Option<String>,
+    /// The highest matching version, inclusive.
+    #[serde(default)]
+    pub version_max: Option<String>,
+    /// The gflags that all must match.
+    #[serde(default)]
+    pub gflags: Vec<SignatureGFlag>,
+    pub remediation: String,
+}
+/// A gflag of a signature, with a regex for the value.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignatureGFlag {
+    pub name: String,
+    pub value: String,
+}
+/// A signature that matched, with the evidence per server.
+#[derive(Debug, Default)]
+pub struct SignatureMatch {
+    pub name: String,
+    pub title: String,
+    pub remediation: String,
+    /// hostname_port, evidence lines
+    pub evidence: BTreeMap<String, Vec<String>>,
+}
+/// Wrapper struct for holding the signature matches.
+#[derive(Debug, Default)]
+pub struct AllSignatureMatches {
+    pub matches: Vec<SignatureMatch>,
+}
@@ -134,7 +134,7 @@
 //! to: .colocation.parent.uuid & .colocation.parent.tablename
 //!
 use chrono::Local;
-use std::{time::Instant, sync::mpsc::channel};
+use std::{time::Instant, sync::mpsc::channel, collections::BTreeMap};
 use log::*;
 use regex::Regex;
 use colored::*;
@@ -142,7 +142,9 @@ use anyhow::{Result, bail};
 use crate::isleader::AllIsLeader;
 use crate::utility;
 use crate::snapshot;
-use crate::entities::{Entities, AllEntities, EntitiesDiff, KeyspaceDiff, TablesDiff, TabletsDiff, ReplicasDiff};
+use crate::entities::{Entities, AllEntities, EntitiesDiff, KeyspaceDiff, TablesDiff, TabletsDiff, ReplicasDiff, Balance, BalanceFields, Skew, Tables, LeaderChurn, TabletChurn};
+use crate::tablet_servers::AllTabletServers;
+use crate::clocks::AllClocks;
 use crate::health_check::AllHealthCheck;
 use crate::Opts;
 
@@ -150,6 +152,36 @@ impl Entities {
     fn new() -> Self {
         Default::default()
     }
+    /// True for a user table or index: a table that is not in a system keyspace,
+    /// and for ysql, that has an oid of 16384 or higher (lower oid numbers are catalog tables).
+    pub fn is_user_table(&self, table: &Tables) -> bool
+    {
+        let is_system_keyspace_name = |keyspace_name: &str| -> bool {
+            matches!(keyspace_name, "system" | "system_schema" | "system_auth" | "template0" | "template1" | "system_platform")
+        };
+        let object_oid_number = |oid: &str| -> u32 {
+            if oid.len() == 32_usize {
+                u32::from_str_radix(&oid[24..], 16).unwrap_or_default()
+            } else {
+                0
+            }
+        };
+        match self.keyspaces.iter().find(|keyspace| keyspace.keyspace_id == table.keyspace_id)
+        {
+            Some(keyspace) if is_system_keyspace_name(&keyspace.keyspace_name) => false,
+            Some(keyspace) if keyspace.keyspace_type == "ysql" => object_oid_number(&table.table_id) >= 16384,
+            Some(_) => true,
+            None => false,
+        }
+    }
+    /// The number of user tables and indexes.
+    pub fn user_table_count(&self) -> usize
+    {
+        self.tables
+            .iter()
+            .filter(|table| self.is_user_table(table))
+            .count()
+    }
 }
 
 impl AllEntities
@@ -1566,6 +1598,378 @@ pub async fn print_entities(
     Ok(())
 }
 
+/// The min, max, mean and standard deviation of counts.
+pub fn skew(
+    values: &[f64],
+) -> Skew
+{
+    if values.is_empty()
+    {
+        return Skew::default();
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    Skew {
+        min: values.iter().copied().fold(f64::INFINITY, f64::min),
+        max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        mean,
+        stddev: (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt(),
+    }
+}
+
+impl Balance {
+    pub fn new() -> Self { Default::default() }
+    /// Count the leaders and replicas of the running user tablets per tablet server, for the whole cluster and per table.
+    /// The entities and tablet servers of the master leader are used.
+    /// A replica is matched with its tablet server on the server uuid, using the tablet server uuids of the clocks.
+    pub fn new_from_data(
+        allentities: &AllEntities,
+        alltabletservers: &AllTabletServers,
+        allclocks: &AllClocks,
+        master_leader: &str,
+    ) -> Balance
+    {
+        let mut balance = Balance::new();
+        let tablet_servers = allclocks.tablet_server_uuids();
+        // server_uuid, addr
+        let mut unmatched_replicas: BTreeMap<String, String> = BTreeMap::new();
+
+        for tabletserver in alltabletservers.tabletservers
+            .iter()
+            .filter(|tabletserver| tabletserver.hostname_port.as_deref() == Some(master_leader))
+        {
+            let tablet_server_hostname_port = tabletserver.tablet_server_hostname_port.clone().expect("tablet server hostname port should be set");
+            balance.cluster.insert(tablet_server_hostname_port, BalanceFields {
+                zone: format!("{}.{}.{}", tabletserver.cloud, tabletserver.region, tabletserver.zone),
+                read_ops_per_sec: tabletserver.read_ops_per_sec,
+                write_ops_per_sec: tabletserver.write_ops_per_sec,
+                ..Default::default()
+            });
+        }
+
+        for entities in allentities.entities
+            .iter()
+            .filter(|entities| entities.hostname_port.as_deref() == Some(master_leader))
+        {
+            let user_tables: BTreeMap<&String, (String, String)> = entities.tables
+                .iter()
+                .filter(|table| entities.is_user_table(table))
+                .map(|table| {
+                    let keyspace_name = entities.keyspaces.iter().find(|keyspace| keyspace.keyspace_id == table.keyspace_id).map(|keyspace| keyspace.keyspace_name.clone()).unwrap_or_default();
+                    (&table.table_id, (keyspace_name, table.table_name.clone()))
+                })
+                .collect();
+            for tablet in entities.tablets.iter().filter(|tablet| tablet.state == "RUNNING")
+            {
+                let Some(table) = user_tables.get(&tablet.table_id) else { continue };
+                // every tablet server is in the table balance, also without replicas of the table.
+                let table_balance = balance.tables
+                    .entry(table.clone())
+                    .or_insert_with(|| balance.cluster.iter().map(|(hostname_port, fields)| (hostname_port.clone(), BalanceFields { leaders: 0, replicas: 0, ..fields.clone() })).collect());
+                for replica in tablet.replicas.as_deref().unwrap_or_default()
+                {
+                    let tablet_server = match tablet_servers.get(&replica.server_uuid)
+                    {
+                        Some(tablet_server_hostname_port) => tablet_server_hostname_port.clone(),
+                        None => {
+                            unmatched_replicas.insert(replica.server_uuid.clone(), replica.addr.clone());
+                            replica.addr.clone()
+                        },
+                    };
+                    let is_leader = tablet.leader.as_deref() == Some(replica.server_uuid.as_str());
+                    for fields in [balance.cluster.entry(tablet_server.clone()).or_default(), table_balance.entry(tablet_server).or_default()]
+                    {
+                        fields.replicas += 1;
+                        if is_leader
+                        {
+                            fields.leaders += 1;
+                        }
+                    }
+                }
+            }
+        }
+        for (server_uuid, addr) in unmatched_replicas
+        {
+            warn!("Replica server {} ({}) not found in the tablet servers, it is counted by its addr", server_uuid, addr);
+        }
+        balance
+    }
+    /// Sum the counts and operations per zone.
+    fn per_zone(
+        per_tablet_server: &BTreeMap<String, BalanceFields>,
+    ) -> BTreeMap<String, BalanceFields>
+    {
+        let mut per_zone: BTreeMap<String, BalanceFields> = BTreeMap::new();
+        for fields in per_tablet_server.values()
+        {
+            let zone = per_zone.entry(fields.zone.clone()).or_default();
+            zone.zone = fields.zone.clone();
+            zone.leaders += fields.leaders;
+            zone.replicas += fields.replicas;
+            zone.read_ops_per_sec += fields.read_ops_per_sec;
+            zone.write_ops_per_sec += fields.write_ops_per_sec;
+        }
+        per_zone
+    }
+    fn print_skew(
+        title: &str,
+        per_server: &BTreeMap<String, BalanceFields>,
+    )
+    {
+        let leaders = skew(&per_server.values().map(|fields| fields.leaders as f64).collect::<Vec<_>>());
+        let replicas = skew(&per_server.values().map(|fields| fields.replicas as f64).collect::<Vec<_>>());
+        let stddev_pct = |skew: &Skew| if skew.mean > 0_f64 { skew.stddev / skew.mean * 100_f64 } else { 0_f64 };
+        let leaders_stddev = format!("{:8.2} ({:5.1}%)", leaders.stddev, stddev_pct(&leaders));
+        println!("{:20} leaders  min {:6} max {:6} stddev {} replicas min {:6} max {:6} stddev {:8.2} ({:5.1}%)",
+                 title,
+                 leaders.min,
+                 leaders.max,
+                 if stddev_pct(&leaders) > 10_f64 { leaders_stddev.yellow() } else { leaders_stddev.normal() },
+                 replicas.min,
+                 replicas.max,
+                 replicas.stddev,
+                 stddev_pct(&replicas),
+        );
+    }
+    fn print_rows(
+        per_server: &BTreeMap<String, BalanceFields>,
+    )
+    {
+        println!("{:30} {:30} {:>8} {:>8} {:>12} {:>12}", "hostname_port", "zone", "leaders", "replicas", "read ops/s", "write ops/s");
+        for (hostname_port, fields) in per_server
+        {
+            println!("{:30} {:30} {:8} {:8} {:12.2} {:12.2}", hostname_port, fields.zone, fields.leaders, fields.replicas, fields.read_ops_per_sec, fields.write_ops_per_sec);
+        }
+    }
+    /// Print the balance for the whole cluster per tablet server and per zone, and the skew per table.
+    /// With details_enable, the leaders and replicas per tablet server are printed for every table too.
+    pub fn print(
+        &self,
+        table_name_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        println!("{}", "Cluster (user tables)".bold());
+        Balance::print_rows(&self.cluster);
+        Balance::print_skew("per tablet server", &self.cluster);
+        let per_zone = Balance::per_zone(&self.cluster);
+        println!();
+        Balance::print_rows(&per_zone);
+        Balance::print_skew("per zone", &per_zone);
+
+        for ((keyspace_name, table_name), per_server) in self.tables
+            .iter()
+            .filter(|((_, table_name), _)| table_name_filter.is_match(table_name))
+        {
+            println!();
+            println!("{}", format!("Table {}.{}", keyspace_name, table_name).bold());
+            if *details_enable
+            {
+                Balance::print_rows(per_server);
+            }
+            Balance::print_skew("per tablet server", per_server);
+            Balance::print_skew("per zone", &Balance::per_zone(per_server));
+        }
+    }
+}
+
+pub async fn print_balance(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+
+    let balance = match options.print_balance.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allentities = AllEntities::new();
+            allentities.entities = snapshot::read_snapshot_json(snapshot_number, "entities")?;
+            let mut alltabletservers = AllTabletServers::new();
+            alltabletservers.tabletservers = snapshot::read_snapshot_json(snapshot_number, "tablet_servers")?;
+            let mut allclocks = AllClocks::new();
+            allclocks.clocks = snapshot::read_snapshot_json(snapshot_number, "clocks")?;
+            let leader_hostname = AllIsLeader::return_leader_snapshot(snapshot_number)?;
+            Balance::new_from_data(&allentities, &alltabletservers, &allclocks, &leader_hostname)
+        },
+        None => {
+            let allentities = AllEntities::read_entities(&hosts, &ports, parallel).await;
+            let alltabletservers = AllTabletServers::read_tabletservers(&hosts, &ports, parallel).await;
+            let allclocks = AllClocks::read_clocks(&hosts, &ports, parallel).await?;
+            let leader_hostname = AllIsLeader::return_leader_http(&hosts, &ports, parallel).await;
+            Balance::new_from_data(&allentities, &alltabletservers, &allclocks, &leader_hostname)
+        },
+    };
+    balance.print(&table_name_filter, &options.details_enable);
+
+    Ok(())
+}
+
+impl Entities {
+    /// The leader (replica addr) and table (keyspace_name.table_name) of the running tablets that have a leader.
+    fn tablet_leaders(&self) -> BTreeMap<&String, (String, String)>
+    {
+        let table_names: BTreeMap<&String, String> = self.tables
+            .iter()
+            .map(|table| {
+                let keyspace_name = self.keyspaces.iter().find(|keyspace| keyspace.keyspace_id == table.keyspace_id).map(|keyspace| keyspace.keyspace_name.as_str()).unwrap_or_default();
+                (&table.table_id, format!("{}.{}", keyspace_name, table.table_name))
+            })
+            .collect();
+        self.tablets
+            .iter()
+            .filter(|tablet| tablet.state == "RUNNING")
+            .filter_map(|tablet| {
+                let leader = tablet.leader.as_ref().filter(|leader| !leader.is_empty())?;
+                let leader_addr = tablet.replicas
+                    .as_deref()
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|replica| replica.server_uuid == *leader)
+                    .map(|replica| replica.addr.clone())
+                    .unwrap_or_else(|| leader.clone());
+                let table_name = table_names.get(&tablet.table_id).cloned().unwrap_or_else(|| tablet.table_id.clone());
+                Some((&tablet.tablet_id, (leader_addr, table_name)))
+            })
+            .collect()
+    }
+}
+
+impl LeaderChurn {
+    pub fn new() -> Self { Default::default() }
+    /// Count the leader changes of the tablets between two consecutive snapshots.
+    pub fn add_snapshot_pair(
+        &mut self,
+        first: &Entities,
+        second: &Entities,
+    )
+    {
+        self.snapshot_pairs += 1;
+        let first_leaders = first.tablet_leaders();
+        for (tablet_id, (second_leader, table_name)) in second.tablet_leaders()
+        {
+            let tabletchurn = self.tablets
+                .entry(tablet_id.clone())
+                .or_insert_with(|| TabletChurn { table: table_name.clone(), ..Default::default() });
+            if let Some((first_leader, _)) = first_leaders.get(tablet_id)
+            {
+                if tabletchurn.leaders.is_empty()
+                {
+                    tabletchurn.leaders.push(first_leader.clone());
+                }
+                if *first_leader != second_leader
+                {
+                    tabletchurn.changes += 1;
+                    *self.tables.entry(table_name).or_default() += 1;
+                    self.servers.entry(first_leader.clone()).or_default().lost += 1;
+                    self.servers.entry(second_leader.clone()).or_default().gained += 1;
+                }
+            }
+            if tabletchurn.leaders.last() != Some(&second_leader)
+            {
+                tabletchurn.leaders.push(second_leader);
+            }
+        }
+    }
+    /// Walk every snapshot between begin and end, and compare the entities of the master leader of each consecutive pair.
+    /// A snapshot without a master leader or its entities is skipped.
+    pub fn read_snapshot_range(
+        begin_snapshot: &str,
+        end_snapshot: &str,
+    ) -> Result<LeaderChurn>
+    {
+        let mut leaderchurn = LeaderChurn::new();
+        let mut previous: Option<Entities> = None;
+
+        for snapshot_number in snapshot::Snapshot::read_snapshot_range(begin_snapshot, end_snapshot)?
+        {
+            // a snapshot can be taken without some data, such as a snapshot with imported log lines.
+            let master_leader = match AllIsLeader::return_leader_snapshot(&snapshot_number)
+            {
+                Ok(master_leader) => master_leader,
+                Err(error) => {
+                    warn!("snapshot {}: no master leader found, skipped: {}", snapshot_number, error);
+                    continue;
+                },
+            };
+            let entities: Vec<Entities> = snapshot::read_snapshot_json(&snapshot_number, "entities").unwrap_or_default();
+            let Some(entities) = entities.into_iter().find(|entities| entities.hostname_port.as_deref() == Some(master_leader.as_str())) else {
+                warn!("snapshot {}: no entities of the master leader found, skipped", snapshot_number);
+                continue;
+            };
+            if let Some(previous_entities) = &previous
+            {
+                leaderchurn.add_snapshot_pair(previous_entities, &entities);
+            }
+            previous = Some(entities);
+        }
+        Ok(leaderchurn)
+    }
+    /// Print the leader changes per tablet server, per table, and the tablets that changed leader more than flap_threshold times.
+    /// With details_enable, all tablets with leader changes are printed.
+    pub fn print(
+        &self,
+        table_name_filter: &Regex,
+        flap_threshold: usize,
+        details_enable: &bool,
+    )
+    {
+        println!("Leader changes over {} snapshot pairs", self.snapshot_pairs);
+        println!();
+        println!("{:30} {:>8} {:>8}", "server", "lost", "gained");
+        for (server, serverchurn) in &self.servers
+        {
+            println!("{:30} {:8} {:8}", server, serverchurn.lost, serverchurn.gained);
+        }
+
+        println!();
+        println!("{:60} {:>8}", "table", "changes");
+        for (table, changes) in self.tables.iter().filter(|(table, _)| table_name_filter.is_match(table))
+        {
+            println!("{:60} {:8}", table, changes);
+        }
+
+        println!();
+        println!("{:32} {:40} {:>8} leaders", "tablet", "table", "changes");
+        let mut tablets: Vec<(&String, &TabletChurn)> = self.tablets
+            .iter()
+            .filter(|(_, tabletchurn)| tabletchurn.changes > 0)
+            .filter(|(_, tabletchurn)| table_name_filter.is_match(&tabletchurn.table))
+            .filter(|(_, tabletchurn)| *details_enable || tabletchurn.changes > flap_threshold)
+            .collect();
+        tablets.sort_by_key(|(_, tabletchurn)| std::cmp::Reverse(tabletchurn.changes));
+        for (tablet_id, tabletchurn) in tablets
+        {
+            let changes = format!("{:8}", tabletchurn.changes);
+            println!("{:32} {:40} {} {}",
+                     tablet_id,
+                     tabletchurn.table,
+                     if tabletchurn.changes > flap_threshold { changes.red() } else { changes.normal() },
+                     tabletchurn.leaders.join(" > "),
+            );
+        }
+    }
+}
+
+pub async fn leader_churn(
+    options: &Opts,
+) -> Result<()>
+{
+    info!("leader churn");
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let leaderchurn = LeaderChurn::read_snapshot_range(&begin_snapshot, &end_snapshot)?;
+    leaderchurn.print(&table_name_filter, options.leader_flap_threshold, &options.details_enable);
+
+    Ok(())
+}
+
 pub async fn print_coloc_leader_host(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -1585,6 +1989,7 @@ pub async fn print_coloc_leader_host(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clocks::Clocks;
 
     #[test]
     fn unit_parse_simple_entities_dump() {
@@ -1646,6 +2051,92 @@ mod tests {
         assert_eq!(result.tablets[1].leader.as_ref().unwrap(),"a3f5a16532bb4ed4a061e794831168f8");
     }
 
+    #[test]
+    fn unit_balance() {
+        let json = r#"
+{
+  "keyspaces": [ { "keyspace_id": "3e7e4b6a0e9d4c5e8f2a1b0c9d8e7f6a", "keyspace_name": "test", "keyspace_type": "ycql" } ],
+  "tables": [ { "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "keyspace_id": "3e7e4b6a0e9d4c5e8f2a1b0c9d8e7f6a", "table_name": "t", "state": "RUNNING" } ],
+  "tablets": [
+    { "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "tablet_id": "235b5b031f094ec3bf6be2a023abebba", "state": "RUNNING",
+      "replicas": [ { "type": "VOTER", "server_uuid": "a3f5a16532bb4ed4a061e794831168f8", "addr": "yb-1.local:9100" },
+                    { "type": "VOTER", "server_uuid": "e7a4a66ae7f94eb6a75b0ce3a90ab5ba", "addr": "yb-2.local:9100" } ],
+      "leader": "a3f5a16532bb4ed4a061e794831168f8" },
+    { "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "tablet_id": "5b6fd994d7e34504ac48a5e653456704", "state": "RUNNING",
+      "replicas": [ { "type": "VOTER", "server_uuid": "a3f5a16532bb4ed4a061e794831168f8", "addr": "yb-1.local:9100" },
+                    { "type": "VOTER", "server_uuid": "e7a4a66ae7f94eb6a75b0ce3a90ab5ba", "addr": "yb-2.local:9100" } ],
+      "leader": "a3f5a16532bb4ed4a061e794831168f8" }
+  ]
+}
+        "#.to_string();
+        let mut allentities = AllEntities::new();
+        allentities.entities.push(AllEntities::parse_entities(json, "yb-1.local", "7000"));
+        allentities.entities[0].hostname_port = Some("yb-1.local:7000".to_string());
+        let mut alltabletservers = AllTabletServers::new();
+        for (hostname_port, zone) in [("yb-1.local:9000", "zone1"), ("yb-2.local:9000", "zone2"), ("yb-2.local:9001", "zone2")]
+        {
+            alltabletservers.tabletservers.push(serde_json::from_value(serde_json::json!({
+                "hostname_port": "yb-1.local:7000", "tablet_server_hostname_port": hostname_port,
+                "time_since_hb": "0.5s", "time_since_hb_sec": 0.5, "status": "ALIVE", "uptime_seconds": 45,
+                "ram_used": "26.21 MB", "ram_used_bytes": 26214400, "num_sst_files": 0,
+                "total_sst_file_size": "0 B", "total_sst_file_size_bytes": 0, "uncompressed_sst_file_size": "0 B", "uncompressed_sst_file_size_bytes": 0,
+                "path_metrics": [], "read_ops_per_sec": 10.0, "write_ops_per_sec": 5.0,
+                "user_tablets_total": 0, "user_tablets_leaders": 0, "system_tablets_total": 0, "system_tablets_leaders": 0, "active_tablets": 0,
+                "cloud": "local", "region": "local", "zone": zone
+            })).unwrap());
+        }
+
+        // the tablet servers yb-2.local:9000 and yb-2.local:9001 share a host, and are matched on the server uuid.
+        let mut allclocks = AllClocks::new();
+        for server in ["yb-1.local:9000 a3f5a16532bb4ed4a061e794831168f8", "yb-2.local:9000 e7a4a66ae7f94eb6a75b0ce3a90ab5ba", "yb-2.local:9001 5c6a7f4d2e1b4c3a9d8e7f6a5b4c3d2e"]
+        {
+            allclocks.clocks.push(Clocks { hostname_port: Some("yb-1.local:7000".to_string()), server: server.to_string(), ..Default::default() });
+        }
+
+        let balance = Balance::new_from_data(&allentities, &alltabletservers, &allclocks, "yb-1.local:7000");
+        assert_eq!(balance.cluster["yb-1.local:9000"].leaders, 2);
+        assert_eq!(balance.cluster["yb-2.local:9000"].replicas, 2);
+        assert_eq!(balance.cluster["yb-2.local:9001"].replicas, 0);
+        let per_tablet_server = &balance.tables[&("test".to_string(), "t".to_string())];
+        assert_eq!(skew(&per_tablet_server.values().map(|fields| fields.leaders as f64).collect::<Vec<_>>()).max, 2_f64);
+        let per_zone = Balance::per_zone(&balance.cluster);
+        assert_eq!(per_zone["local.local.zone2"].replicas, 2);
+        assert_eq!(per_zone["local.local.zone2"].read_ops_per_sec, 20_f64);
+        assert_eq!(skew(&[1_f64, 3_f64]), Skew { min: 1_f64, max: 3_f64, mean: 2_f64, stddev: 1_f64 });
+    }
+
+    #[test]
+    fn unit_leader_churn() {
+        let entities = |leader: &str| -> Entities {
+            let json = format!(r#"
+{{
+  "keyspaces": [ {{ "keyspace_id": "3e7e4b6a0e9d4c5e8f2a1b0c9d8e7f6a", "keyspace_name": "test", "keyspace_type": "ycql" }} ],
+  "tables": [ {{ "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "keyspace_id": "3e7e4b6a0e9d4c5e8f2a1b0c9d8e7f6a", "table_name": "t", "state": "RUNNING" }} ],
+  "tablets": [
+    {{ "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "tablet_id": "235b5b031f094ec3bf6be2a023abebba", "state": "RUNNING",
+      "replicas": [ {{ "type": "VOTER", "server_uuid": "a3f5a16532bb4ed4a061e794831168f8", "addr": "yb-1.local:9100" }},
+                    {{ "type": "VOTER", "server_uuid": "e7a4a66ae7f94eb6a75b0ce3a90ab5ba", "addr": "yb-2.local:9100" }} ],
+      "leader": "{}" }}
+  ]
+}}
+            "#, leader);
+            AllEntities::parse_entities(json, "yb-1.local", "7000")
+        };
+        let mut leaderchurn = LeaderChurn::new();
+        leaderchurn.add_snapshot_pair(&entities("a3f5a16532bb4ed4a061e794831168f8"), &entities("e7a4a66ae7f94eb6a75b0ce3a90ab5ba"));
+        leaderchurn.add_snapshot_pair(&entities("e7a4a66ae7f94eb6a75b0ce3a90ab5ba"), &entities("e7a4a66ae7f94eb6a75b0ce3a90ab5ba"));
+        leaderchurn.add_snapshot_pair(&entities("e7a4a66ae7f94eb6a75b0ce3a90ab5ba"), &entities("a3f5a16532bb4ed4a061e794831168f8"));
+
+        assert_eq!(leaderchurn.snapshot_pairs, 3);
+        let tabletchurn = &leaderchurn.tablets["235b5b031f094ec3bf6be2a023abebba"];
+        assert_eq!(tabletchurn.changes, 2);
+        assert_eq!(tabletchurn.table, "test.t");
+        assert_eq!(tabletchurn.leaders, vec!["yb-1.local:9100", "yb-2.local:9100", "yb-1.local:9100"]);
+        assert_eq!(leaderchurn.tables["test.t"], 2);
+        assert_eq!(leaderchurn.servers["yb-1.local:9100"].lost, 1);
+        assert_eq!(leaderchurn.servers["yb-1.local:9100"].gained, 1);
+    }
+
     #[test]
     fn integration_parse_entities() {
         let hostname = utility::get_hostname_master();
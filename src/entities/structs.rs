@@ -143,4 +143,62 @@ pub struct ReplicasDiff {
     pub first_addr: String,
     pub second_replica_type: String,
     pub second_addr: String,
-}
\ No newline at end of file
+}
+// balance
+/// The leaders and replicas of a tablet server (or zone), with the read and write operations per second of the tablet server.
+#[derive(Debug, Default, Clone)]
+pub struct BalanceFields {
+    pub zone: String,
+    pub leaders: usize,
+    pub replicas: usize,
+    pub read_ops_per_sec: f64,
+    pub write_ops_per_sec: f64,
+}
+/// The leader and replica balance of the user tablets.
+///
+/// The tablet servers are identified by their http hostname:port from `/api/v1/tablet-servers`,
+/// a replica is matched on its server uuid, and a replica of an unknown server is identified by its addr (rpc port).
+#[derive(Debug, Default)]
+pub struct Balance {
+    /// tablet server hostname_port, the fields for the whole cluster.
+    pub cluster: BTreeMap<String, BalanceFields>,
+    /// (keyspace_name, table_name), tablet server hostname_port, the fields for the table.
+    pub tables: BTreeMap<(String, String), BTreeMap<String, BalanceFields>>,
+}
+/// The skew of a count between tablet servers or zones.
+#[derive(Debug, Default, PartialEq)]
+pub struct Skew {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// The population standard deviation.
+    pub stddev: f64,
+}
+// leader churn
+/// The leader changes across a snapshot range: per tablet, per table and per tablet server.
+#[derive(Debug, Default)]
+pub struct LeaderChurn {
+    /// The number of snapshot pairs that are compared.
+    pub snapshot_pairs: usize,
+    /// tablet_id, churn of the tablet.
+    pub tablets: BTreeMap<String, TabletChurn>,
+    /// keyspace_name.table_name, number of leader changes.
+    pub tables: BTreeMap<String, usize>,
+    /// replica addr, churn of the server.
+    pub servers: BTreeMap<String, ServerChurn>,
+}
+/// The leader changes of a tablet.
+#[derive(Debug, Default)]
+pub struct TabletChurn {
+    /// keyspace_name.table_name
+    pub table: String,
+    pub changes: usize,
+    /// The leader (replica addr) in every snapshot in which the tablet is found, consecutive duplicates removed.
+    pub leaders: Vec<String>,
+}
+/// The number of times a tablet server lost and gained leadership of a tablet.
+#[derive(Debug, Default)]
+pub struct ServerChurn {
+    pub lost: usize,
+    pub gained: usize,
+}
@@ -1,14 +1,34 @@
 //! The impls and functions
 //!
-use std::{time::Instant, sync::mpsc::channel};
+use std::{collections::{BTreeMap, BTreeSet}, time::Instant, sync::mpsc::channel};
 use chrono::Local;
 use regex::Regex;
+use colored::*;
 use log::*;
 use anyhow::Result;
 use crate::Opts;
 use crate::utility;
 use crate::snapshot;
-use crate::gflags::{AllGFlags, GFlag};
+use crate::gflags::{AllGFlags, GFlag, GFlagsDiff, GFlagsDiffFields, GFlagsDrift};
+
+/// The gflags that are different per server by nature, such as addresses, UUIDs and paths.
+/// These are ignored for the drift report, in addition to the gflags matching `--gflags-ignore`.
+const GFLAGS_DRIFT_IGNORE: &[&str] = &[
+    r"_addresses?$",
+    r"_addrs$",
+    r"^rpc_bind",
+    r"^server_broadcast",
+    r"^webserver_interface$",
+    r"_bind_address$",
+    r"^local_ip_for_outbound_sockets$",
+    r"_uuid",
+    r"_dirs?$",
+    r"_path$",
+    r"_file$",
+    r"^placement_",
+    r"^metric_node_name$",
+    r"^hostname",
+];
 
 impl AllGFlags {
     pub fn new() -> Self { Default::default() }
@@ -121,6 +141,232 @@ impl AllGFlags {
     }
 }
 
+impl AllGFlags {
+    /// The role of the server of a hostname_port: master or tserver.
+    /// The role is determined by the log_filename gflag, which is set to the name of the executable.
+    pub fn roles(&self) -> BTreeMap<String, String>
+    {
+        self.gflags
+            .iter()
+            .filter(|gflag| gflag.name == "log_filename")
+            .map(|gflag| {
+                let role = if gflag.value.contains("master") { "master" } else if gflag.value.contains("tserver") { "tserver" } else { "unknown" };
+                (gflag.hostname_port.clone().expect("hostname:port should be set"), role.to_string())
+            })
+            .collect()
+    }
+}
+
+impl GFlagsDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<GFlagsDiff>
+    {
+        let mut gflagsdiff = GFlagsDiff::new();
+
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags = snapshot::read_snapshot_json(begin_snapshot, "gflags")?;
+        gflagsdiff.first_snapshot(allgflags);
+
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags = snapshot::read_snapshot_json(end_snapshot, "gflags")?;
+        gflagsdiff.second_snapshot(allgflags);
+
+        Ok(gflagsdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allgflags: AllGFlags,
+    )
+    {
+        for gflag in allgflags.gflags
+        {
+            self.btreegflagsdiff
+                .entry((gflag.hostname_port.clone().expect("hostname:port should be set"), gflag.name.clone()))
+                .and_modify(|_| error!("Duplicate hostname:port + gflag name entry: {}, {}", gflag.hostname_port.clone().expect("hostname:port should be set"), gflag.name))
+                .or_insert( GFlagsDiffFields {
+                    first_value: Some(gflag.value.clone()),
+                    ..Default::default()
+                });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allgflags: AllGFlags,
+    )
+    {
+        for gflag in allgflags.gflags
+        {
+            self.btreegflagsdiff
+                .entry((gflag.hostname_port.clone().expect("hostname:port should be set"), gflag.name.clone()))
+                .or_default()
+                .second_value = Some(gflag.value);
+        }
+    }
+    /// Print the gflags that changed value, appeared or disappeared between the snapshots.
+    /// If a server is only found in one of the snapshots, the gflags of it are not printed.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+    )
+    {
+        let first_hostname_ports: BTreeSet<_> = self.btreegflagsdiff.iter().filter(|(_, row)| row.first_value.is_some()).map(|((hostname_port, _), _)| hostname_port).collect();
+        let second_hostname_ports: BTreeSet<_> = self.btreegflagsdiff.iter().filter(|(_, row)| row.second_value.is_some()).map(|((hostname_port, _), _)| hostname_port).collect();
+
+        for ((hostname_port, name), row) in self.btreegflagsdiff
+            .iter()
+            .filter(|((hostname_port, name), _)| hostname_filter.is_match(hostname_port) && stat_name_filter.is_match(name))
+            .filter(|((hostname_port, _), _)| first_hostname_ports.contains(hostname_port) && second_hostname_ports.contains(hostname_port))
+        {
+            match (&row.first_value, &row.second_value)
+            {
+                (Some(first_value), Some(second_value)) if first_value != second_value => {
+                    println!("{} {:20} GFlags: {:50} {}->{}", "=".to_string().yellow(), hostname_port, name, first_value.yellow(), second_value.yellow());
+                },
+                (Some(first_value), None) => {
+                    println!("{} {:20} GFlags: {:50} {}", "-".to_string().red(), hostname_port, name, first_value);
+                },
+                (None, Some(second_value)) => {
+                    println!("{} {:20} GFlags: {:50} {}", "+".to_string().green(), hostname_port, name, second_value);
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+impl GFlagsDrift {
+    pub fn new() -> Self { Default::default() }
+    /// Group the servers by role, and find the gflags that have more than one value within a role.
+    /// A gflag that is not set on all servers of a role is reported as "(absent)" for the servers that lack it.
+    /// The gflags matching the ignore list are skipped.
+    pub fn new_from_gflags(
+        allgflags: &AllGFlags,
+        ignore_gflags: &[Regex],
+    ) -> GFlagsDrift
+    {
+        let roles = allgflags.roles();
+        let role_of = |hostname_port: &String| roles.get(hostname_port).cloned().unwrap_or_else(|| "unknown".to_string());
+
+        let mut hostname_ports_per_role: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut values: BTreeMap<(String, String), BTreeMap<String, Vec<String>>> = BTreeMap::new();
+        for gflag in &allgflags.gflags
+        {
+            let hostname_port = gflag.hostname_port.clone().expect("hostname:port should be set");
+            let role = role_of(&hostname_port);
+            hostname_ports_per_role.entry(role.clone()).or_default().insert(hostname_port.clone());
+            if ignore_gflags.iter().any(|ignore| ignore.is_match(&gflag.name))
+            {
+                continue;
+            }
+            values.entry((role, gflag.name.clone()))
+                .or_default()
+                .entry(gflag.value.clone())
+                .or_default()
+                .push(hostname_port);
+        }
+
+        let mut gflagsdrift = GFlagsDrift::new();
+        for ((role, name), mut value_hostname_ports) in values
+        {
+            let found: usize = value_hostname_ports.values().map(|hostname_ports| hostname_ports.len()).sum();
+            let role_hostname_ports = &hostname_ports_per_role[&role];
+            if found < role_hostname_ports.len()
+            {
+                let absent: Vec<String> = role_hostname_ports
+                    .iter()
+                    .filter(|hostname_port| !value_hostname_ports.values().any(|hostname_ports| hostname_ports.contains(hostname_port)))
+                    .cloned()
+                    .collect();
+                value_hostname_ports.insert("(absent)".to_string(), absent);
+            }
+            if value_hostname_ports.len() > 1
+            {
+                gflagsdrift.btreegflagsdrift.insert((role, name), value_hostname_ports);
+            }
+        }
+        gflagsdrift
+    }
+    pub fn print(
+        &self,
+        stat_name_filter: &Regex,
+    )
+    {
+        let mut previous_role = String::new();
+        for ((role, name), value_hostname_ports) in self.btreegflagsdrift
+            .iter()
+            .filter(|((_, name), _)| stat_name_filter.is_match(name))
+        {
+            if *role != previous_role
+            {
+                println!("{}", "-".repeat(120));
+                println!("Role: {}", role);
+                println!("{}", "-".repeat(120));
+                previous_role = role.to_string();
+            }
+            println!("{}", name.yellow());
+            for (value, hostname_ports) in value_hostname_ports
+            {
+                println!("  {:40} {}", value, hostname_ports.join(", "));
+            }
+        }
+    }
+}
+
+pub async fn gflags_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let gflagsdiff = GFlagsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    gflagsdiff.print(&hostname_filter, &stat_name_filter);
+
+    Ok(())
+}
+
+pub async fn print_gflags_drift(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+    let mut ignore_gflags: Vec<Regex> = GFLAGS_DRIFT_IGNORE.iter().map(|ignore| Regex::new(ignore).unwrap()).collect();
+    if let Some(gflags_ignore) = &options.gflags_ignore
+    {
+        ignore_gflags.push(Regex::new(gflags_ignore)?);
+    }
+
+    let mut allgflags = match options.print_gflags_drift.as_ref().unwrap() {
+        Some(snapshot_number) => {
+            let mut allgflags = AllGFlags::new();
+            allgflags.gflags = snapshot::read_snapshot_json(snapshot_number, "gflags")?;
+            allgflags
+        },
+        None => AllGFlags::read_gflags(&hosts, &ports, parallel).await,
+    };
+    allgflags.gflags.retain(|gflag| hostname_filter.is_match(gflag.hostname_port.as_ref().expect("hostname:port should be set")));
+
+    let gflagsdrift = GFlagsDrift::new_from_gflags(&allgflags, &ignore_gflags);
+    gflagsdrift.print(&stat_name_filter);
+
+    Ok(())
+}
+
 pub async fn print_gflags(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -149,6 +395,58 @@ pub async fn print_gflags(
 mod tests {
     use super::*;
 
+    fn gflag(hostname_port: &str, name: &str, value: &str) -> GFlag {
+        GFlag {
+            hostname_port: Some(hostname_port.to_string()),
+            name: name.to_string(),
+            value: value.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unit_gflags_drift() {
+        let mut allgflags = AllGFlags::new();
+        for (hostname_port, log_filename, max_clock_skew, rpc_bind_addresses) in [
+            ("192.168.66.80:7000", "yb-master", "500000", "192.168.66.80"),
+            ("192.168.66.81:7000", "yb-master", "500000", "192.168.66.81"),
+            ("192.168.66.80:9000", "yb-tserver", "500000", "192.168.66.80"),
+            ("192.168.66.81:9000", "yb-tserver", "1000000", "192.168.66.81"),
+        ] {
+            allgflags.gflags.push(gflag(hostname_port, "log_filename", log_filename));
+            allgflags.gflags.push(gflag(hostname_port, "max_clock_skew_usec", max_clock_skew));
+            allgflags.gflags.push(gflag(hostname_port, "rpc_bind_addresses", rpc_bind_addresses));
+        }
+        allgflags.gflags.push(gflag("192.168.66.80:9000", "ysql_enable_auth", "true"));
+
+        let ignore_gflags: Vec<Regex> = GFLAGS_DRIFT_IGNORE.iter().map(|ignore| Regex::new(ignore).unwrap()).collect();
+        let gflagsdrift = GFlagsDrift::new_from_gflags(&allgflags, &ignore_gflags);
+        assert_eq!(gflagsdrift.btreegflagsdrift.len(), 2);
+        let max_clock_skew = &gflagsdrift.btreegflagsdrift[&("tserver".to_string(), "max_clock_skew_usec".to_string())];
+        assert_eq!(max_clock_skew["1000000"], vec!["192.168.66.81:9000"]);
+        let ysql_enable_auth = &gflagsdrift.btreegflagsdrift[&("tserver".to_string(), "ysql_enable_auth".to_string())];
+        assert_eq!(ysql_enable_auth["(absent)"], vec!["192.168.66.81:9000"]);
+    }
+
+    #[test]
+    fn unit_gflags_diff() {
+        let mut first = AllGFlags::new();
+        first.gflags.push(gflag("192.168.66.80:9000", "log_min_seconds_to_retain", "300"));
+        first.gflags.push(gflag("192.168.66.80:9000", "certs_dir", ""));
+        let mut second = AllGFlags::new();
+        second.gflags.push(gflag("192.168.66.80:9000", "log_min_seconds_to_retain", "900"));
+        second.gflags.push(gflag("192.168.66.80:9000", "certs_dir", ""));
+
+        let mut gflagsdiff = GFlagsDiff::new();
+        gflagsdiff.first_snapshot(first);
+        gflagsdiff.second_snapshot(second);
+        let row = &gflagsdiff.btreegflagsdiff[&("192.168.66.80:9000".to_string(), "log_min_seconds_to_retain".to_string())];
+        assert_eq!(row.first_value.as_deref(), Some("300"));
+        assert_eq!(row.second_value.as_deref(), Some("900"));
+        let row = &gflagsdiff.btreegflagsdiff[&("192.168.66.80:9000".to_string(), "certs_dir".to_string())];
+        assert_eq!(row.first_value, row.second_value);
+    }
+
     #[test]
     fn unit_parse_regular_gflags() {
         // These are the gflags of the tserver of version 2.15.2.1
@@ -1,5 +1,6 @@
 //! The structs
 //!
+use std::collections::BTreeMap;
 use chrono::{DateTime, Local};
 
 /// The root structure for deserializing the gflags
@@ -30,3 +31,32 @@ pub struct GFlag {
 pub struct AllGFlags {
     pub gflags: Vec<GFlag>,
 }
+// diff
+/// BTreeMap for storing a gflags diff struct per hostname_port, gflag name
+type BTreeGFlagsDiff = BTreeMap<(String, String), GFlagsDiffFields>;
+/// Wrapper struct for holding the btreemap
+#[derive(Debug, Default)]
+pub struct GFlagsDiff {
+    pub btreegflagsdiff: BTreeGFlagsDiff,
+}
+/// The gflags diff struct.
+///
+/// The hostname:port and gflag name is the key of the btreemap,
+/// This struct holds the first and second value.
+/// A gflag value can be empty, therefore a gflag that is not found in a snapshot is None.
+#[derive(Debug, Default)]
+pub struct GFlagsDiffFields {
+    pub first_value: Option<String>,
+    pub second_value: Option<String>,
+}
+// drift
+/// BTreeMap for storing the hostname_ports per value, per role and gflag name
+type BTreeGFlagsDrift = BTreeMap<(String, String), BTreeMap<String, Vec<String>>>;
+/// The gflags that have a different value between servers of the same role (master or tserver).
+///
+/// The role and gflag name is the key of the btreemap,
+/// the value is a btreemap with the gflag value and the hostname_ports that have that value.
+#[derive(Debug, Default)]
+pub struct GFlagsDrift {
+    pub btreegflagsdrift: BTreeGFlagsDrift,
+}
@@ -0,0 +1,272 @@
+//! The impls and functions
+//!
+use std::collections::BTreeMap;
+use regex::Regex;
+use colored::*;
+use anyhow::{Context, Result};
+use crate::utility;
+use crate::snapshot;
+use crate::metrics::MetricEntityDiff;
+use crate::tablet_detail::AllTablets;
+use crate::hot_tablets::{AllHotTablets, HotTablet};
+use crate::Opts;
+
+/// The number of times the average operations of the tablets of a table above which a tablet is a split candidate.
+pub const HOT_TABLET_FACTOR: f64 = 2.;
+/// The percentage of the writes of a table in a single tablet above which the key design is suspect.
+pub const KEY_DESIGN_WRITE_PCT: f64 = 80.;
+/// The operations per second below which a tablet is not flagged.
+pub const MINIMUM_OPS_PER_SECOND: f64 = 1.;
+
+impl HotTablet {
+    pub fn per_second(
+        &self,
+        value: u64,
+    ) -> f64
+    {
+        if self.seconds > 0. { value as f64 / self.seconds } else { 0. }
+    }
+    pub fn ops_per_second(&self) -> f64 { self.per_second(self.read_ops + self.write_ops) }
+}
+
+impl AllHotTablets {
+    pub fn new() -> Self { Default::default() }
+    /// Take the operations per tablet from the metrics diff (with the tablet ids, so created with details enabled),
+    /// and the size and partition of the tablets from the tablet servers.
+    pub fn new_from_diff(
+        metricentitydiff: &MetricEntityDiff,
+        alltablets: &AllTablets,
+    ) -> AllHotTablets
+    {
+        let mut allhottablets = AllHotTablets::new();
+
+        for ((_, _, tablet_id, name), row) in metricentitydiff.btreemetricdiffvalue
+            .iter()
+            .filter(|((_, metrics_type, _, name), _)| metrics_type == "tablet" && matches!(name.as_str(), "rows_inserted" | "rocksdb_bytes_written"))
+        {
+            let hottablet = allhottablets.tablets.entry(tablet_id.clone()).or_insert_with(|| HotTablet {
+                table: format!("{}.{}", row.namespace, row.table_name),
+                ..Default::default()
+            });
+            hottablet.seconds = hottablet.seconds.max((row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 / 1000.);
+            // these are applied on every replica.
+            let value = (row.second_value - row.first_value).max(0) as u64;
+            match name.as_str()
+            {
+                "rows_inserted" => hottablet.rows_inserted = hottablet.rows_inserted.max(value),
+                _ => hottablet.bytes_written = hottablet.bytes_written.max(value),
+            }
+        }
+        for ((_, _, tablet_id, name), row) in metricentitydiff.btreemetricdiffcountsum
+            .iter()
+            .filter(|((_, metrics_type, _, name), _)| metrics_type == "tablet" && matches!(name.as_str(), "ql_read_latency" | "ql_write_latency"))
+        {
+            let hottablet = allhottablets.tablets.entry(tablet_id.clone()).or_insert_with(|| HotTablet {
+                table: format!("{}.{}", row.namespace, row.table_name),
+                ..Default::default()
+            });
+            hottablet.seconds = hottablet.seconds.max((row.second_snapshot_time - row.first_snapshot_time).num_milliseconds() as f64 / 1000.);
+            // these are performed by the replica that is read or written.
+            let value = row.second_total_count.saturating_sub(row.first_total_count);
+            match name.as_str()
+            {
+                "ql_read_latency" => hottablet.read_ops += value,
+                _ => hottablet.write_ops += value,
+            }
+        }
+
+        for tabletbasic in alltablets.tablet.iter().flat_map(|tablet| tablet.tabletbasic.iter())
+        {
+            if let Some(hottablet) = allhottablets.tablets.get_mut(&tabletbasic.tablet_id)
+            {
+                hottablet.on_disk_size = hottablet.on_disk_size.max(tabletbasic.total_on_disk_size_bytes());
+                hottablet.num_sst_files = hottablet.num_sst_files.max(tabletbasic.num_sst_files.parse().ok());
+                if hottablet.partition.is_empty()
+                {
+                    hottablet.partition = tabletbasic.partition.clone();
+                }
+            }
+        }
+        allhottablets
+    }
+    /// Flag the tablets that are split candidates, or that point to a key design problem.
+    pub fn recommend(&mut self)
+    {
+        // table, tablet_ids
+        let mut tables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (tablet_id, hottablet) in &self.tablets
+        {
+            tables.entry(hottablet.table.clone()).or_default().push(tablet_id.clone());
+        }
+
+        for tablet_ids in tables.values()
+        {
+            let mean_ops_per_second = tablet_ids.iter().map(|tablet_id| self.tablets[tablet_id].ops_per_second()).sum::<f64>() / tablet_ids.len() as f64;
+            let table_write_ops: u64 = tablet_ids.iter().map(|tablet_id| self.tablets[tablet_id].write_ops).sum();
+            for tablet_id in tablet_ids
+            {
+                let hottablet = self.tablets.get_mut(tablet_id).unwrap();
+                if hottablet.ops_per_second() < MINIMUM_OPS_PER_SECOND
+                {
+                    continue;
+                }
+                let write_pct = if table_write_ops > 0 { hottablet.write_ops as f64 / table_write_ops as f64 * 100. } else { 0. };
+                hottablet.recommendation = if tablet_ids.len() > 1 && write_pct > KEY_DESIGN_WRITE_PCT
+                {
+                    if hottablet.partition.starts_with("range") && hottablet.partition.ends_with("<end>)")
+                    {
+                        Some(format!("key design: the last tablet of the range has {:.0}% of the writes of the table, is the range key monotonically increasing?", write_pct))
+                    }
+                    else if hottablet.partition.starts_with("range")
+                    {
+                        Some(format!("key design: {:.0}% of the writes of the table are in the range of this tablet", write_pct))
+                    }
+                    else
+                    {
+                        Some(format!("key design: {:.0}% of the writes of the table are in the hash range of this tablet, is there a hot key?", write_pct))
+                    }
+                }
+                else if tablet_ids.len() == 1
+                {
+                    Some("split: the only tablet of the table".to_string())
+                }
+                else if hottablet.ops_per_second() > HOT_TABLET_FACTOR * mean_ops_per_second
+                {
+                    Some(format!("split: {:.1}x the average operations of the tablets of the table", hottablet.ops_per_second() / mean_ops_per_second))
+                }
+                else
+                {
+                    None
+                };
+            }
+        }
+    }
+    pub fn print(
+        &self,
+        table_name_filter: &Regex,
+        limit: usize,
+    )
+    {
+        let mut tablets: Vec<(&String, &HotTablet)> = self.tablets
+            .iter()
+            .filter(|(_, hottablet)| table_name_filter.is_match(&hottablet.table))
+            .collect();
+        tablets.sort_by(|(_, a), (_, b)| b.ops_per_second().total_cmp(&a.ops_per_second()));
+        let size = |bytes: Option<u64>| bytes.map(utility::bytes_to_human_readable).unwrap_or_else(|| "-".to_string());
+
+        println!("{:40} {:32} {:>10} {:>10} {:>10} {:>10} {:>10} {:>5}", "table", "tablet", "reads/s", "writes/s", "rows ins/s", "written/s", "on disk", "sst");
+        for (tablet_id, hottablet) in tablets.iter().take(limit)
+        {
+            println!("{:40} {:32} {:10.1} {:10.1} {:10.1} {:>10} {:>10} {:>5}",
+                hottablet.table,
+                tablet_id,
+                hottablet.per_second(hottablet.read_ops),
+                hottablet.per_second(hottablet.write_ops),
+                hottablet.per_second(hottablet.rows_inserted),
+                utility::bytes_to_human_readable(hottablet.per_second(hottablet.bytes_written) as u64),
+                size(hottablet.on_disk_size),
+                hottablet.num_sst_files.map(|num_sst_files| num_sst_files.to_string()).unwrap_or_else(|| "-".to_string()),
+            );
+        }
+
+        let flagged: Vec<&(&String, &HotTablet)> = tablets.iter().filter(|(_, hottablet)| hottablet.recommendation.is_some()).collect();
+        if flagged.is_empty()
+        {
+            println!("{}", "No tablets are flagged for splitting or key design.".green());
+            return;
+        }
+        println!();
+        println!("{:40} {:32} {:30} recommendation", "table", "tablet", "partition");
+        for (tablet_id, hottablet) in flagged
+        {
+            println!("{:40} {:32} {:30} {}", hottablet.table, tablet_id, hottablet.partition, hottablet.recommendation.as_deref().unwrap_or_default().yellow());
+        }
+    }
+}
+
+pub async fn hot_tablets(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    // the tablet ids are only kept in the metrics diff with details enabled.
+    let metricentitydiff = MetricEntityDiff::snapshot_diff(&begin_snapshot, &end_snapshot, &begin_snapshot_row.timestamp, &true)?;
+    let mut alltablets = AllTablets::new();
+    // the on disk size and number of SST files are taken from the tablet detail of the end snapshot.
+    alltablets.tablet = snapshot::read_snapshot_json(&end_snapshot, "tablets")
+        .with_context(|| format!("Cannot read the tablet detail of snapshot {}, hot tablets requires a snapshot taken with the --extra-data switch", end_snapshot))?;
+    if alltablets.tablet.is_empty()
+    {
+        println!("{}", format!("No tablet detail found in snapshot {}: the on disk size and number of SST files are not shown, take the snapshot with the --extra-data switch", end_snapshot).yellow());
+    }
+
+    let mut allhottablets = AllHotTablets::new_from_diff(&metricentitydiff, &alltablets);
+    allhottablets.recommend();
+    allhottablets.print(&table_name_filter, options.hot_tablets_limit);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Local};
+    use crate::metrics::MetricDiffCountSum;
+
+    #[test]
+    fn unit_hot_tablets_recommend() {
+        let first_snapshot_time = Local::now();
+        let mut metricentitydiff = MetricEntityDiff::new();
+        let mut add_writes = |table_name: &str, tablet_id: &str, writes: u64| {
+            metricentitydiff.btreemetricdiffcountsum.insert(
+                ("yb-1.local:9000".to_string(), "tablet".to_string(), tablet_id.to_string(), "ql_write_latency".to_string()),
+                MetricDiffCountSum {
+                    table_name: table_name.to_string(),
+                    namespace: "yugabyte".to_string(),
+                    first_snapshot_time,
+                    second_snapshot_time: first_snapshot_time + Duration::seconds(100),
+                    second_total_count: writes,
+                    ..Default::default()
+                },
+            );
+        };
+        // a range table with the writes in the last tablet.
+        add_writes("events", "events1", 100);
+        add_writes("events", "events2", 100);
+        add_writes("events", "events3", 9800);
+        // a hash table with a tablet with more than twice the average.
+        add_writes("orders", "orders1", 1000);
+        add_writes("orders", "orders2", 1000);
+        add_writes("orders", "orders3", 1000);
+        add_writes("orders", "orders4", 4000);
+        // an idle table with a single tablet.
+        add_writes("idle", "idle1", 10);
+
+        let mut alltablets = AllTablets::new();
+        alltablets.tablet.push(serde_json::from_value(serde_json::json!({
+            "hostname_port": "yb-1.local:9000",
+            "timestamp": null,
+            "tabletbasic": [
+                { "namespace": "yugabyte", "table_name": "events", "table_uuid": "", "tablet_id": "events3", "partition": "range: [DocKey([], [1000]), <end>)", "state": "RUNNING", "hidden": "false", "num_sst_files": "4", "on_disk_size": "Total: 1.00M Consensus Metadata: 1.5K WAL Files: 1.00M SST Files: 0B SST Files Uncompressed: 0B", "raftconfig": "", "last_status": "" },
+            ],
+            "tabletdetail": [],
+        })).unwrap());
+
+        let mut allhottablets = AllHotTablets::new_from_diff(&metricentitydiff, &alltablets);
+        allhottablets.recommend();
+        assert_eq!(allhottablets.tablets["events3"].ops_per_second(), 98.);
+        assert_eq!(allhottablets.tablets["events3"].on_disk_size, Some(1048576));
+        assert_eq!(allhottablets.tablets["events3"].num_sst_files, Some(4));
+        assert!(allhottablets.tablets["events3"].recommendation.as_ref().unwrap().starts_with("key design: the last tablet of the range has 98%"));
+        assert_eq!(allhottablets.tablets["events1"].recommendation, None);
+        assert_eq!(allhottablets.tablets["orders4"].recommendation, Some("split: 2.3x the average operations of the tablets of the table".to_string()));
+        assert_eq!(allhottablets.tablets["orders1"].recommendation, None);
+        assert_eq!(allhottablets.tablets["idle1"].recommendation, None);
+    }
+}
@@ -0,0 +1,14 @@
+//! Module for ranking the tablets by their read and write rate between two snapshots, and recommending tablets to split (`--hot-tablets`).
+//!
+//! The operations are taken from the tablet metrics, the size and partition from `/tablets` of the end snapshot.
+//! A tablet with much more operations than the other tablets of its table is a split candidate,
+//! a single tablet that receives most of the writes of its table points to the key design.
+//!
+//! The hot tablets functionality is called from:
+//! - [crate] -> [hot_tablets] (for a begin and end snapshot)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
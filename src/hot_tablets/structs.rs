@@ -0,0 +1,27 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+
+/// The operations of a tablet between two snapshots, with the size of the tablet in the end snapshot.
+#[derive(Debug, Default)]
+pub struct HotTablet {
+    /// namespace.table_name
+    pub table: String,
+    pub read_ops: u64,
+    pub write_ops: u64,
+    pub rows_inserted: u64,
+    pub bytes_written: u64,
+    /// The time between the snapshots.
+    pub seconds: f64,
+    pub on_disk_size: Option<u64>,
+    pub num_sst_files: Option<u64>,
+    pub partition: String,
+    pub recommendation: Option<String>,
+}
+/// Wrapper struct for holding the tablets.
+///
+/// The key is the tablet_id.
+#[derive(Debug, Default)]
+pub struct AllHotTablets {
+    pub tablets: BTreeMap<String, HotTablet>,
+}
@@ -0,0 +1,640 @@
+//! The impls and functions
+//!
+use std::{cmp::Ordering, collections::{BTreeMap, BTreeSet}, fs};
+use chrono::NaiveDateTime;
+use regex::Regex;
+use colored::*;
+use anyhow::{Context, Result};
+use crate::utility;
+use crate::snapshot;
+use crate::gflags::AllGFlags;
+use crate::cluster_config::AllSysClusterConfigEntryPB;
+use crate::clocks::AllClocks;
+use crate::drives::AllDrives;
+use crate::vars::AllVars;
+use crate::lint::{AllLintViolations, LintRule, LintViolation, Operator, RuleKind, Severity};
+use crate::Opts;
+
+/// The default of the max_clock_skew_usec gflag, if the gflag is not found.
+const DEFAULT_MAX_CLOCK_SKEW_USEC: i64 = 500_000;
+
+/// The built-in rules.
+pub fn builtin_rules() -> Vec<LintRule>
+{
+    let rule = |name: &str, gflag: &str, role: Option<&str>, operator: Operator, value: &str, severity: Severity, rationale: &str| LintRule {
+        name: name.to_string(),
+        kind: RuleKind::Gflag,
+        setting: gflag.to_string(),
+        role: role.map(|role| role.to_string()),
+        operator,
+        value: value.to_string(),
+        severity,
+        rationale: rationale.to_string(),
+        ..Default::default()
+    };
+    let check = |name: &str, kind: RuleKind, threshold: Option<f64>, severity: Severity, rationale: &str| LintRule {
+        name: name.to_string(),
+        kind,
+        threshold,
+        severity,
+        rationale: rationale.to_string(),
+        ..Default::default()
+    };
+    vec![
+        rule("replication-factor", "replication_factor", Some("master"), Operator::Ge, "3", Severity::Critical,
+             "Without live replicas in the cluster config, the replication_factor gflag sets the number of replicas of a tablet: with fewer than 3, the failure of a single server makes its tablets unavailable."),
+        rule("load-balancing", "enable_load_balancing", Some("master"), Operator::Eq, "true", Severity::Warning,
+             "Without the load balancer, tablets and leaders are not moved to new or recovered servers."),
+        rule("max-clock-skew", "max_clock_skew_usec", None, Operator::Le, "500000", Severity::Warning,
+             "A higher maximum clock skew increases the uncertainty window, causing more read restarts and higher latency."),
+        rule("leader-lease", "leader_lease_duration_ms", None, Operator::Between, "1000..5000", Severity::Warning,
+             "A short leader lease can expire during a short pause of the leader, a long leader lease increases the failover time."),
+        rule("raft-heartbeat", "raft_heartbeat_interval_ms", None, Operator::Between, "100..1000", Severity::Warning,
+             "A short raft heartbeat interval increases the network and CPU usage, a long interval increases the leader failure detection time."),
+        rule("follower-unavailable", "follower_unavailable_considered_failed_sec", Some("tserver"), Operator::Ge, "900", Severity::Warning,
+             "A low value causes tablets to be re-replicated during short outages, such as a rolling restart."),
+        rule("durable-wal-write", "durable_wal_write", Some("tserver"), Operator::Ne, "false", Severity::Info,
+             "Without durable WAL writes, the WAL is fsynced periodically, a simultaneous failure of servers can lose recent writes."),
+        rule("callhome", "callhome_enabled", None, Operator::Eq, "false", Severity::Info,
+             "Callhome sends diagnostics data to Yugabyte, which is often not allowed in production environments."),
+        check("placement-replication-factor", RuleKind::ReplicationFactor, Some(3_f64), Severity::Critical,
+              "The live replicas in the cluster config override the replication_factor gflag: with fewer than 3 replicas, the failure of a single server makes tablets unavailable."),
+        check("drive-usage-warning", RuleKind::DriveUsage, Some(80_f64), Severity::Warning,
+              "A full drive stops the server from writing, compactions need free space to complete."),
+        check("drive-usage-critical", RuleKind::DriveUsage, Some(90_f64), Severity::Critical,
+              "A full drive stops the server from writing, compactions need free space to complete."),
+        check("clock-skew-warning", RuleKind::ClockSkew, Some(0.5_f64), Severity::Warning,
+              "A clock skew near max_clock_skew_usec increases read restarts."),
+        check("clock-skew-critical", RuleKind::ClockSkew, Some(1_f64), Severity::Critical,
+              "A tablet server with a clock skew above max_clock_skew_usec stops serving."),
+        check("tserver-status", RuleKind::TserverStatus, None, Severity::Critical,
+              "A tablet server that is not alive reduces the number of available replicas of its tablets."),
+    ]
+}
+
+/// Read the rules from the JSON file, and add them to the built-in rules.
+/// A rule in the file with the same name as a built-in rule replaces it.
+pub fn read_rules(
+    rules_file: &Option<String>,
+) -> Result<Vec<LintRule>>
+{
+    let mut rules = builtin_rules();
+    if let Some(rules_file) = rules_file
+    {
+        let file_rules = fs::read_to_string(rules_file)
+            .with_context(|| format!("Cannot read file: {}", rules_file))?;
+        let file_rules: Vec<LintRule> = serde_json::from_str(&file_rules)
+            .with_context(|| format!("Error parsing lint rules: {}", rules_file))?;
+        for file_rule in file_rules
+        {
+            rules.retain(|rule| rule.name != file_rule.name);
+            rules.push(file_rule);
+        }
+    }
+    Ok(rules)
+}
+
+/// Compare a gflag value with an expected value.
+/// The comparison is numeric if both values are numbers, otherwise it is a string comparison.
+fn compare(
+    value: &str,
+    expected: &str,
+) -> Option<Ordering>
+{
+    match (value.trim().parse::<f64>(), expected.trim().parse::<f64>())
+    {
+        (Ok(value), Ok(expected)) => value.partial_cmp(&expected),
+        _ => Some(value.cmp(expected)),
+    }
+}
+
+impl LintRule {
+    /// The threshold of a replication_factor, drive_usage or clock_skew rule, a missing threshold is an error.
+    fn threshold(&self) -> Result<f64>
+    {
+        self.threshold
+            .with_context(|| format!("Missing threshold for lint rule {} of kind {:?}", self.name, self.kind))
+    }
+    /// Evaluate the rule for a gflag or var value, true means the value satisfies the rule.
+    /// An invalid regex for match, or an invalid range for between is an error.
+    pub fn is_satisfied(
+        &self,
+        value: &str,
+    ) -> Result<bool>
+    {
+        let is = |predicate: fn(Ordering) -> bool| compare(value, &self.value).map(predicate).unwrap_or(false);
+        Ok(match self.operator
+        {
+            Operator::Eq => is(Ordering::is_eq),
+            Operator::Ne => is(Ordering::is_ne),
+            Operator::Lt => is(Ordering::is_lt),
+            Operator::Le => is(Ordering::is_le),
+            Operator::Gt => is(Ordering::is_gt),
+            Operator::Ge => is(Ordering::is_ge),
+            Operator::Between => {
+                let (minimum, maximum) = self.value.split_once("..")
+                    .with_context(|| format!("Invalid range for lint rule {}: {} (expected: <minimum>..<maximum>)", self.name, self.value))?;
+                compare(value, minimum).map(Ordering::is_ge).unwrap_or(false)
+                    && compare(value, maximum).map(Ordering::is_le).unwrap_or(false)
+            },
+            Operator::Match => Regex::new(&self.value)
+                .with_context(|| format!("Invalid regex for lint rule {}: {}", self.name, self.value))?
+                .is_match(value),
+        })
+    }
+}
+
+impl AllLintViolations {
+    pub fn new() -> Self { Default::default() }
+    fn add(
+        &mut self,
+        severity: Severity,
+        rule: &str,
+        hostname_port: &str,
+        message: String,
+        rationale: &str,
+    )
+    {
+        self.violations.push(LintViolation {
+            severity,
+            rule: rule.to_string(),
+            hostname_port: hostname_port.to_string(),
+            message,
+            rationale: rationale.to_string(),
+        });
+    }
+    /// Evaluate the rules of a kind for the settings (hostname:port, name, value) of the servers.
+    fn lint_settings<'a>(
+        &mut self,
+        settings: impl Iterator<Item = (&'a str, &'a str, &'a str)>,
+        roles: &BTreeMap<String, String>,
+        kind: RuleKind,
+        rules: &[LintRule],
+    ) -> Result<()>
+    {
+        for (hostname_port, name, value) in settings
+        {
+            let role = roles.get(hostname_port).map(|role| role.as_str()).unwrap_or("unknown");
+            for rule in rules
+                .iter()
+                .filter(|rule| rule.kind == kind && rule.setting == name)
+                .filter(|rule| rule.role.as_ref().map(|rule_role| rule_role == role).unwrap_or(true))
+            {
+                if !rule.is_satisfied(value)?
+                {
+                    self.add(rule.severity, &rule.name, hostname_port, format!("{}={} (expected: {} {})", name, value, rule.operator, rule.value), &rule.rationale);
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Evaluate the gflag rules for every server that provided gflags.
+    /// The replication_factor gflag is only evaluated if the cluster config has no live replicas,
+    /// because the live replicas override it, and are evaluated by the replication_factor rules.
+    pub fn lint_gflags(
+        &mut self,
+        allgflags: &AllGFlags,
+        allsysclusterconfigentrypb: &AllSysClusterConfigEntryPB,
+        rules: &[LintRule],
+    ) -> Result<()>
+    {
+        let has_live_replicas = allsysclusterconfigentrypb.sysclusterconfigentrypb
+            .first()
+            .and_then(|cluster_config| cluster_config.replication_info.as_ref())
+            .and_then(|replication_info| replication_info.live_replicas.as_ref())
+            .is_some();
+        let settings = allgflags.gflags
+            .iter()
+            .filter(|gflag| !(has_live_replicas && gflag.name == "replication_factor"))
+            .filter_map(|gflag| gflag.hostname_port.as_deref().map(|hostname_port| (hostname_port, gflag.name.as_str(), gflag.value.as_str())));
+        self.lint_settings(settings, &allgflags.roles(), RuleKind::Gflag, rules)
+    }
+    /// Evaluate the var rules for every server that provided vars.
+    /// The role of a server is taken from its gflags.
+    pub fn lint_vars(
+        &mut self,
+        allvars: &AllVars,
+        allgflags: &AllGFlags,
+        rules: &[LintRule],
+    ) -> Result<()>
+    {
+        let settings = allvars.vars
+            .iter()
+            .filter_map(|vars| vars.hostname_port.as_deref().map(|hostname_port| (hostname_port, vars)))
+            .flat_map(|(hostname_port, vars)| vars.flags.iter().map(move |var| (hostname_port, var.name.as_str(), var.value.as_str())));
+        self.lint_settings(settings, &allgflags.roles(), RuleKind::Var, rules)
+    }
+    /// Evaluate the replication info and blacklists of the cluster config.
+    /// All masters return the cluster config, so only the first one is used.
+    pub fn lint_cluster_config(
+        &mut self,
+        allsysclusterconfigentrypb: &AllSysClusterConfigEntryPB,
+        rules: &[LintRule],
+    ) -> Result<()>
+    {
+        let Some(cluster_config) = allsysclusterconfigentrypb.sysclusterconfigentrypb.first() else { return Ok(()) };
+        let hostname_port = cluster_config.hostname_port.clone().unwrap_or_default();
+
+        match cluster_config.replication_info.as_ref().and_then(|replication_info| replication_info.live_replicas.as_ref())
+        {
+            None => self.add(Severity::Warning, "replication-info", &hostname_port, "no replication info (live replicas) set in the cluster config".to_string(),
+                             "Without replication info, the replication factor is taken from the replication_factor gflag, and replicas are not placed over fault domains explicitly."),
+            Some(live_replicas) => {
+                let num_replicas = live_replicas.num_replicas.unwrap_or_default();
+                let mut below_threshold = false;
+                for rule in rules.iter().filter(|rule| rule.kind == RuleKind::ReplicationFactor)
+                {
+                    if (num_replicas as f64) < rule.threshold()?
+                    {
+                        self.add(rule.severity, &rule.name, &hostname_port, format!("num_replicas={} (expected: ge {})", num_replicas, rule.threshold()?), &rule.rationale);
+                        below_threshold = true;
+                    }
+                }
+                if !below_threshold
+                    && num_replicas % 2 == 0
+                {
+                    self.add(Severity::Warning, "placement-even-replication-factor", &hostname_port, format!("num_replicas={}", num_replicas),
+                             "An even replication factor does not tolerate more failures than the odd replication factor below it, but requires a larger majority.");
+                }
+                let placement_blocks = live_replicas.placement_blocks.as_deref().unwrap_or_default();
+                let min_num_replicas: i32 = placement_blocks.iter().filter_map(|placement_block| placement_block.min_num_replicas).sum();
+                if min_num_replicas > num_replicas
+                {
+                    self.add(Severity::Critical, "placement-min-num-replicas", &hostname_port, format!("sum of min_num_replicas={} is more than num_replicas={}", min_num_replicas, num_replicas),
+                             "The placement cannot be satisfied, tablets cannot be created or re-replicated.");
+                }
+                let fault_domains: BTreeSet<_> = placement_blocks
+                    .iter()
+                    .filter_map(|placement_block| placement_block.cloud_info.as_ref())
+                    .map(|cloud_info| (cloud_info.placement_cloud.clone(), cloud_info.placement_region.clone(), cloud_info.placement_zone.clone()))
+                    .collect();
+                if !placement_blocks.is_empty()
+                    && (fault_domains.len() as i32) < num_replicas
+                {
+                    self.add(Severity::Warning, "placement-fault-domains", &hostname_port, format!("{} placement blocks for num_replicas={}", fault_domains.len(), num_replicas),
+                             "With fewer placement blocks than replicas, a single placement block holds more than one replica, and its failure can make tablets lose their majority.");
+                }
+            },
+        }
+        if let Some(hosts) = cluster_config.server_blacklist.as_ref().and_then(|blacklist| blacklist.hosts.as_ref()).filter(|hosts| !hosts.is_empty())
+        {
+            self.add(Severity::Info, "server-blacklist", &hostname_port, format!("{} server(s) blacklisted: {}", hosts.len(), hosts.iter().map(|host| format!("{}:{}", host.host, host.port)).collect::<Vec<_>>().join(", ")),
+                     "The load balancer moves all tablets away from blacklisted servers, which should be temporary.");
+        }
+        if let Some(hosts) = cluster_config.leader_blacklist.as_ref().and_then(|blacklist| blacklist.hosts.as_ref()).filter(|hosts| !hosts.is_empty())
+        {
+            self.add(Severity::Info, "leader-blacklist", &hostname_port, format!("{} server(s) leader blacklisted: {}", hosts.len(), hosts.iter().map(|host| format!("{}:{}", host.host, host.port)).collect::<Vec<_>>().join(", ")),
+                     "The load balancer moves all leaders away from leader blacklisted servers, which should be temporary.");
+        }
+        Ok(())
+    }
+    /// Evaluate the status of the tablet servers as seen by the masters.
+    pub fn lint_tserver_status(
+        &mut self,
+        allclocks: &AllClocks,
+        rules: &[LintRule],
+    )
+    {
+        // every master reports all tablet servers, so a tablet server is reported once.
+        let mut servers: BTreeMap<String, (String, String)> = BTreeMap::new();
+        for clock in &allclocks.clocks
+        {
+            servers.entry(clock.server.clone())
+                .or_insert((clock.hostname_port.clone().unwrap_or_default(), clock.status_uptime.clone()));
+        }
+        for (server, (hostname_port, status_uptime)) in servers
+            .iter()
+            .filter(|(_, (_, status_uptime))| !status_uptime.starts_with("ALIVE"))
+        {
+            for rule in rules.iter().filter(|rule| rule.kind == RuleKind::TserverStatus)
+            {
+                self.add(rule.severity, &rule.name, hostname_port, format!("{} status: {}", server.split_whitespace().next().unwrap_or_default(), status_uptime), &rule.rationale);
+            }
+        }
+    }
+    /// Evaluate the clock skew of the alive tablet servers, using the clocks as seen by a single master.
+    ///
+    /// The physical time of a tablet server is the time of its last heartbeat,
+    /// so the time since the heartbeat is added to make the times of the tablet servers comparable.
+    /// The skew of a tablet server is the difference with the median time of all tablet servers,
+    /// which violates a clock_skew rule above the threshold times max_clock_skew_usec of the master.
+    /// Only the most severe violated rule is reported per tablet server.
+    pub fn lint_clock_skew(
+        &mut self,
+        allclocks: &AllClocks,
+        allgflags: &AllGFlags,
+        rules: &[LintRule],
+    ) -> Result<()>
+    {
+        let Some(master) = allclocks.clocks.iter().filter_map(|clock| clock.hostname_port.clone()).min() else { return Ok(()) };
+        let max_clock_skew_usec = allgflags.gflags
+            .iter()
+            .find(|gflag| gflag.hostname_port.as_deref() == Some(master.as_str()) && gflag.name == "max_clock_skew_usec")
+            .and_then(|gflag| gflag.value.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_MAX_CLOCK_SKEW_USEC);
+
+        let mut times: Vec<(&str, i64)> = allclocks.clocks
+            .iter()
+            .filter(|clock| clock.hostname_port.as_deref() == Some(master.as_str()) && clock.status_uptime.starts_with("ALIVE"))
+            .filter_map(|clock| {
+                let physical_time = NaiveDateTime::parse_from_str(&clock.physical_time_utc, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+                let time_since_heartbeat = clock.time_since_heartbeat.trim_end_matches('s').parse::<f64>().ok()?;
+                Some((clock.server.split_whitespace().next().unwrap_or_default(), physical_time.timestamp_micros() + (time_since_heartbeat * 1_000_000_f64) as i64))
+            })
+            .collect();
+        if times.len() < 2
+        {
+            return Ok(());
+        }
+        times.sort_by_key(|(_, time)| *time);
+        let median = times[times.len() / 2].1;
+        for (server, time) in times
+        {
+            let skew_usec = (time - median).abs();
+            let mut violated_rule: Option<&LintRule> = None;
+            for rule in rules.iter().filter(|rule| rule.kind == RuleKind::ClockSkew)
+            {
+                if skew_usec as f64 > rule.threshold()? * max_clock_skew_usec as f64
+                    && violated_rule.map(|violated_rule| rule.severity > violated_rule.severity).unwrap_or(true)
+                {
+                    violated_rule = Some(rule);
+                }
+            }
+            if let Some(rule) = violated_rule
+            {
+                self.add(rule.severity, &rule.name, &master, format!("{} clock skew: {} us (max_clock_skew_usec={})", server, skew_usec, max_clock_skew_usec), &rule.rationale);
+            }
+        }
+        Ok(())
+    }
+    /// Evaluate the space usage and the number of drives per server.
+    /// Only the most severe violated drive_usage rule is reported per drive.
+    pub fn lint_drives(
+        &mut self,
+        alldrives: &AllDrives,
+        rules: &[LintRule],
+    ) -> Result<()>
+    {
+        for drives in &alldrives.drives
+        {
+            let hostname_port = drives.hostname_port.clone().unwrap_or_default();
+            let drive: Vec<_> = drives.drive.iter().flatten().collect();
+            if drive.is_empty()
+            {
+                continue;
+            }
+            if drive.len() == 1
+            {
+                self.add(Severity::Info, "single-drive", &hostname_port, format!("single drive: {}", drive[0].path),
+                         "With a single drive, all IO of the server goes to one device, multiple drives spread the IO.");
+            }
+            for drive in drive
+            {
+                if let (Some(used_space), Some(total_space)) = (utility::human_readable_to_bytes(&drive.used_space), utility::human_readable_to_bytes(&drive.total_space))
+                {
+                    if total_space == 0
+                    {
+                        continue;
+                    }
+                    let usage_percentage = used_space as f64 / total_space as f64 * 100_f64;
+                    let mut violated_rule: Option<&LintRule> = None;
+                    for rule in rules.iter().filter(|rule| rule.kind == RuleKind::DriveUsage)
+                    {
+                        if usage_percentage >= rule.threshold()?
+                            && violated_rule.map(|violated_rule| rule.severity > violated_rule.severity).unwrap_or(true)
+                        {
+                            violated_rule = Some(rule);
+                        }
+                    }
+                    if let Some(rule) = violated_rule
+                    {
+                        self.add(rule.severity, &rule.name, &hostname_port, format!("{} {:.1}% used ({} of {})", drive.path, usage_percentage, drive.used_space, drive.total_space), &rule.rationale);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn lint(
+        allgflags: &AllGFlags,
+        allvars: &AllVars,
+        allsysclusterconfigentrypb: &AllSysClusterConfigEntryPB,
+        allclocks: &AllClocks,
+        alldrives: &AllDrives,
+        rules: &[LintRule],
+    ) -> Result<AllLintViolations>
+    {
+        let mut alllintviolations = AllLintViolations::new();
+        alllintviolations.lint_gflags(allgflags, allsysclusterconfigentrypb, rules)?;
+        alllintviolations.lint_vars(allvars, allgflags, rules)?;
+        alllintviolations.lint_cluster_config(allsysclusterconfigentrypb, rules)?;
+        alllintviolations.lint_tserver_status(allclocks, rules);
+        alllintviolations.lint_clock_skew(allclocks, allgflags, rules)?;
+        alllintviolations.lint_drives(alldrives, rules)?;
+        // most severe first.
+        alllintviolations.violations.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.rule.cmp(&b.rule)).then(a.hostname_port.cmp(&b.hostname_port)));
+        Ok(alllintviolations)
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        let mut previous_rule = String::new();
+        for violation in self.violations
+            .iter()
+            .filter(|violation| hostname_filter.is_match(&violation.hostname_port))
+        {
+            let severity = match violation.severity
+            {
+                Severity::Critical => violation.severity.to_string().red(),
+                Severity::Warning => violation.severity.to_string().yellow(),
+                Severity::Info => violation.severity.to_string().normal(),
+            };
+            println!("{:10} {:35} {:25} {}", severity, violation.rule, violation.hostname_port, violation.message);
+            // the rationale is printed once per rule, or for every violation with details_enable.
+            if violation.rule != previous_rule || *details_enable
+            {
+                println!("{:10} {}", "", violation.rationale.italic());
+                previous_rule = violation.rule.clone();
+            }
+        }
+    }
+}
+
+pub async fn print_lint(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let rules = read_rules(&options.lint_rules)?;
+
+    let (allgflags, allvars, allsysclusterconfigentrypb, allclocks, alldrives) = match options.lint.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allgflags = AllGFlags::new();
+            allgflags.gflags = snapshot::read_snapshot_json(snapshot_number, "gflags")?;
+            let mut allvars = AllVars::new();
+            allvars.vars = snapshot::read_snapshot_json(snapshot_number, "vars")?;
+            let mut allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::new();
+            allsysclusterconfigentrypb.sysclusterconfigentrypb = snapshot::read_snapshot_json(snapshot_number, "cluster-config")?;
+            let mut allclocks = AllClocks::new();
+            allclocks.clocks = snapshot::read_snapshot_json(snapshot_number, "clocks")?;
+            let mut alldrives = AllDrives::new();
+            alldrives.drives = snapshot::read_snapshot_json(snapshot_number, "drives")?;
+            (allgflags, allvars, allsysclusterconfigentrypb, allclocks, alldrives)
+        },
+        None => {
+            (
+                AllGFlags::read_gflags(&hosts, &ports, parallel).await,
+                AllVars::read_vars(&hosts, &ports, parallel).await,
+                AllSysClusterConfigEntryPB::read_cluster_config(&hosts, &ports, parallel).await,
+                AllClocks::read_clocks(&hosts, &ports, parallel).await?,
+                AllDrives::read_drives(&hosts, &ports, parallel).await,
+            )
+        },
+    };
+
+    let alllintviolations = AllLintViolations::lint(&allgflags, &allvars, &allsysclusterconfigentrypb, &allclocks, &alldrives, &rules)?;
+    alllintviolations.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gflags::GFlag;
+    use crate::drives::{Drive, Drives};
+    use crate::clocks::Clocks;
+    use crate::vars::{Var, Vars};
+    use crate::cluster_config::SysClusterConfigEntryPB;
+    use std::env;
+
+    #[test]
+    fn unit_lint_rule_is_satisfied() {
+        let mut rule = builtin_rules().into_iter().find(|rule| rule.name == "replication-factor").unwrap();
+        assert!(rule.is_satisfied("3").unwrap());
+        assert!(rule.is_satisfied("5").unwrap());
+        assert!(!rule.is_satisfied("1").unwrap());
+        rule.operator = Operator::Eq;
+        rule.value = "true".to_string();
+        assert!(rule.is_satisfied("true").unwrap());
+        assert!(!rule.is_satisfied("false").unwrap());
+        rule.operator = Operator::Match;
+        rule.value = "^(true|1)$".to_string();
+        assert!(rule.is_satisfied("1").unwrap());
+        rule.value = "(".to_string();
+        assert!(rule.is_satisfied("1").is_err());
+        let rule = builtin_rules().into_iter().find(|rule| rule.name == "leader-lease").unwrap();
+        assert!(rule.is_satisfied("2000").unwrap());
+        assert!(rule.is_satisfied("1000").unwrap());
+        assert!(!rule.is_satisfied("500").unwrap());
+        assert!(!rule.is_satisfied("10000").unwrap());
+    }
+
+    #[test]
+    fn unit_lint_clock_skew() {
+        let clock = |server: &str, time_since_heartbeat: &str, physical_time_utc: &str| Clocks {
+            hostname_port: Some("192.168.66.80:7000".to_string()),
+            server: server.to_string(),
+            time_since_heartbeat: time_since_heartbeat.to_string(),
+            status_uptime: "ALIVE: 2:09:21".to_string(),
+            physical_time_utc: physical_time_utc.to_string(),
+            ..Default::default()
+        };
+        let mut allclocks = AllClocks::new();
+        allclocks.clocks.push(clock("yb-1.local:9000 bca5ecc0f10a44c1a9d2d85a9c97ea49", "0.1s", "2023-01-17 14:22:18.908376"));
+        allclocks.clocks.push(clock("yb-2.local:9000 7db98e6ba63d41cc8f731faa52470cb5", "0.1s", "2023-01-17 14:22:18.899160"));
+        // the older physical time is compensated by the time since the heartbeat.
+        allclocks.clocks.push(clock("yb-3.local:9000 b00600497d314ab1a7f590b91d6c1699", "1.0s", "2023-01-17 14:22:18.025692"));
+        let mut alllintviolations = AllLintViolations::new();
+        alllintviolations.lint_clock_skew(&allclocks, &AllGFlags::new(), &builtin_rules()).unwrap();
+        assert!(alllintviolations.violations.is_empty());
+
+        allclocks.clocks.push(clock("yb-4.local:9000 c00600497d314ab1a7f590b91d6c1699", "0.1s", "2023-01-17 14:22:19.725692"));
+        let mut alllintviolations = AllLintViolations::new();
+        alllintviolations.lint_clock_skew(&allclocks, &AllGFlags::new(), &builtin_rules()).unwrap();
+        let violations: Vec<_> = alllintviolations.violations.iter().map(|violation| (violation.severity, violation.message.as_str())).collect();
+        assert_eq!(violations, vec![(Severity::Critical, "yb-4.local:9000 clock skew: 800000 us (max_clock_skew_usec=500000)")]);
+    }
+
+    #[test]
+    fn unit_lint_override_check_rules() {
+        let file_rules = r#"[
+            { "name": "drive-usage-critical", "kind": "drive_usage", "threshold": 99, "severity": "critical", "rationale": "" },
+            { "name": "tserver-status", "kind": "tserver_status", "severity": "warning", "rationale": "" },
+            { "name": "clock-skew-critical", "kind": "clock_skew", "severity": "critical", "rationale": "" },
+            { "name": "yb-version", "kind": "var", "var": "version", "operator": "match", "value": "^2\\.18", "severity": "info", "rationale": "" }
+        ]"#;
+        let file_name = env::temp_dir().join(format!("lint-rules.{}.json", std::process::id()));
+        fs::write(&file_name, file_rules).unwrap();
+        let rules = read_rules(&Some(file_name.to_string_lossy().to_string())).unwrap();
+        fs::remove_file(&file_name).unwrap();
+        assert_eq!(rules.len(), builtin_rules().len() + 1);
+
+        let mut alldrives = AllDrives::new();
+        alldrives.drives.push(Drives {
+            hostname_port: Some("192.168.66.80:9000".to_string()),
+            drive: vec![Some(Drive { path: "/mnt/d0".to_string(), used_space: "9.50G".to_string(), total_space: "10.00G".to_string() }), Some(Drive { path: "/mnt/d1".to_string(), used_space: "1.00G".to_string(), total_space: "10.00G".to_string() })],
+            ..Default::default()
+        });
+        let mut allvars = AllVars::new();
+        allvars.vars.push(Vars {
+            hostname_port: Some("192.168.66.80:9000".to_string()),
+            flags: vec![Var { name: "version".to_string(), value: "2.17.2.0".to_string(), vars_type: "NodeInfo".to_string() }],
+            ..Default::default()
+        });
+        let mut allclocks = AllClocks::new();
+        allclocks.clocks.push(Clocks {
+            hostname_port: Some("192.168.66.80:7000".to_string()),
+            server: "yb-1.local:9000 bca5ecc0f10a44c1a9d2d85a9c97ea49".to_string(),
+            status_uptime: "DEAD".to_string(),
+            ..Default::default()
+        });
+
+        let mut alllintviolations = AllLintViolations::new();
+        alllintviolations.lint_drives(&alldrives, &rules).unwrap();
+        alllintviolations.lint_vars(&allvars, &AllGFlags::new(), &rules).unwrap();
+        alllintviolations.lint_tserver_status(&allclocks, &rules);
+        let violations: Vec<_> = alllintviolations.violations.iter().map(|violation| (violation.severity, violation.rule.as_str())).collect();
+        // 95% used is below the overridden critical threshold of 99%, and is reported once as warning.
+        assert_eq!(violations, vec![(Severity::Warning, "drive-usage-warning"), (Severity::Info, "yb-version"), (Severity::Warning, "tserver-status")]);
+        // a clock_skew rule without a threshold is an error.
+        assert!(rules.iter().find(|rule| rule.name == "clock-skew-critical").unwrap().threshold().is_err());
+    }
+
+    #[test]
+    fn unit_lint_gflags_and_drives() {
+        let mut allgflags = AllGFlags::new();
+        for (name, value) in [("log_filename", "yb-master"), ("replication_factor", "1"), ("enable_load_balancing", "true")] {
+            allgflags.gflags.push(GFlag {
+                hostname_port: Some("192.168.66.80:7000".to_string()),
+                name: name.to_string(),
+                value: value.to_string(),
+                ..Default::default()
+            });
+        }
+        let mut alldrives = AllDrives::new();
+        alldrives.drives.push(Drives {
+            hostname_port: Some("192.168.66.80:9000".to_string()),
+            drive: vec![Some(Drive { path: "/mnt/d0".to_string(), used_space: "9.50G".to_string(), total_space: "10.00G".to_string() })],
+            ..Default::default()
+        });
+
+        let alllintviolations = AllLintViolations::lint(&allgflags, &AllVars::new(), &AllSysClusterConfigEntryPB::new(), &AllClocks::new(), &alldrives, &builtin_rules()).unwrap();
+        let rules: Vec<_> = alllintviolations.violations.iter().map(|violation| (violation.severity, violation.rule.as_str())).collect();
+        assert_eq!(rules, vec![(Severity::Critical, "drive-usage-critical"), (Severity::Critical, "replication-factor"), (Severity::Info, "single-drive")]);
+
+        // with live replicas in the cluster config, the replication_factor gflag is not evaluated, and the replication factor is reported once.
+        let mut allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::new();
+        allsysclusterconfigentrypb.sysclusterconfigentrypb.push(serde_json::from_str::<SysClusterConfigEntryPB>(r#"{ "version": 1, "cluster_uuid": "", "replication_info": { "live_replicas": { "num_replicas": 1 } } }"#).unwrap());
+        let alllintviolations = AllLintViolations::lint(&allgflags, &AllVars::new(), &allsysclusterconfigentrypb, &AllClocks::new(), &alldrives, &builtin_rules()).unwrap();
+        let rules: Vec<_> = alllintviolations.violations.iter().map(|violation| (violation.severity, violation.rule.as_str())).collect();
+        assert_eq!(rules, vec![(Severity::Critical, "drive-usage-critical"), (Severity::Critical, "placement-replication-factor"), (Severity::Info, "single-drive")]);
+    }
+}
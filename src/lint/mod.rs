@@ -0,0 +1,44 @@
+//! Module for checking a snapshot or the live cluster against configuration best practices (`--lint`).
+//!
+//! The gflags, the vars, the cluster config, the tablet server status and clock skew, and the drives are checked.
+//! The rules are built in, and can be extended or overridden (by rule name) with a JSON file (`--lint-rules <file>`):
+//! ```json
+//! [
+//!   {
+//!     "name": "ysql-auth",
+//!     "gflag": "ysql_enable_auth",
+//!     "role": "tserver",
+//!     "operator": "eq",
+//!     "value": "true",
+//!     "severity": "warning",
+//!     "rationale": "Without authentication, anyone who can reach the YSQL port can log in."
+//!   },
+//!   {
+//!     "name": "drive-usage-critical",
+//!     "kind": "drive_usage",
+//!     "threshold": 95,
+//!     "severity": "critical",
+//!     "rationale": "A full drive stops the server from writing."
+//!   }
+//! ]
+//! ```
+//! The kind of a rule is the data it is evaluated against, and is gflag if not set:
+//! - gflag (`/varz`) and var (`/api/v1/varz`): the setting ("gflag" or "var") is compared using operator and value.
+//!   The operator can be: eq, ne, lt, le, gt, ge (numeric if both values are numbers), between (`<minimum>..<maximum>`) or match (regex).
+//!   The role is master or tserver, and is optional: without a role, the rule is evaluated for both.
+//! - replication_factor: the number of live replicas in the cluster config is below the threshold.
+//!   The live replicas override the replication_factor gflag, which is therefore only evaluated without live replicas.
+//! - drive_usage: the percentage of used space of a drive is at or above the threshold.
+//! - clock_skew: the clock skew of a tablet server is above the threshold times max_clock_skew_usec.
+//! - tserver_status: a tablet server is not alive (no threshold).
+//!
+//! For drive_usage and clock_skew, only the most severe violated rule is reported.
+//!
+//! The lint functionality is called from:
+//! - [crate] -> [print_lint] (evaluate a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
@@ -0,0 +1,106 @@
+//! The structs
+//!
+use std::fmt;
+
+/// The severity of a lint violation, ordered from the least to the most severe.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+/// The comparison of a gflag or var rule.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Operator {
+    #[default]
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// A range, including the minimum and maximum: `<minimum>..<maximum>`.
+    Between,
+    Match,
+}
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Eq => write!(f, "eq"),
+            Operator::Ne => write!(f, "ne"),
+            Operator::Lt => write!(f, "lt"),
+            Operator::Le => write!(f, "le"),
+            Operator::Gt => write!(f, "gt"),
+            Operator::Ge => write!(f, "ge"),
+            Operator::Between => write!(f, "between"),
+            Operator::Match => write!(f, "match"),
+        }
+    }
+}
+/// The source of the data a rule is evaluated against.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// A gflag (`/varz`), compared with operator and value.
+    #[default]
+    Gflag,
+    /// A var (`/api/v1/varz`), compared with operator and value.
+    Var,
+    /// The number of replicas in the cluster config, violated below the threshold.
+    ReplicationFactor,
+    /// The percentage of used space of a drive, violated at or above the threshold.
+    DriveUsage,
+    /// The clock skew of a tablet server, violated above the threshold times max_clock_skew_usec.
+    ClockSkew,
+    /// The status of a tablet server, violated if the tablet server is not alive.
+    TserverStatus,
+}
+/// A lint rule: the value that is expected for a gflag or var, optionally only for a role (master or tserver),
+/// or the threshold for one of the other kinds of checks.
+///
+/// The built-in rules are defined in [crate::lint::builtin_rules], additional rules are read from the `--lint-rules` JSON file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LintRule {
+    pub name: String,
+    #[serde(default)]
+    pub kind: RuleKind,
+    /// The name of the gflag or var, for gflag and var rules.
+    #[serde(default, alias = "gflag", alias = "var")]
+    pub setting: String,
+    pub role: Option<String>,
+    #[serde(default)]
+    pub operator: Operator,
+    #[serde(default)]
+    pub value: String,
+    /// The threshold for replication_factor, drive_usage and clock_skew rules.
+    pub threshold: Option<f64>,
+    pub severity: Severity,
+    pub rationale: String,
+}
+/// A single violation found by the linter.
+#[derive(Debug, Default)]
+pub struct LintViolation {
+    pub severity: Severity,
+    pub rule: String,
+    /// The server (hostname:port) for violations of a server, or the master that provided the cluster config.
+    pub hostname_port: String,
+    pub message: String,
+    pub rationale: String,
+}
+/// Wrapper struct for holding the violations.
+#[derive(Debug, Default)]
+pub struct AllLintViolations {
+    pub violations: Vec<LintViolation>,
+}
@@ -1,17 +1,18 @@
 //! The impls and functions.
 //!
-use std::{sync::mpsc::channel, time::{Instant, Duration}, collections::BTreeMap};
-use chrono::{DateTime, Utc, TimeZone};
+use std::{sync::mpsc::channel, time::{Instant, Duration}, collections::{BTreeMap, BTreeSet}, env, fs, io::{BufRead, BufReader, Read, Seek, SeekFrom, Write}, path::PathBuf};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, Utc, TimeZone};
 use regex::{Regex,Captures};
 use log::*;
 use colored::*;
 use tokio::time;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use scraper::{Html, Selector};
+use flate2::read::GzDecoder;
 use crate::snapshot;
 use crate::Opts;
 use crate::utility;
-use crate::loglines::{AllLogLines, LogLine};
+use crate::loglines::{AllLogLines, LogLine, LogTemplateKey, LogTemplatesDiff, YsqlLogFields};
 
 impl AllLogLines {
     pub fn new() -> Self { Default::default() }
@@ -20,22 +21,26 @@ impl AllLogLines {
         ports: &Vec<&str>,
         snapshot_number: i32,
         parallel: usize,
+        ysql_log_line_prefix: &str,
     ) -> Result<()>
     {
         info!("begin snapshot");
         let timer = Instant::now();
 
-        let allloglines = AllLogLines::read_loglines(hosts, ports, parallel).await;
+        let allloglines = AllLogLines::read_loglines(hosts, ports, parallel, ysql_log_line_prefix).await;
         snapshot::save_snapshot_json(snapshot_number, "loglines", allloglines.loglines)?;
 
         info!("end snapshot: {:?}", timer.elapsed());
 
         Ok(())
     }
+    /// Read `/logs` of all servers.
+    /// The ysql_log_line_prefix is the YSQL log_line_prefix, which is used for the YSQL (PostgreSQL format) logs.
     pub async fn read_loglines(
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
         parallel: usize,
+        ysql_log_line_prefix: &str,
     ) -> AllLogLines
     {
         info!("begin parallel http read");
@@ -50,7 +55,7 @@ impl AllLogLines {
                     let tx = tx.clone();
                     s.spawn(move |_| {
                         // no detail_snapshot_time: the time of the logline is part of LogLine!
-                        let mut loglines = AllLogLines::read_http(host, port);
+                        let mut loglines = AllLogLines::read_http(host, port, ysql_log_line_prefix);
                         loglines.iter_mut().for_each(|r| r.hostname_port = Some(format!("{}:{}", host, port)));
                         tx.send(loglines).expect("error sending data via tx");
                     });
@@ -74,17 +79,17 @@ impl AllLogLines {
     fn read_http(
         host: &str,
         port: &str,
+        ysql_log_line_prefix: &str,
     ) -> Vec<LogLine>
     {
         let data_from_http = utility::http_get(host, port, "logs");
-        AllLogLines::parse_loglines(data_from_http)
+        AllLogLines::parse_loglines(data_from_http, ysql_log_line_prefix)
     }
     fn parse_loglines(
-        http_data: String
+        http_data: String,
+        ysql_log_line_prefix: &str,
     ) -> Vec<LogLine>
     {
-        let mut loglines: Vec<LogLine> = Vec::new();
-
         let html = Html::parse_document(&http_data);
 
         //  <div class='yb-main container-fluid'>
@@ -107,67 +112,275 @@ impl AllLogLines {
         let selector = Selector::parse("div.yb-main > pre").unwrap();
 
         // If the selector returns a result, start parsing glog lines
-        if let Some(raw_loglines) = html.select(&selector).next() {
-            // fs_manager:
-            //I0217 10:12:35.491056 26960 fs_manager.cc:278] Opened local filesystem: /mnt/d0
-            //uuid: "05b8d17620eb4cd79eddaddb2fbcbb42"
-            //format_stamp: "Formatted at 2022-02-13 16:26:17 on yb-1.local"
-            let regular_log_line = Regex::new( r"([IWFE])(\d{2}\d{2} \d{2}:\d{2}:\d{2}\.\d{6})\s+(\d{1,6}) ([a-z_A-Z.:0-9]*)] (.*)\n" ).unwrap();
-
-            // Just take the year, it's not in the loglines, however, when the year switches this will lead to error results
-            let year= Utc::now().format("%Y").to_string();
-            let to_logline = |captures: Captures<'_>|
+        match html.select(&selector).next()
+        {
+            Some(raw_loglines) => {
+                // The year is not in the loglines, and /logs shows the last part of the log, which ends now.
+                // If the first log line is of a later month than now, the log started in the previous year.
+                let raw_loglines = raw_loglines.text().collect::<String>();
+                let now = Utc::now();
+                let year = match Regex::new(r"[IWFE](\d{2})\d{2} \d{2}:\d{2}:\d{2}\.\d{6}").unwrap().captures(&raw_loglines)
                 {
-                    let timestamp_string = format!("{}{}", year, &captures[2]);
-                    let timestamp = Utc
-                        .datetime_from_str(&timestamp_string, "%Y%m%d %H:%M:%S.%6f")
-                        .unwrap();
-
-                    LogLine {
-                        severity: captures[1].to_string(),
-                        timestamp,
-                        tid: captures[3].to_string(),
-                        sourcefile_nr: captures[4].to_string(),
-                        message: captures[5].to_string(),
-                        ..Default::default()
-                    }
+                    Some(captures) if captures[1].parse::<u32>().unwrap_or_default() > now.month() => now.year() - 1,
+                    _ => now.year(),
                 };
-            // Find first log line.  Any non-regular-log-line data at the beginning of
-            // the logs is discarded.  `remaining` covers all the logs following the
-            // first regular log line.
-            let mut logline;
-            let mut remaining;
-            let stored_raw_loglines = &raw_loglines.text().collect::<String>();
-            match regular_log_line.captures(stored_raw_loglines)
+                AllLogLines::parse_log_text(&raw_loglines, &year.to_string(), ysql_log_line_prefix)
+            },
+            // YSQL /logs is not inside <div class='yb-main..
+            None => AllLogLines::parse_ysql_log(&html.root_element().text().collect::<String>(), ysql_log_line_prefix),
+        }
+    }
+    /// Parse the text of a log, which is in glog format, or if no glog lines are found, in YSQL (PostgreSQL) format.
+    fn parse_log_text(
+        raw_loglines: &str,
+        year: &str,
+        ysql_log_line_prefix: &str,
+    ) -> Vec<LogLine>
+    {
+        let loglines = AllLogLines::parse_glog(raw_loglines, year);
+        if loglines.is_empty()
+        {
+            AllLogLines::parse_ysql_log(raw_loglines, ysql_log_line_prefix)
+        }
+        else
+        {
+            loglines
+        }
+    }
+    /// Parse the text of a YSQL (PostgreSQL format) log into log lines, using the log_line_prefix to find the log lines.
+    ///
+    /// DETAIL, HINT, CONTEXT, QUERY and LOCATION lines are added to the message of the log line they belong to,
+    /// a STATEMENT line sets the statement of the log line it belongs to.
+    /// Lines that are not log lines are continuation lines, such as the next lines of a multiline statement.
+    ///
+    /// The timestamp is converted to UTC using the zone that follows it: UTC, GMT or a numeric offset (+01, +0100, +01:00).
+    /// A zone abbreviation other than UTC or GMT cannot be converted, and is taken as UTC with a warning.
+    fn parse_ysql_log(
+        raw_loglines: &str,
+        log_line_prefix: &str,
+    ) -> Vec<LogLine>
+    {
+        let ysql_log_line = ysql_log_line_regex(log_line_prefix);
+        let mut loglines: Vec<LogLine> = Vec::new();
+        // true if the continuation lines belong to the statement of a STATEMENT line.
+        let mut in_statement = false;
+        // the zones that could not be converted, to warn once per zone.
+        let mut unknown_zones: BTreeSet<String> = BTreeSet::new();
+
+        for line in raw_loglines.lines()
+        {
+            match ysql_log_line.captures(line)
             {
-                None => return loglines,
                 Some(captures) => {
-                    let offset = captures.get(0).map(|m| m.end()).unwrap_or(0);
-                    remaining = &stored_raw_loglines[offset..];
-                    logline = to_logline(captures);
+                    let level = &captures["level"];
+                    let message = &captures["message"];
+                    match (level, loglines.last_mut())
+                    {
+                        ("STATEMENT", Some(logline)) => {
+                            logline.ysql.get_or_insert_with(Default::default).statement = Some(message.to_string());
+                            in_statement = true;
+                        },
+                        ("DETAIL" | "HINT" | "CONTEXT" | "QUERY" | "LOCATION", Some(logline)) => {
+                            logline.message += &format!("\n{}:  {}", level, message);
+                            in_statement = false;
+                        },
+                        _ => {
+                            let field = |name: &str| captures.name(name).map(|m| m.as_str().to_string()).filter(|value| !value.is_empty());
+                            // a log line without a valid timestamp gets the timestamp of the previous log line,
+                            // or is discarded if it is the first log line.
+                            let zone = captures.name("zone").or_else(|| captures.name("offset")).map(|m| m.as_str());
+                            let Some(timestamp) = captures.name("timestamp")
+                                .and_then(|m| NaiveDateTime::parse_from_str(m.as_str(), "%Y-%m-%d %H:%M:%S%.f").ok())
+                                .map(|timestamp| ysql_timestamp_to_utc(&timestamp, zone).unwrap_or_else(|| {
+                                    if unknown_zones.insert(zone.unwrap_or_default().to_string())
+                                    {
+                                        warn!("Unknown time zone {} in YSQL log line, timestamp taken as UTC: {}", zone.unwrap_or_default(), line);
+                                    }
+                                    Utc.from_utc_datetime(&timestamp)
+                                }))
+                                .or_else(|| loglines.last().map(|logline| logline.timestamp))
+                            else {
+                                warn!("Log line without a valid timestamp discarded: {}", line);
+                                continue;
+                            };
+                            loglines.push(LogLine {
+                                severity: ysql_level_to_severity(level).to_string(),
+                                timestamp,
+                                tid: field("pid").unwrap_or_default(),
+                                sourcefile_nr: level.to_string(),
+                                message: message.to_string(),
+                                ysql: Some(YsqlLogFields {
+                                    user: field("user"),
+                                    database: field("database"),
+                                    sqlstate: field("sqlstate"),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            });
+                            in_statement = false;
+                        },
+                    }
+                },
+                None => {
+                    // any text before the first log line is discarded.
+                    if let Some(logline) = loglines.last_mut()
+                    {
+                        match logline.ysql.as_mut().and_then(|ysql| ysql.statement.as_mut())
+                        {
+                            Some(statement) if in_statement => *statement += &format!("\n{}", line),
+                            _ => logline.message += &format!("\n{}", line),
+                        }
+                    }
+                },
+            }
+        }
+
+        // log_min_duration_statement and log_duration:
+        // duration: 1234.567 ms  statement: select * from t
+        // duration: 1234.567 ms  execute <unnamed>: select * from t where id=$1
+        let duration_line = Regex::new(r"(?s)^duration: (\d+(?:\.\d+)?) ms(?:\s+(?:statement|execute [^:]*|parse [^:]*|bind [^:]*): (.*))?").unwrap();
+        for logline in loglines.iter_mut()
+        {
+            if let (Some(captures), Some(ysql)) = (duration_line.captures(&logline.message), logline.ysql.as_mut())
+            {
+                ysql.duration_ms = captures[1].parse().ok();
+                if let Some(statement) = captures.get(2)
+                {
+                    ysql.statement = Some(statement.as_str().trim().to_string());
                 }
+            }
+        }
+
+        loglines
+    }
+    /// Parse the text of a glog file into log lines.
+    /// The glog line format does not contain the year, so the year of the first log line must be provided.
+    /// When the month decreases between log lines (December to January), the year is incremented.
+    /// A log line with a timestamp that is not a valid date (February 29 in a non-leap year) gets the timestamp of the previous log line,
+    /// or is discarded if it is the first log line.
+    fn parse_glog(
+        stored_raw_loglines: &str,
+        year: &str,
+    ) -> Vec<LogLine>
+    {
+        let mut loglines: Vec<LogLine> = Vec::new();
+
+        // fs_manager:
+        //I0217 10:12:35.491056 26960 fs_manager.cc:278] Opened local filesystem: /mnt/d0
+        //uuid: "05b8d17620eb4cd79eddaddb2fbcbb42"
+        //format_stamp: "Formatted at 2022-02-13 16:26:17 on yb-1.local"
+        let regular_log_line = Regex::new( r"([IWFE])(\d{2}\d{2} \d{2}:\d{2}:\d{2}\.\d{6})\s+(\d{1,6}) ([a-z_A-Z.:0-9]*)] (.*)\n" ).unwrap();
+
+        let mut year: i32 = year.parse().unwrap_or_else(|_| Utc::now().year());
+        let mut previous_month: Option<u32> = None;
+        let mut previous_timestamp: Option<DateTime<Utc>> = None;
+        let mut to_logline = |captures: &Captures<'_>| -> Option<LogLine>
+            {
+                let month = captures[2][..2].parse::<u32>().ok();
+                if month < previous_month
+                {
+                    year += 1;
+                }
+                previous_month = month;
+                let timestamp_string = format!("{}{}", year, &captures[2]);
+                let timestamp = match NaiveDateTime::parse_from_str(&timestamp_string, "%Y%m%d %H:%M:%S%.6f")
+                {
+                    Ok(timestamp) => Utc.from_utc_datetime(&timestamp),
+                    Err(error) => {
+                        warn!("Invalid timestamp {} in log line, {}: {}", timestamp_string, error, &captures[0].trim_end());
+                        previous_timestamp?
+                    },
+                };
+                previous_timestamp = Some(timestamp);
+
+                Some(LogLine {
+                    severity: captures[1].to_string(),
+                    timestamp,
+                    tid: captures[3].to_string(),
+                    sourcefile_nr: captures[4].to_string(),
+                    message: captures[5].to_string(),
+                    ..Default::default()
+                })
             };
-            // For each subsequent match, append any lines before the match to the
-            // current `LogLine`, store it, and start a new `LogLine`.  Update where
-            // we are in the logs by updating `remaining`.
-            while let Some(captures) = regular_log_line.captures(remaining)
+        // Any non-regular-log-line data before the first log line is discarded.
+        // For each match, append any lines before the match to the current `LogLine`, store it, and start a new `LogLine`.
+        // Update where we are in the logs by updating `remaining`.
+        let mut logline: Option<LogLine> = None;
+        let mut remaining = stored_raw_loglines;
+        while let Some(captures) = regular_log_line.captures(remaining)
+        {
+            let all = captures.get(0).unwrap();
+            if let Some(new_logline) = to_logline(&captures)
             {
-                let all = captures.get(0).unwrap();
-                let from = all.start();
-                let offset = all.end();
-                logline.message += &remaining[..from];
-                loglines.push(logline);
-                logline = to_logline(captures);
-                remaining = &remaining[offset..]
+                if let Some(mut logline) = logline.replace(new_logline)
+                {
+                    logline.message += &remaining[..all.start()];
+                    loglines.push(logline);
+                }
             }
+            remaining = &remaining[all.end()..];
+        }
 
-            // Append final logline and return
+        // Append final logline and return
+        if let Some(mut logline) = logline
+        {
             logline.message += remaining;
             loglines.push(logline);
         }
 
         loglines
     }
+    /// Read a glog file from disk, such as `yb-tserver.INFO` or `yb-master.WARNING.<date>.gz` from a support bundle.
+    /// A file that ends with `.gz` is decompressed.
+    /// The year of the log lines is taken from the 'Log file created at' header, if present, otherwise the current year is used.
+    fn read_glog_file(
+        file_name: &str,
+        hostname_port: &str,
+        ysql_log_line_prefix: &str,
+    ) -> Result<Vec<LogLine>>
+    {
+        let file = fs::File::open(file_name)
+            .with_context(|| format!("Cannot open file: {}", file_name))?;
+        let mut raw_bytes: Vec<u8> = Vec::new();
+        if file_name.ends_with(".gz")
+        {
+            GzDecoder::new(file).read_to_end(&mut raw_bytes)
+                .with_context(|| format!("Error decompressing file: {}", file_name))?;
+        }
+        else
+        {
+            BufReader::new(file).read_to_end(&mut raw_bytes)
+                .with_context(|| format!("Error reading file: {}", file_name))?;
+        }
+        let raw_loglines = String::from_utf8_lossy(&raw_bytes);
+
+        // Log file created at: 2023/02/03 11:13:22
+        let year = Regex::new(r"Log file created at: (\d{4})/").unwrap()
+            .captures(&raw_loglines)
+            .map(|captures| captures[1].to_string())
+            .unwrap_or_else(|| Utc::now().format("%Y").to_string());
+
+        let mut loglines = AllLogLines::parse_log_text(&raw_loglines, &year, ysql_log_line_prefix);
+        loglines.iter_mut().for_each(|r| r.hostname_port = Some(hostname_port.to_string()));
+        Ok(loglines)
+    }
+    /// Read glog files or YSQL `postgresql-*.log` files from disk and label the log lines with hostname_port.
+    pub fn read_glog_files(
+        file_names: &[String],
+        hostname_port: &str,
+        ysql_log_line_prefix: &str,
+    ) -> Result<AllLogLines>
+    {
+        let mut allloglines = AllLogLines::new();
+        for file_name in file_names
+        {
+            let loglines = AllLogLines::read_glog_file(file_name, hostname_port, ysql_log_line_prefix)?;
+            info!("log lines read from {}: {}", file_name, loglines.len());
+            allloglines.loglines.extend(loglines);
+        }
+        allloglines.loglines.sort_by_key(|logline| logline.timestamp);
+        Ok(allloglines)
+    }
     pub fn print(
         &self,
         hostname_filter: &Regex,
@@ -183,9 +396,7 @@ impl AllLogLines {
         // use the sorted vector to loop over.
         for row in &sorted_loglines
         {
-            if hostname_filter.is_match(&row.hostname_port.clone().expect("no hostname:port set"))
-                && log_severity.contains(&row.severity)
-                && ( stat_name_filter.is_match(&row.message) || stat_name_filter.is_match(&row.sourcefile_nr) )
+            if row.is_selected(hostname_filter, stat_name_filter, log_severity)
             {
                 print!("{:20} {:33} ", row.hostname_port.clone().expect("no hostname:port set"), row.timestamp);
                 match row.severity.as_str()
@@ -203,6 +414,634 @@ impl AllLogLines {
     }
 }
 
+impl LogLine {
+    /// True if the log line matches the hostname filter, the log severities, and the stat name filter for the message or source file.
+    fn is_selected(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    ) -> bool
+    {
+        hostname_filter.is_match(self.hostname_port.as_ref().expect("no hostname:port set"))
+            && log_severity.contains(&self.severity)
+            && ( stat_name_filter.is_match(&self.message) || stat_name_filter.is_match(&self.sourcefile_nr) )
+    }
+}
+
+/// The glog severity letter for a PostgreSQL level, so --log-severity works for YSQL log lines too.
+fn ysql_level_to_severity(
+    level: &str,
+) -> &'static str
+{
+    match level
+    {
+        "WARNING" => "W",
+        "ERROR" => "E",
+        "FATAL" | "PANIC" => "F",
+        _ => "I",
+    }
+}
+
+/// Convert a YSQL log timestamp to UTC, using the zone of the timestamp.
+/// No zone, UTC, GMT and numeric offsets (+01, +0100, +01:00) are converted, other zone abbreviations return None.
+fn ysql_timestamp_to_utc(
+    timestamp: &NaiveDateTime,
+    zone: Option<&str>,
+) -> Option<DateTime<Utc>>
+{
+    let offset_seconds = match zone
+    {
+        None | Some("UTC") | Some("GMT") | Some("Z") => 0,
+        Some(zone) if zone.starts_with(['+', '-']) => {
+            let digits = zone[1..].replace(':', "");
+            let hours = digits.get(..2)?.parse::<i32>().ok()?;
+            let minutes = if digits.len() > 2 { digits.get(2..4)?.parse::<i32>().ok()? } else { 0 };
+            let seconds = hours * 3600 + minutes * 60;
+            if zone.starts_with('-') { -seconds } else { seconds }
+        },
+        Some(_) => return None,
+    };
+    FixedOffset::east_opt(offset_seconds)?
+        .from_local_datetime(timestamp)
+        .single()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Create the regex for a YSQL log line from the log_line_prefix.
+/// The prefix escapes timestamp (%m, %t) with its zone, process id (%p), user (%u), database (%d) and SQLSTATE (%e) are captured,
+/// the other escapes are matched, but not captured.
+fn ysql_log_line_regex(
+    log_line_prefix: &str,
+) -> Regex
+{
+    let mut pattern = String::from("^");
+    let mut captured: BTreeSet<&str> = BTreeSet::new();
+    let mut characters = log_line_prefix.chars();
+    while let Some(character) = characters.next()
+    {
+        if character != '%'
+        {
+            pattern += &regex::escape(&character.to_string());
+            continue;
+        }
+        let Some(escape) = characters.next() else { break };
+        // name of the capture group, pattern, pattern after the capture group.
+        let (name, escape_pattern, suffix) = match escape
+        {
+            'm' | 't' => (Some("timestamp"), r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?", r"(?: (?P<zone>[A-Za-z]+)| ?(?P<offset>[+-]\d{2}(?::?\d{2})?))?"),
+            'p' => (Some("pid"), r"\d+", ""),
+            'u' => (Some("user"), r"[^\s@\[\],]*", ""),
+            'd' => (Some("database"), r"[^\s@\[\],]*", ""),
+            'e' => (Some("sqlstate"), r"[0-9A-Z]{5}", ""),
+            'l' => (None, r"\d+", ""),
+            'c' => (None, r"[0-9a-f]+\.[0-9a-f]+", ""),
+            '%' => (None, "%", ""),
+            'q' => (None, "", ""),
+            _ => (None, r".*?", ""),
+        };
+        match name
+        {
+            Some(name) if captured.insert(name) => pattern += &format!("(?P<{}>{}){}", name, escape_pattern, suffix),
+            // only the zone of the captured timestamp is captured.
+            _ => pattern += &format!("(?:{}){}", escape_pattern, suffix.replace("(?P<zone>", "(?:").replace("(?P<offset>", "(?:")),
+        }
+    }
+    pattern += r"(?P<level>LOG|ERROR|WARNING|FATAL|PANIC|NOTICE|INFO|DEBUG[1-5]?|DETAIL|HINT|STATEMENT|CONTEXT|QUERY|LOCATION):\s+(?P<message>.*)$";
+    Regex::new(&pattern).expect("the log_line_prefix regex should be valid")
+}
+
+impl AllLogLines {
+    /// Print the YSQL statements with a duration, logged by log_min_duration_statement or log_duration, ranked by duration.
+    pub fn print_ysql_slow_statements(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+    )
+    {
+        let mut slow_statements: Vec<(&LogLine, &YsqlLogFields, f64)> = self.loglines
+            .iter()
+            .filter(|logline| hostname_filter.is_match(logline.hostname_port.as_ref().expect("no hostname:port set")))
+            .filter_map(|logline| logline.ysql.as_ref().map(|ysql| (logline, ysql)))
+            .filter_map(|(logline, ysql)| ysql.duration_ms.map(|duration_ms| (logline, ysql, duration_ms)))
+            .filter(|(_, ysql, _)| stat_name_filter.is_match(ysql.statement.as_deref().unwrap_or_default()))
+            .collect();
+        slow_statements.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+        println!("{:20} {:33} {:>12} {:15} {:15} statement", "hostname_port", "timestamp", "duration ms", "user", "database");
+        for (logline, ysql, duration_ms) in slow_statements
+        {
+            println!("{:20} {:33} {:12.3} {:15} {:15} {}",
+                     logline.hostname_port.as_ref().expect("no hostname:port set"),
+                     logline.timestamp,
+                     duration_ms,
+                     ysql.user.as_deref().unwrap_or("-"),
+                     ysql.database.as_deref().unwrap_or("-"),
+                     ysql.statement.as_deref().unwrap_or_default().replace('\n', " "),
+            );
+        }
+    }
+}
+
+/// The key that identifies a log line when the same log line is read again: timestamp, hostname_port and sourcefile_nr.
+/// This is the same key that [tail_loglines] uses.
+type LogLineKey = (DateTime<Utc>, String, String);
+/// The keys of the last archived log lines, and the last archived timestamp per hostname_port.
+type LogArchiveLastKeys = (BTreeSet<LogLineKey>, BTreeMap<String, DateTime<Utc>>);
+
+/// The number of bytes at the end of a log archive file that is read to find the last archived log lines.
+const LOG_ARCHIVE_TAIL_BYTES: u64 = 64 * 1024;
+
+/// The directory of the log archive: yb_stats.snapshots/log_archive.
+fn log_archive_directory() -> Result<PathBuf>
+{
+    let current_directory = env::current_dir()?;
+    Ok(current_directory.join("yb_stats.snapshots").join("log_archive"))
+}
+
+impl AllLogLines {
+    /// Append log lines to the log archive, which is a file per server: `loglines_<hostname:port>.jsonl`.
+    /// Every line in the file is a [LogLine] in JSON format.
+    fn append_log_archive(
+        &self,
+    ) -> Result<()>
+    {
+        let log_archive_directory = log_archive_directory()?;
+        fs::create_dir_all(&log_archive_directory)
+            .with_context(|| format!("Cannot create directory: {}", log_archive_directory.display()))?;
+
+        let mut per_hostname_port: BTreeMap<&String, Vec<&LogLine>> = BTreeMap::new();
+        for logline in &self.loglines
+        {
+            per_hostname_port.entry(logline.hostname_port.as_ref().expect("no hostname:port set")).or_default().push(logline);
+        }
+        for (hostname_port, loglines) in per_hostname_port
+        {
+            let archive_file = &log_archive_directory.join(format!("loglines_{}.jsonl", hostname_port));
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(archive_file)
+                .with_context(|| format!("Cannot create file: {}", archive_file.display()))?;
+            for logline in loglines
+            {
+                writeln!(file, "{}", serde_json::to_string(logline).with_context(|| "Json serialization error")?)
+                    .with_context(|| format!("Error writing file: {}", archive_file.display()))?;
+            }
+        }
+        Ok(())
+    }
+    /// Read the log lines from the log archive with a timestamp between begin and end (inclusive),
+    /// that are selected by the hostname filter, stat name filter and log severities.
+    /// The log lines are filtered while reading, and archive files of servers that do not match the hostname filter are not read.
+    pub fn read_log_archive(
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    ) -> Result<AllLogLines>
+    {
+        let mut allloglines = AllLogLines::new();
+        let log_archive_directory = log_archive_directory()?;
+        if !log_archive_directory.exists()
+        {
+            return Ok(allloglines);
+        }
+        for entry in fs::read_dir(&log_archive_directory)
+            .with_context(|| format!("Cannot read directory: {}", log_archive_directory.display()))?
+        {
+            let archive_file = entry?.path();
+            if archive_file.extension().map(|extension| extension != "jsonl").unwrap_or(true)
+            {
+                continue;
+            }
+            let hostname_port = archive_file.file_stem()
+                .and_then(|file_stem| file_stem.to_str())
+                .and_then(|file_stem| file_stem.strip_prefix("loglines_"))
+                .unwrap_or_default();
+            if !hostname_filter.is_match(hostname_port)
+            {
+                continue;
+            }
+            let file = fs::File::open(&archive_file)
+                .with_context(|| format!("Cannot open file: {}", archive_file.display()))?;
+            for line in BufReader::new(file).lines()
+            {
+                let logline: LogLine = serde_json::from_str(&line?)
+                    .with_context(|| format!("Json deserialization error: {}", archive_file.display()))?;
+                if logline.timestamp >= begin
+                    && logline.timestamp <= end
+                    && logline.is_selected(hostname_filter, stat_name_filter, log_severity)
+                {
+                    allloglines.loglines.push(logline);
+                }
+            }
+        }
+        Ok(allloglines)
+    }
+    /// Read the complete log lines in the last tail_bytes of a log archive file.
+    /// The first line in the tail is skipped, because it can be partial.
+    /// If the tail does not contain a complete line, the tail is doubled until it does, or the whole file is read.
+    fn read_log_archive_tail<R: Read + Seek>(
+        reader: &mut R,
+        tail_bytes: u64,
+    ) -> Result<Vec<LogLine>>
+    {
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let mut tail_bytes = tail_bytes.max(1);
+        loop
+        {
+            let start = file_size.saturating_sub(tail_bytes);
+            reader.seek(SeekFrom::Start(start))?;
+            let mut tail = String::new();
+            reader.take(file_size - start).read_to_string(&mut tail)?;
+            // a tail that does not start at the beginning of the file can start with a partial line.
+            let lines: Vec<&str> = tail.lines().skip(if start > 0 { 1 } else { 0 }).filter(|line| !line.is_empty()).collect();
+            if lines.is_empty()
+                && start > 0
+            {
+                tail_bytes *= 2;
+                continue;
+            }
+            return lines
+                .iter()
+                .map(|line| serde_json::from_str(line).with_context(|| "Json deserialization error"))
+                .collect();
+        }
+    }
+    /// The keys of the log lines in the log archive with the latest timestamp per server,
+    /// and the latest timestamp per server.
+    /// This allows a collector to continue an existing archive without duplicating log lines.
+    /// The log lines are appended to the archive in order, so only the tail of every archive file is read.
+    fn log_archive_last_keys(
+    ) -> Result<LogArchiveLastKeys>
+    {
+        let mut allloglines = AllLogLines::new();
+        let log_archive_directory = log_archive_directory()?;
+        if log_archive_directory.exists()
+        {
+            for entry in fs::read_dir(&log_archive_directory)
+                .with_context(|| format!("Cannot read directory: {}", log_archive_directory.display()))?
+            {
+                let archive_file = entry?.path();
+                if archive_file.extension().map(|extension| extension != "jsonl").unwrap_or(true)
+                {
+                    continue;
+                }
+                let mut file = fs::File::open(&archive_file)
+                    .with_context(|| format!("Cannot open file: {}", archive_file.display()))?;
+                allloglines.loglines.extend(AllLogLines::read_log_archive_tail(&mut file, LOG_ARCHIVE_TAIL_BYTES)
+                    .with_context(|| format!("Error reading log archive: {}", archive_file.display()))?);
+            }
+        }
+        let mut last_timestamps: BTreeMap<String, DateTime<Utc>> = BTreeMap::new();
+        for logline in &allloglines.loglines
+        {
+            let last_timestamp = last_timestamps.entry(logline.hostname_port.clone().expect("no hostname:port set")).or_insert(logline.timestamp);
+            if logline.timestamp > *last_timestamp
+            {
+                *last_timestamp = logline.timestamp;
+            }
+        }
+        let last_keys = allloglines.loglines
+            .into_iter()
+            .filter(|logline| last_timestamps.get(logline.hostname_port.as_ref().expect("no hostname:port set")) == Some(&logline.timestamp))
+            .map(|logline| (logline.timestamp, logline.hostname_port.expect("no hostname:port set"), logline.sourcefile_nr))
+            .collect();
+        Ok((last_keys, last_timestamps))
+    }
+    /// Take the log lines that are not in the previous read, and not older than the last archived log line of the server.
+    fn new_loglines(
+        self,
+        previous_keys: &BTreeSet<LogLineKey>,
+        last_timestamps: &BTreeMap<String, DateTime<Utc>>,
+    ) -> AllLogLines
+    {
+        let mut allloglines = AllLogLines::new();
+        allloglines.loglines = self.loglines
+            .into_iter()
+            .filter(|logline| !previous_keys.contains(&(logline.timestamp, logline.hostname_port.clone().expect("no hostname:port set"), logline.sourcefile_nr.clone())))
+            .filter(|logline| last_timestamps.get(logline.hostname_port.as_ref().expect("no hostname:port set")).map(|last_timestamp| logline.timestamp >= *last_timestamp).unwrap_or(true))
+            .collect();
+        allloglines
+    }
+    /// The keys of the log lines.
+    fn log_archive_keys(&self) -> BTreeSet<LogLineKey>
+    {
+        self.loglines
+            .iter()
+            .map(|logline| (logline.timestamp, logline.hostname_port.clone().expect("no hostname:port set"), logline.sourcefile_nr.clone()))
+            .collect()
+    }
+    /// Update the keys of the previous read and the last archived timestamp per server after the new log lines (self) of a read are archived.
+    /// A server that returned no log lines, for example because the http read failed, keeps its previous keys,
+    /// so its log lines are not archived again when it returns them on the next read.
+    fn update_log_archive_keys(
+        &self,
+        read_keys: BTreeSet<LogLineKey>,
+        previous_keys: &mut BTreeSet<LogLineKey>,
+        last_timestamps: &mut BTreeMap<String, DateTime<Utc>>,
+    )
+    {
+        let read_hostname_ports: BTreeSet<String> = read_keys
+            .iter()
+            .map(|(_, hostname_port, _)| hostname_port.clone())
+            .collect();
+        // a log line that disappeared from /logs does not come back, so only the keys of the last read of a server are needed.
+        previous_keys.retain(|(_, hostname_port, _)| !read_hostname_ports.contains(hostname_port));
+        previous_keys.extend(read_keys);
+        for logline in &self.loglines
+        {
+            let last_timestamp = last_timestamps.entry(logline.hostname_port.clone().expect("no hostname:port set")).or_insert(logline.timestamp);
+            if logline.timestamp > *last_timestamp
+            {
+                *last_timestamp = logline.timestamp;
+            }
+        }
+    }
+}
+
+/// Read `/logs` every collect_log_interval seconds, and append the new log lines to the log archive.
+/// This runs until it is stopped.
+pub async fn collect_loglines(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let mut interval = time::interval(Duration::from_secs(options.collect_log_interval));
+
+    let (mut previous_keys, mut last_timestamps) = AllLogLines::log_archive_last_keys()?;
+
+    println!("Collecting log lines into {}, press ctrl-c to stop.", log_archive_directory()?.display());
+
+    loop
+    {
+        interval.tick().await;
+        let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await;
+        let read_keys = allloglines.log_archive_keys();
+        let new_loglines = allloglines.new_loglines(&previous_keys, &last_timestamps);
+        new_loglines.append_log_archive()?;
+        info!("log lines appended to the log archive: {}", new_loglines.loglines.len());
+        new_loglines.update_log_archive_keys(read_keys, &mut previous_keys, &mut last_timestamps);
+    }
+}
+
+/// Print the log lines from the log archive between the begin and end snapshot times.
+/// This is used by --log-diff, and by --print-log with --begin and --end.
+pub async fn print_log_archive(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let (begin_snapshot, end_snapshot, begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+    let end_snapshot_timestamp = snapshot::Snapshot::read_snapshot_timestamp(&end_snapshot)?;
+
+    let allloglines = AllLogLines::read_log_archive(begin_snapshot_row.timestamp.with_timezone(&Utc), end_snapshot_timestamp.with_timezone(&Utc), &hostname_filter, &stat_name_filter, &options.log_severity)?;
+    if allloglines.loglines.is_empty()
+    {
+        println!("No log lines found in the log archive between snapshot {} and {}, the log archive is created with --collect-log.", begin_snapshot, end_snapshot);
+    }
+    allloglines.print(&hostname_filter, &stat_name_filter, &options.log_severity)?;
+
+    Ok(())
+}
+
+/// The regular expressions and replacements to turn a log message into a template.
+/// The order matters: hybrid times, UUIDs and ids, and hostnames and ip addresses contain numbers, and therefore are replaced first.
+fn template_regexes() -> Vec<(Regex, &'static str)>
+{
+    [
+        (r"\{ (days: \d+, )?physical: \d+( logical: \d+)? \}", "<ht>"),
+        (r"HT\{[^}]*\}", "<ht>"),
+        (r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b", "<uuid>"),
+        (r"\b[0-9a-f]{32}\b", "<id>"),
+        (r"\b\d{1,3}(\.\d{1,3}){3}(:\d+)?\b", "<host>"),
+        (r"\b[a-zA-Z][-a-zA-Z0-9]*(\.[-a-zA-Z0-9]+)+:\d+\b", "<host>"),
+        (r"\b0x[0-9a-fA-F]+\b", "<n>"),
+        (r"\b\d+(\.\d+)?(e[-+]?\d+)?", "<n>"),
+    ]
+        .iter()
+        .map(|(regex, replacement)| (Regex::new(regex).unwrap(), *replacement))
+        .collect()
+}
+
+/// Turn a log message into a template by masking hybrid times, UUIDs, tablet ids, hostnames and numbers.
+/// Only the first line of the message is used, the following lines are for example a stack trace.
+pub fn log_message_template(
+    message: &str,
+    template_regexes: &[(Regex, &str)],
+) -> String
+{
+    let first_line = message.trim().lines().next().unwrap_or_default();
+    template_regexes
+        .iter()
+        .fold(first_line.to_string(), |template, (regex, replacement)| regex.replace_all(&template, *replacement).to_string())
+}
+
+impl AllLogLines {
+    /// Count the log lines per hostname_port, severity, sourcefile_nr and message template.
+    /// Only log lines newer than the timestamp for the hostname_port in newer_than are counted.
+    pub fn templates(
+        &self,
+        newer_than: &BTreeMap<String, DateTime<Utc>>,
+    ) -> BTreeMap<LogTemplateKey, usize>
+    {
+        let template_regexes = template_regexes();
+        let mut templates: BTreeMap<LogTemplateKey, usize> = BTreeMap::new();
+        for logline in &self.loglines
+        {
+            let hostname_port = logline.hostname_port.clone().expect("no hostname:port set");
+            if newer_than.get(&hostname_port).map(|timestamp| logline.timestamp <= *timestamp).unwrap_or(false)
+            {
+                continue;
+            }
+            *templates
+                .entry((hostname_port, logline.severity.clone(), logline.sourcefile_nr.clone(), log_message_template(&logline.message, &template_regexes)))
+                .or_default() += 1;
+        }
+        templates
+    }
+    /// Print the message templates with the number of log lines, the most frequent first.
+    pub fn print_templates(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    )
+    {
+        let mut templates: Vec<_> = self.templates(&BTreeMap::new())
+            .into_iter()
+            .filter(|((hostname_port, severity, sourcefile_nr, template), _)| hostname_filter.is_match(hostname_port)
+                && log_severity.contains(severity.as_str())
+                && (stat_name_filter.is_match(template) || stat_name_filter.is_match(sourcefile_nr)))
+            .collect();
+        templates.sort_by(|(a_key, a_count), (b_key, b_count)| b_count.cmp(a_count).then(a_key.cmp(b_key)));
+        for ((hostname_port, severity, sourcefile_nr, template), count) in templates
+        {
+            println!("{:>8} {:20} {} {:30} {}", count, hostname_port, severity, sourcefile_nr, template);
+        }
+    }
+}
+
+impl LogTemplatesDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<LogTemplatesDiff>
+    {
+        let mut logtemplatesdiff = LogTemplatesDiff::new();
+
+        let mut allloglines = AllLogLines::new();
+        allloglines.loglines = snapshot::read_snapshot_json(begin_snapshot, "loglines")?;
+        logtemplatesdiff.first_snapshot(allloglines);
+
+        let mut allloglines = AllLogLines::new();
+        allloglines.loglines = snapshot::read_snapshot_json(end_snapshot, "loglines")?;
+        logtemplatesdiff.second_snapshot(allloglines);
+
+        Ok(logtemplatesdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allloglines: AllLogLines,
+    )
+    {
+        for logline in &allloglines.loglines
+        {
+            let last_timestamp = self.first_last_timestamp
+                .entry(logline.hostname_port.clone().expect("no hostname:port set"))
+                .or_insert(logline.timestamp);
+            if logline.timestamp > *last_timestamp
+            {
+                *last_timestamp = logline.timestamp;
+            }
+        }
+        for (key, count) in allloglines.templates(&BTreeMap::new())
+        {
+            self.btreelogtemplatesdiff.entry(key).or_default().first_count = count;
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allloglines: AllLogLines,
+    )
+    {
+        for (key, count) in allloglines.templates(&self.first_last_timestamp)
+        {
+            self.btreelogtemplatesdiff.entry(key).or_default().second_count = count;
+        }
+    }
+    /// Print the message templates that have new log lines in the second snapshot, the most frequent first.
+    /// The factor is the number of new log lines compared to the number of log lines in the first snapshot.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        log_severity: &str,
+    )
+    {
+        let mut rows: Vec<_> = self.btreelogtemplatesdiff
+            .iter()
+            .filter(|(_, row)| row.second_count > 0)
+            .filter(|((hostname_port, severity, sourcefile_nr, template), _)| hostname_filter.is_match(hostname_port)
+                && log_severity.contains(severity.as_str())
+                && (stat_name_filter.is_match(template) || stat_name_filter.is_match(sourcefile_nr)))
+            .collect();
+        rows.sort_by(|(a_key, a), (b_key, b)| b.second_count.cmp(&a.second_count).then(a_key.cmp(b_key)));
+        println!("{:>8} {:>8} {:>8} {:20} S {:30} template", "first", "new", "factor", "hostname_port", "sourcefile_nr");
+        for ((hostname_port, severity, sourcefile_nr, template), row) in rows
+        {
+            let factor = if row.first_count == 0 { "new".to_string() } else { format!("{:.1}x", row.second_count as f64 / row.first_count as f64) };
+            println!("{:>8} {:>8} {:>8} {:20} {} {:30} {}",
+                     row.first_count,
+                     row.second_count,
+                     if row.first_count == 0 { factor.yellow() } else if row.second_count > 10 * row.first_count { factor.red() } else { factor.normal() },
+                     hostname_port,
+                     severity,
+                     sourcefile_nr,
+                     template,
+            );
+        }
+    }
+    pub async fn adhoc_read_first_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+        ysql_log_line_prefix: &str,
+    )
+    {
+        let allloglines = AllLogLines::read_loglines(hosts, ports, parallel, ysql_log_line_prefix).await;
+        self.first_snapshot(allloglines);
+    }
+    pub async fn adhoc_read_second_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+        ysql_log_line_prefix: &str,
+    )
+    {
+        let allloglines = AllLogLines::read_loglines(hosts, ports, parallel, ysql_log_line_prefix).await;
+        self.second_snapshot(allloglines);
+    }
+}
+
+pub async fn log_templates_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let logtemplatesdiff = LogTemplatesDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    logtemplatesdiff.print(&hostname_filter, &stat_name_filter, &options.log_severity);
+
+    Ok(())
+}
+
+pub async fn print_log_templates(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+    match options.print_log_templates.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allloglines = AllLogLines::new();
+            allloglines.loglines = snapshot::read_snapshot_json(snapshot_number, "loglines")?;
+            allloglines.print_templates(&hostname_filter, &stat_name_filter, &options.log_severity);
+        },
+        None => {
+            let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await;
+            allloglines.print_templates(&hostname_filter, &stat_name_filter, &options.log_severity);
+        },
+    }
+    Ok(())
+}
+
 pub async fn print_loglines(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -219,14 +1058,70 @@ pub async fn print_loglines(
             allloglines.loglines = snapshot::read_snapshot_json(snapshot_number, "loglines")?;
             allloglines.print(&hostname_filter, &stat_name_filter, &options.log_severity)?;
         },
+        // with a begin and end snapshot, the log lines between the snapshot times are printed from the log archive.
+        None if options.begin.is_some() && options.end.is_some() => print_log_archive(options).await?,
         None => {
-            let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel).await;
+            let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await;
             allloglines.print(&hostname_filter, &stat_name_filter, &options.log_severity)?;
         },
     }
     Ok(())
 }
 
+/// Import glog files from disk into the loglines of a snapshot, so they can be used with --print-log and --print-log-templates.
+/// Without --import-log-snapshot a new snapshot is created.
+pub async fn import_loglines(
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_port = options.import_log_host.as_ref().expect("--import-log-host should be set");
+    let allloglines = AllLogLines::read_glog_files(&options.import_log, hostname_port, &options.ysql_log_line_prefix)?;
+
+    let snapshot_number = match &options.import_log_snapshot
+    {
+        Some(snapshot_number) => snapshot_number.clone(),
+        None => {
+            let snapshot_comment = Some(options.snapshot_comment.clone().unwrap_or_else(|| format!("imported log files {}", hostname_port)));
+            snapshot::Snapshot::insert_new_snapshot_number(&snapshot_comment)?.to_string()
+        },
+    };
+    // the log lines of the other hosts in the snapshot are kept, the log lines of the imported host are replaced.
+    let mut loglines: Vec<LogLine> = snapshot::read_snapshot_json(&snapshot_number, "loglines").unwrap_or_default();
+    loglines.retain(|logline| logline.hostname_port.as_ref() != Some(hostname_port));
+    let imported_loglines = allloglines.loglines.len();
+    loglines.extend(allloglines.loglines);
+    snapshot::save_snapshot_json(snapshot_number.parse()?, "loglines", loglines)?;
+
+    if !options.silent {
+        println!("imported {} log lines for {} into snapshot number {}", imported_loglines, hostname_port, snapshot_number);
+    }
+    Ok(())
+}
+
+pub async fn print_ysql_slow_statements(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+    match options.print_ysql_slow_statements.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allloglines = AllLogLines::new();
+            allloglines.loglines = snapshot::read_snapshot_json(snapshot_number, "loglines")?;
+            allloglines.print_ysql_slow_statements(&hostname_filter, &stat_name_filter);
+        },
+        None => {
+            let allloglines = AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await;
+            allloglines.print_ysql_slow_statements(&hostname_filter, &stat_name_filter);
+        },
+    }
+    Ok(())
+}
+
 pub async fn tail_loglines(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -251,7 +1146,7 @@ pub async fn tail_loglines(
         }
         btreemap
     };
-    let loglines = AllLogLines::read_loglines(&hosts, &ports, parallel).await;
+    let loglines = AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await;
     let mut first_loglines_btreemap = into_btreemap(loglines);
 
     println!("Tail log ready, showing severities: {}", &options.log_severity);
@@ -259,7 +1154,7 @@ pub async fn tail_loglines(
     loop
     {
         let mut display_loglines_btreemap: BTreeMap<(DateTime<Utc>, String, String), SpecialLogLine> = BTreeMap::new();
-        let loglines = AllLogLines::read_loglines(&hosts, &ports, parallel).await;
+        let loglines = AllLogLines::read_loglines(&hosts, &ports, parallel, &options.ysql_log_line_prefix).await;
         let second_loglines_btreemap = into_btreemap(loglines);
         // add all loglines that are not found in the second loglines snapshot to display loglines
         for (key, value) in &second_loglines_btreemap
@@ -297,6 +1192,9 @@ pub async fn tail_loglines(
 mod tests {
     use super::*;
 
+    /// The default log_line_prefix of YSQL.
+    const DEFAULT_YSQL_LOG_LINE_PREFIX: &str = "%m [%p] ";
+
     // This is a test function to test log line output.
     // Run with `cargo test unit_parse_proble_logline -- --nocapture` to see the output of println!
     #[test]
@@ -310,11 +1208,191 @@ I1218 12:12:34.464701  7650 client-internal.cc:2273] Reinitialize master address
 I1218 12:12:34.464808  7650 client-internal.cc:2302] New master addresses: [yb-1.local:7100,yb-2.local:7100,yb-3.local:7100, yb-1.local:7100, yb-2.local:7100, yb-3.local:7100]
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         //assert_eq!(result[0].message,"FLAGS_rocksdb_base_background_compactions was not set, automatically configuring 1 base background compactions.");
         println!("{:#?}", result);
     }
 
+    #[test]
+    fn unit_log_message_template() {
+        let template_regexes = template_regexes();
+        assert_eq!(log_message_template("T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42 [term 3 FOLLOWER]: Leader election lost for term 4. Reason: could not achieve majority.", &template_regexes),
+                   "T <id> P <id> [term <n> FOLLOWER]: Leader election lost for term <n>. Reason: could not achieve majority.");
+        assert_eq!(log_message_template("Failed to heartbeat to 192.168.66.80:7100 after 12.5s, read time: { physical: 1675424002698063 logical: 1 }", &template_regexes),
+                   "Failed to heartbeat to <host> after <n>s, read time: <ht>");
+        assert_eq!(log_message_template("Connection to yb-1.local:7100 lost\n    @ 0x7f3a4c1f2e0d", &template_regexes),
+                   "Connection to <host> lost");
+    }
+
+    #[test]
+    fn unit_new_loglines_for_log_archive() {
+        let logline = |timestamp: &str, sourcefile_nr: &str| LogLine {
+            hostname_port: Some("192.168.66.80:9000".to_string()),
+            severity: "I".to_string(),
+            timestamp: timestamp.parse().unwrap(),
+            sourcefile_nr: sourcefile_nr.to_string(),
+            message: "message".to_string(),
+            ..Default::default()
+        };
+        let mut allloglines = AllLogLines::new();
+        allloglines.loglines.push(logline("2023-02-03T11:13:21Z", "tablet.cc:10"));
+        allloglines.loglines.push(logline("2023-02-03T11:13:22Z", "tablet.cc:10"));
+        allloglines.loglines.push(logline("2023-02-03T11:13:22Z", "tablet.cc:20"));
+        allloglines.loglines.push(logline("2023-02-03T11:13:23Z", "tablet.cc:10"));
+
+        // the archive contains up to 11:13:22 tablet.cc:10
+        let previous_keys: BTreeSet<LogLineKey> = [("2023-02-03T11:13:22Z".parse().unwrap(), "192.168.66.80:9000".to_string(), "tablet.cc:10".to_string())].into_iter().collect();
+        let last_timestamps: BTreeMap<String, DateTime<Utc>> = [("192.168.66.80:9000".to_string(), "2023-02-03T11:13:22Z".parse().unwrap())].into_iter().collect();
+
+        let new_loglines = allloglines.new_loglines(&previous_keys, &last_timestamps);
+        let sourcefile_nrs: Vec<_> = new_loglines.loglines.iter().map(|logline| (logline.timestamp.to_rfc3339(), logline.sourcefile_nr.as_str())).collect();
+        assert_eq!(sourcefile_nrs, vec![("2023-02-03T11:13:22+00:00".to_string(), "tablet.cc:20"), ("2023-02-03T11:13:23+00:00".to_string(), "tablet.cc:10")]);
+
+        // the tail of the archive file skips the first, partial, line.
+        let archive: String = new_loglines.loglines.iter().map(|logline| serde_json::to_string(logline).unwrap() + "\n").collect();
+        let last_line_length = archive.lines().last().unwrap().len() as u64 + 1;
+        let tail = AllLogLines::read_log_archive_tail(&mut std::io::Cursor::new(archive.clone()), last_line_length + 10).unwrap();
+        assert_eq!(tail.iter().map(|logline| logline.sourcefile_nr.as_str()).collect::<Vec<_>>(), vec!["tablet.cc:10"]);
+        // a tail that is too small is doubled until a complete line is read.
+        assert_eq!(AllLogLines::read_log_archive_tail(&mut std::io::Cursor::new(archive.clone()), 10).unwrap().len(), 1);
+        assert_eq!(AllLogLines::read_log_archive_tail(&mut std::io::Cursor::new(archive), 1024 * 1024).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn unit_log_archive_server_missing_for_one_read() {
+        let logline = |hostname_port: &str, timestamp: &str| LogLine {
+            hostname_port: Some(hostname_port.to_string()),
+            severity: "I".to_string(),
+            timestamp: timestamp.parse().unwrap(),
+            sourcefile_nr: "tablet.cc:10".to_string(),
+            message: "message".to_string(),
+            ..Default::default()
+        };
+        let mut previous_keys: BTreeSet<LogLineKey> = BTreeSet::new();
+        let mut last_timestamps: BTreeMap<String, DateTime<Utc>> = BTreeMap::new();
+        let mut archived = 0;
+        let mut collect = |loglines: Vec<LogLine>| {
+            let mut allloglines = AllLogLines::new();
+            allloglines.loglines = loglines;
+            let read_keys = allloglines.log_archive_keys();
+            let new_loglines = allloglines.new_loglines(&previous_keys, &last_timestamps);
+            new_loglines.update_log_archive_keys(read_keys, &mut previous_keys, &mut last_timestamps);
+            archived += new_loglines.loglines.len();
+            new_loglines.loglines.len()
+        };
+
+        assert_eq!(collect(vec![logline("yb-1.local:9000", "2023-02-03T11:13:21Z"), logline("yb-1.local:9000", "2023-02-03T11:13:22Z"), logline("yb-2.local:9000", "2023-02-03T11:13:21Z")]), 3);
+        // the read of yb-1 fails.
+        assert_eq!(collect(vec![logline("yb-2.local:9000", "2023-02-03T11:13:21Z"), logline("yb-2.local:9000", "2023-02-03T11:13:23Z")]), 1);
+        // yb-1 returns the same log lines and one new log line: only the new log line is archived.
+        assert_eq!(collect(vec![logline("yb-1.local:9000", "2023-02-03T11:13:21Z"), logline("yb-1.local:9000", "2023-02-03T11:13:22Z"), logline("yb-1.local:9000", "2023-02-03T11:13:24Z"), logline("yb-2.local:9000", "2023-02-03T11:13:23Z")]), 1);
+        assert_eq!(archived, 5);
+        assert_eq!(last_timestamps["yb-1.local:9000"].to_rfc3339(), "2023-02-03T11:13:24+00:00");
+    }
+
+    #[test]
+    fn unit_log_templates_diff() {
+        let logline = |timestamp: &str, message: &str| LogLine {
+            hostname_port: Some("192.168.66.80:9000".to_string()),
+            severity: "W".to_string(),
+            timestamp: timestamp.parse().unwrap(),
+            sourcefile_nr: "raft_consensus.cc:1234".to_string(),
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let mut first = AllLogLines::new();
+        first.loglines.push(logline("2023-02-03T11:13:22Z", "Leader election lost for term 4."));
+        let mut second = AllLogLines::new();
+        // the first log line is also in the second snapshot, and must not be counted again.
+        second.loglines.push(logline("2023-02-03T11:13:22Z", "Leader election lost for term 4."));
+        second.loglines.push(logline("2023-02-03T11:14:22Z", "Leader election lost for term 5."));
+        second.loglines.push(logline("2023-02-03T11:15:22Z", "Leader election lost for term 6."));
+
+        let mut logtemplatesdiff = LogTemplatesDiff::new();
+        logtemplatesdiff.first_snapshot(first);
+        logtemplatesdiff.second_snapshot(second);
+        let row = &logtemplatesdiff.btreelogtemplatesdiff[&("192.168.66.80:9000".to_string(), "W".to_string(), "raft_consensus.cc:1234".to_string(), "Leader election lost for term <n>.".to_string())];
+        assert_eq!(row.first_count, 1);
+        assert_eq!(row.second_count, 2);
+    }
+
+    #[test]
+    fn unit_read_glog_file() {
+        let raw_loglines = "Log file created at: 2021/12/18 12:12:30\n\
+Current UTC time: 2021/12/18 12:12:30\n\
+Log line format: [IWEF]mmdd hh:mm:ss.uuuuuu threadid file:line] msg\n\
+I1218 12:12:34.464701  7650 async_initializer.cc:95] Reinitialize master addresses from file: /opt/yugabyte/conf/master.conf\n\
+W1218 12:12:35.464808  7650 master_rpc.cc:287] New master addresses: [yb-1.local:7100]\n";
+        let file_name = env::temp_dir().join(format!("yb-tserver.WARNING.{}.gz", std::process::id()));
+        let mut encoder = flate2::write::GzEncoder::new(fs::File::create(&file_name).unwrap(), flate2::Compression::default());
+        encoder.write_all(raw_loglines.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let allloglines = AllLogLines::read_glog_files(&[file_name.to_string_lossy().to_string()], "yb-1.local:9000", DEFAULT_YSQL_LOG_LINE_PREFIX).unwrap();
+        fs::remove_file(&file_name).unwrap();
+        assert_eq!(allloglines.loglines.len(), 2);
+        assert_eq!(allloglines.loglines[0].timestamp.to_rfc3339(), "2021-12-18T12:12:34.464701+00:00");
+        assert_eq!(allloglines.loglines[1].severity, "W");
+        assert_eq!(allloglines.loglines[1].hostname_port, Some("yb-1.local:9000".to_string()));
+    }
+
+    #[test]
+    fn unit_parse_glog_year_and_invalid_date() {
+        let raw_loglines = "I1231 23:59:59.000001  7650 tablet.cc:10] last log line of the year\n\
+I0101 00:00:00.000001  7650 tablet.cc:20] first log line of the year\n\
+I0229 00:00:00.000001  7650 tablet.cc:30] invalid date in a non-leap year\n";
+        let loglines = AllLogLines::parse_glog(raw_loglines, "2022");
+        assert_eq!(loglines.len(), 3);
+        assert_eq!(loglines[0].timestamp.to_rfc3339(), "2022-12-31T23:59:59.000001+00:00");
+        assert_eq!(loglines[1].timestamp.to_rfc3339(), "2023-01-01T00:00:00.000001+00:00");
+        // the invalid date gets the timestamp of the previous log line.
+        assert_eq!(loglines[2].timestamp, loglines[1].timestamp);
+        assert_eq!(loglines[2].sourcefile_nr, "tablet.cc:30");
+
+        // a first log line with an invalid date is discarded.
+        let loglines = AllLogLines::parse_glog("I0229 00:00:00.000001  7650 tablet.cc:30] invalid date\nI0301 00:00:00.000001  7650 tablet.cc:40] valid date\n", "2023");
+        assert_eq!(loglines.len(), 1);
+        assert_eq!(loglines[0].sourcefile_nr, "tablet.cc:40");
+    }
+
+    #[test]
+    fn unit_parse_ysql_log() {
+        let raw_loglines = "2023-02-03 11:13:22.123 UTC [7164] yugabyte@yugabyte LOG:  duration: 1234.567 ms  statement: select *
+\tfrom t
+2023-02-03 11:13:23.456 UTC [7164] yugabyte@yugabyte ERROR:  relation \"x\" does not exist at character 15
+2023-02-03 11:13:23.456 UTC [7164] yugabyte@yugabyte STATEMENT:  select * from x;
+2023-02-03 11:13:24.000 UTC [7000] @ LOG:  database system is ready to accept connections
+";
+        let loglines = AllLogLines::parse_log_text(raw_loglines, "2023", "%m [%p] %u@%d ");
+        assert_eq!(loglines.len(), 3);
+        assert_eq!(loglines[0].timestamp.to_rfc3339(), "2023-02-03T11:13:22.123+00:00");
+        assert_eq!(loglines[0].tid, "7164");
+        assert_eq!(loglines[0].ysql, Some(YsqlLogFields { user: Some("yugabyte".to_string()), database: Some("yugabyte".to_string()), sqlstate: None, duration_ms: Some(1234.567), statement: Some("select *\n\tfrom t".to_string()) }));
+        assert_eq!(loglines[1].severity, "E");
+        assert_eq!(loglines[1].ysql.as_ref().unwrap().statement, Some("select * from x;".to_string()));
+        assert_eq!(loglines[2].ysql.as_ref().unwrap().user, None);
+
+        let loglines = AllLogLines::parse_ysql_log("2023-02-03 11:13:22 UTC 42P01 WARNING:  there is no transaction in progress\n", "%t %e ");
+        assert_eq!(loglines[0].severity, "W");
+        assert_eq!(loglines[0].ysql.as_ref().unwrap().sqlstate, Some("42P01".to_string()));
+
+        // a log line without a prefix timestamp (%p only) gets the timestamp of the previous log line, or is discarded.
+        let loglines = AllLogLines::parse_ysql_log("[7164] LOG:  first\n", "[%p] ");
+        assert!(loglines.is_empty());
+        let loglines = AllLogLines::parse_ysql_log("2023-02-03 11:13:22.123 UTC [7164] LOG:  first\n2023-02-31 11:13:23.456 UTC [7164] LOG:  second\n", "%m [%p] ");
+        assert_eq!(loglines.len(), 2);
+        assert_eq!(loglines[1].timestamp, loglines[0].timestamp);
+
+        // the timestamp is converted to UTC using a numeric offset, a zone abbreviation other than UTC or GMT is taken as UTC.
+        let loglines = AllLogLines::parse_ysql_log("2023-02-03 12:13:22.123 +01 [7164] LOG:  first\n2023-02-03 06:13:22 -05:00 [7164] LOG:  second\n2023-02-03 12:13:22 CET [7164] LOG:  third\n", "%m [%p] ");
+        assert_eq!(loglines.len(), 3);
+        assert_eq!(loglines[0].timestamp.to_rfc3339(), "2023-02-03T11:13:22.123+00:00");
+        assert_eq!(loglines[1].timestamp.to_rfc3339(), "2023-02-03T11:13:22+00:00");
+        assert_eq!(loglines[2].timestamp.to_rfc3339(), "2023-02-03T12:13:22+00:00");
+        assert_eq!(ysql_timestamp_to_utc(&loglines[2].timestamp.naive_utc(), Some("CET")), None);
+        assert_eq!(ysql_timestamp_to_utc(&loglines[2].timestamp.naive_utc(), Some("+0530")).unwrap().to_rfc3339(), "2023-02-03T06:43:22+00:00");
+    }
+
     #[test]
     fn unit_parse_regular_logline() {
         // This is a regular log line.
@@ -323,7 +1401,7 @@ I1218 12:12:34.464808  7650 client-internal.cc:2302] New master addresses: [yb-1
         I0217 10:19:56.834905  31987 docdb_rocksdb_util.cc:416] FLAGS_rocksdb_base_background_compactions was not set, automatically configuring 1 base background compactions.\n
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         assert_eq!(result[0].message.trim(),"FLAGS_rocksdb_base_background_compactions was not set, automatically configuring 1 base background compactions.\\n");
     }
 
@@ -353,7 +1431,7 @@ I1218 12:12:34.464808  7650 client-internal.cc:2302] New master addresses: [yb-1
     @     0x7fa353cb3ae7  yb::consensus::ReplicaState::NotifyReplicationFinishedUnlocked()
             </pre></div>
     "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         // this is the old assertion when the following lines of a multiline logoine were not joined:
         //assert_eq!(result[0].message,"UpdateReplica running for 1.000s in thread 7814:");
         // this is the new line with the new code that adds the lines of a multiline message:
@@ -381,7 +1459,7 @@ I1218 12:12:34.464808  7650 client-internal.cc:2302] New master addresses: [yb-1
     ulimit: max user processes 12000(12000)
     </pre></div>
     "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         // this is the old assertion when the following lines of a multiline logline were not joined:
         //assert_eq!(result[0].message,"ulimit cur(max)...");
         // this is the new line with the new code that adds the lines of a multiline message:
@@ -400,7 +1478,7 @@ create table test (id int primary key, f1 tdxt);
                                           ^^^^
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         //assert_eq!(result[0].message,"SQL Error: Type Not Found. Could not find user defined type");
         assert_eq!(result[0].message.trim(),"SQL Error: Type Not Found. Could not find user defined typecreate table test (id int primary key, f1 tdxt);\n                                          ^^^^");
     }
@@ -417,7 +1495,7 @@ insert into test values (1,'a');
                  ^^^^^^^^^^^^^^
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         //assert_eq!(result[0].message,"SQL Error: Invalid CQL Statement. Missing list of target columns");
         assert_eq!(result[0].message.trim(),"SQL Error: Invalid CQL Statement. Missing list of target columnsinsert into test values (1,'a');\n                 ^^^^^^^^^^^^^^");
     }
@@ -434,7 +1512,7 @@ uuid: "05b8d17620eb4cd79eddaddb2fbcbb42"
 format_stamp: "Formatted at 2022-02-13 16:26:17 on yb-1.local"
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         //assert_eq!(result[0].message,"Opened local filesystem: /mnt/d0");
         assert_eq!(result[0].message.trim(),"Opened local filesystem: /mnt/d0uuid: \"05b8d17620eb4cd79eddaddb2fbcbb42\"\nformat_stamp: \"Formatted at 2022-02-13 16:26:17 on yb-1.local\"");
     }
@@ -504,7 +1582,7 @@ flushed_frontier {
 }
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         assert_eq!(result[0].message.trim(),"T b770079b94ad430493ba5f729fb1f0e7 P 05b8d17620eb4cd79eddaddb2fbcbb42 [R]: Writing version edit: log_number: 33new_files {\n  level: 0\n  number: 10\n  total_file_size: 66565\n  base_file_size: 66384\n  smallest {\n    key: \"Gg\\303I\\200\\000\\000\\000\\000\\0003\\341I\\200\\000\\000\\000\\000\\000B\\225!!J\\200#\\200\\001|E\\302\\264\\205v\\200J\\001\\004\\000\\000\\000\\000\\000\\004\"\n    seqno: 1125899906842625\n    user_values {\n      tag: 1\n      data: \"\\200\\001|E\\302\\274j0\\200J\"\n    }\n    user_frontier {\n      [type.googleapis.com/yb.docdb.ConsensusFrontierPB] {\n        op_id {\n          term: 1\n          index: 2\n        }\n        hybrid_time: 6737247907820138496\n        history_cutoff: 18446744073709551614\n        max_value_level_ttl_expiration_time: 18446744073709551614\n      }\n    }\n  }\n  largest {\n    key: \"G\\222oI\\200\\000\\000\\000\\000\\0003\\341I\\200\\000\\000\\000\\000\\000B{!!K\\203#\\200\\001|E\\302\\274j0\\200?\\213\\001\\003\\000\\000\\000\\000\\000\\004\"\n    seqno: 1125899906842632\n    user_values {\n      tag: 1\n      data: \"\\200\\001|EXN\\364\\273\\200?\\253\"\n    }\n    user_frontier {\n      [type.googleapis.com/yb.docdb.ConsensusFrontierPB] {\n        op_id {\n          term: 1\n          index: 4\n        }\n        hybrid_time: 6737255221467299840\n        history_cutoff: 18446744073709551614\n        max_value_level_ttl_expiration_time: 1\n      }\n    }\n  }\n}\nflushed_frontier {\n  [type.googleapis.com/yb.docdb.ConsensusFrontierPB] {\n    op_id {\n      term: 1\n      index: 4\n    }\n    hybrid_time: 6737255221467299840\n    history_cutoff: 18446744073709551614\n    max_value_level_ttl_expiration_time: 1\n  }\n}");
     }
 
@@ -528,7 +1606,7 @@ properties: contain_counters: false is_transactional: true consistency_level: ST
 properties: contain_counters: false is_transactional: true consistency_level: STRONG use_mangled_column_name: false is_ysql_catalog_table: false retain_delete_markers: false version 1
         </pre></div>
         "#.to_string();
-        let result = AllLogLines::parse_loglines(logline);
+        let result = AllLogLines::parse_loglines(logline, DEFAULT_YSQL_LOG_LINE_PREFIX);
         //assert_eq!(result[0].message,"T c6099b05976f49d9b782ccbe126f9b2d P 05b8d17620eb4cd79eddaddb2fbcbb42: Alter schema from Schema [");
         assert_eq!(result[0].message.trim(),"T c6099b05976f49d9b782ccbe126f9b2d P 05b8d17620eb4cd79eddaddb2fbcbb42: Alter schema from Schema [        0:ybrowid[binary NOT NULL PARTITION KEY],\n        1:dir[string NULLABLE NOT A PARTITION KEY],\n        2:dirname[string NULLABLE NOT A PARTITION KEY]\n]\nproperties: contain_counters: false is_transactional: true consistency_level: STRONG use_mangled_column_name: false is_ysql_catalog_table: false retain_delete_markers: false version 0 to Schema [\n        0:ybrowid[binary NOT NULL PARTITION KEY],\n        1:dir[string NULLABLE NOT A PARTITION KEY],\n        2:dirname[string NULLABLE NOT A PARTITION KEY]\n]\nproperties: contain_counters: false is_transactional: true consistency_level: STRONG use_mangled_column_name: false is_ysql_catalog_table: false retain_delete_markers: false version 1");
     }
@@ -537,7 +1615,7 @@ properties: contain_counters: false is_transactional: true consistency_level: ST
     async fn integration_parse_loglines_master() {
         let hostname = utility::get_hostname_master();
         let port = utility::get_port_master();
-        let allloglines = AllLogLines::read_loglines(&vec![&hostname], &vec![&port], 1).await;
+        let allloglines = AllLogLines::read_loglines(&vec![&hostname], &vec![&port], 1, DEFAULT_YSQL_LOG_LINE_PREFIX).await;
 
         // it's likely there will be logging
         assert!(!allloglines.loglines.is_empty());
@@ -547,7 +1625,7 @@ properties: contain_counters: false is_transactional: true consistency_level: ST
     async fn integration_parse_loglines_tserver() {
         let hostname = utility::get_hostname_tserver();
         let port = utility::get_port_tserver();
-        let allloglines = AllLogLines::read_loglines(&vec![&hostname], &vec![&port], 1).await;
+        let allloglines = AllLogLines::read_loglines(&vec![&hostname], &vec![&port], 1, DEFAULT_YSQL_LOG_LINE_PREFIX).await;
 
         // it's likely there will be logging
         assert!(!allloglines.loglines.is_empty());
@@ -6,6 +6,9 @@
 //! - the masters, default port 7000
 //! - the tablet servers, default port 9000.
 //!
+//! Because a snapshot only contains the last 1MB, `--collect-log` reads `/logs` at an interval
+//! and appends the new lines to the log archive in `yb_stats.snapshots/log_archive`.
+//!
 mod structs;
 mod functions;
 
@@ -1,5 +1,6 @@
 //! The structs
 //!
+use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
 
 /// The root struct for deserializing the glog lines.
@@ -23,9 +24,51 @@ pub struct LogLine {
     pub tid: String,
     pub sourcefile_nr: String,
     pub message: String,
+    /// The structured fields of a YSQL (PostgreSQL format) log line, not set for glog lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ysql: Option<YsqlLogFields>,
+}
+/// The structured fields of a YSQL log line.
+///
+/// For a YSQL log line, the LogLine severity is the glog severity letter for the PostgreSQL level,
+/// tid is the process id, and sourcefile_nr is the PostgreSQL level (LOG, ERROR, etc.).
+/// The user, database and SQLSTATE are only known if they are part of log_line_prefix.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct YsqlLogFields {
+    pub user: Option<String>,
+    pub database: Option<String>,
+    pub sqlstate: Option<String>,
+    /// The duration in milliseconds logged by log_min_duration_statement or log_duration.
+    pub duration_ms: Option<f64>,
+    /// The statement of a duration line, or of the STATEMENT line following an error.
+    pub statement: Option<String>,
 }
 
 #[derive(Debug, Default)]
 pub struct AllLogLines {
     pub loglines: Vec<LogLine>,
-}
\ No newline at end of file
+}
+/// The key of a log message template: hostname_port, severity, sourcefile_nr and the message template.
+pub type LogTemplateKey = (String, String, String, String);
+// diff
+/// BTreeMap for storing a log template diff struct per hostname_port, severity, sourcefile_nr and message template.
+type BTreeLogTemplatesDiff = BTreeMap<LogTemplateKey, LogTemplatesDiffFields>;
+/// Wrapper struct for holding the btreemap.
+///
+/// Because `/logs` shows the last 1MB of log, the log lines in both snapshots overlap.
+/// Therefore, the second snapshot only counts the log lines that are newer than the last log line of the first snapshot of the same server.
+#[derive(Debug, Default)]
+pub struct LogTemplatesDiff {
+    pub btreelogtemplatesdiff: BTreeLogTemplatesDiff,
+    /// The timestamp of the last log line per hostname_port in the first snapshot.
+    pub first_last_timestamp: BTreeMap<String, DateTime<Utc>>,
+}
+/// The log template diff struct.
+///
+/// The first count is the number of log lines with the template in the first snapshot,
+/// the second count is the number of new log lines with the template in the second snapshot.
+#[derive(Debug, Default)]
+pub struct LogTemplatesDiffFields {
+    pub first_count: usize,
+    pub second_count: usize,
+}
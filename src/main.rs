@@ -49,6 +49,16 @@ mod tasks;
 mod tablet_replication;
 mod tablet_server_operations;
 mod drives;
+mod memory_accounting;
+mod lint;
+mod cluster_spec;
+mod security;
+mod timeline;
+mod diagnose;
+mod placement;
+mod partitions;
+mod hot_tablets;
+mod sharding;
 
 // constants
 const DEFAULT_HOSTS: &str = "192.168.66.80,192.168.66.81,192.168.66.82";
@@ -126,12 +136,33 @@ pub struct Opts {
     /// Create a versions diff report using a begin and end snapshot number.
     #[arg(long)]
     versions_diff: bool,
+    /// Create a memtrackers diff report using a begin and end snapshot number.
+    #[arg(long)]
+    memtrackers_diff: bool,
+    /// Output setting for the percentage of the limit above which memtrackers are highlighted (use with --memtrackers-diff)
+    #[arg(long, value_name = "pct", default_value = "80")]
+    memtrackers_limit_pct: f64,
+    /// Create a memz (tcmalloc statistics) diff report using a begin and end snapshot number.
+    #[arg(long)]
+    mems_diff: bool,
+    /// Create a pprof heap growth diff report using a begin and end snapshot number.
+    #[arg(long)]
+    pprof_growth_diff: bool,
+    /// Create a gflags diff report using a begin and end snapshot number.
+    #[arg(long)]
+    gflags_diff: bool,
+    /// Create a threads CPU usage diff report using a begin and end snapshot number.
+    #[arg(long)]
+    threads_diff: bool,
     /// Create an adhoc diff report only for metrics
     #[arg(long)]
     adhoc_metrics_diff: bool,
     /// Create an adhoc diff report only for node_exporter
     #[arg(long)]
     adhoc_node_exporter_diff: bool,
+    /// Create an adhoc diff report only for threads CPU usage
+    #[arg(long)]
+    adhoc_threads_diff: bool,
     /// Create an adhoc diff report excluding metrics
     #[arg(long)]
     adhoc_nonmetrics_diff: bool,
@@ -150,15 +181,87 @@ pub struct Opts {
     /// tail log data
     #[arg(long)]
     tail_log: bool,
-    /// Print log data for the given snapshot number
+    /// Print log data for the given snapshot number, or from the log archive between --begin and --end
     #[arg(long, value_name = "snapshot number")]
     print_log: Option<Option<String>>,
+    /// Read /logs every --collect-log-interval seconds, and append the new log lines to the log archive, until stopped.
+    #[arg(long)]
+    collect_log: bool,
+    /// Output setting for the time between /logs reads in seconds (use with --collect-log)
+    #[arg(long, value_name = "seconds", default_value = "10")]
+    collect_log_interval: u64,
+    /// Print all log lines from the log archive between the begin and end snapshot times.
+    #[arg(long)]
+    log_diff: bool,
+    /// Import glog files (yb-tserver.INFO, yb-master.WARNING.*) or postgresql-*.log files, optionally gzipped, into the loglines of a snapshot.
+    #[arg(long, value_name = "file", num_args = 1.., requires = "import_log_host")]
+    import_log: Vec<String>,
+    /// Output setting for the hostname:port label of the imported log lines (use with --import-log)
+    #[arg(long, value_name = "hostname:port")]
+    import_log_host: Option<String>,
+    /// Output setting for the YSQL log_line_prefix of the YSQL logs in /logs and of imported postgresql-*.log files
+    #[arg(long, value_name = "log_line_prefix", default_value = "%m [%p] ")]
+    ysql_log_line_prefix: String,
+    /// Output setting for the snapshot to add the imported log lines to, default a new snapshot (use with --import-log)
+    #[arg(long, value_name = "snapshot number")]
+    import_log_snapshot: Option<String>,
+    /// Print the YSQL statements logged with a duration, ranked by duration, for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_ysql_slow_statements: Option<Option<String>>,
+    /// Print the log message templates with the number of log lines for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_log_templates: Option<Option<String>>,
+    /// Create a log message templates diff report using a begin and end snapshot number.
+    #[arg(long)]
+    log_templates_diff: bool,
+    /// Create an adhoc diff report only for log message templates
+    #[arg(long)]
+    adhoc_log_templates_diff: bool,
     /// Output log data severity to include: optional: I (use with --print_log)
     #[arg(long, default_value = "WEF")]
     log_severity: String,
     /// Print entity data for snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_entities: Option<Option<String>>,
+    /// Print the leader changes per tablet server, table and tablet, using every snapshot between a begin and end snapshot number.
+    #[arg(long)]
+    leader_churn: bool,
+    /// Output setting for the number of leader changes above which a tablet is flapping (use with --leader-churn)
+    #[arg(long, value_name = "changes", default_value = "3")]
+    leader_flap_threshold: usize,
+    /// Print the tablets ranked by read and write operations per second, and the tablets to split or with a key design problem, using a begin and end snapshot number.
+    #[arg(long)]
+    hot_tablets: bool,
+    /// Output setting for the number of tablets ranked (use with --hot-tablets)
+    #[arg(long, value_name = "tablets", default_value = "10")]
+    hot_tablets_limit: usize,
+    /// Print the leader and replica balance per tablet server and zone for snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_balance: Option<Option<String>>,
+    /// Check the tablet replica placement of the user tables against the placement policy for snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    check_placement: Option<Option<String>>,
+    /// Print the tablet, with the leader and followers, that holds the key of a table for snapshot number, or get current (requires --table and --key)
+    #[arg(long, value_name = "snapshot number", requires_all = ["table", "key"])]
+    locate_key: Option<Option<String>>,
+    /// Check that the tablets of every table cover the whole key space without gaps or overlaps for snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    check_partitions: Option<Option<String>>,
+    /// Print the number and sizes of the tablets per table, with the tables to colocate, or with too many tiny or too few huge tablets for snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    sharding_advice: Option<Option<String>>,
+    /// Output setting for the tablet size below which tablets are tiny (use with --sharding-advice)
+    #[arg(long, value_name = "size", default_value = "512M")]
+    sharding_min_tablet_size: String,
+    /// Output setting for the tablet size above which tablets are huge (use with --sharding-advice)
+    #[arg(long, value_name = "size", default_value = "10G")]
+    sharding_max_tablet_size: String,
+    /// Input setting for the table name, or keyspace.table_name (use with --locate-key)
+    #[arg(long, value_name = "table name")]
+    table: Option<String>,
+    /// Input setting for the key value, comma separated for a key of multiple columns (use with --locate-key)
+    #[arg(long, value_name = "key value")]
+    key: Option<String>,
     /// Print master server data for snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_masters: Option<Option<String>>,
@@ -174,15 +277,63 @@ pub struct Opts {
     /// Print rpcs for the given snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_rpcs: Option<Option<String>>,
+    /// Print in-flight rpcs slower than --slow-rpc-threshold for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_slow_rpcs: Option<Option<String>>,
+    /// Output setting for the minimal elapsed time of in-flight rpcs (use with --print-slow-rpcs)
+    #[arg(long, value_name = "ms", default_value = "1000")]
+    slow_rpc_threshold: u64,
     /// print clocks for the given snapshot number, or get current.
     #[arg(long, value_name = "snapshot_number")]
     print_clocks: Option<Option<String>>,
     /// print master leader tablet server latencies
     #[arg(long, value_name = "snapshot_number")]
     print_latencies: Option<Option<String>>,
+    /// Print memz (tcmalloc statistics) for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_mems: Option<Option<String>>,
+    /// Print the memory accounting of memtrackers, tcmalloc and node memory for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_memory_accounting: Option<Option<String>>,
+    /// Output setting for the percentage of untracked memory above which a server or node is highlighted (use with --print-memory-accounting)
+    #[arg(long, value_name = "pct", default_value = "20")]
+    memory_gap_pct: f64,
+    /// Print the top allocation sites of the pprof heap growth profile for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_pprof_growth: Option<Option<String>>,
     /// Print threads data for the given snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_threads: Option<Option<String>>,
+    /// Print the gflags that differ between servers of the same role for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    print_gflags_drift: Option<Option<String>>,
+    /// Output setting: regex for gflags to ignore in the drift report, in addition to the built-in per-server gflags (use with --print-gflags-drift)
+    #[arg(long, value_name = "regex")]
+    gflags_ignore: Option<String>,
+    /// Check the configuration against best practices for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    lint: Option<Option<String>>,
+    /// Output setting: a JSON file with rules that extend or replace the built-in rules (use with --lint)
+    #[arg(long, value_name = "file")]
+    lint_rules: Option<String>,
+    /// Check the cluster against the YAML cluster spec file, and exit with an error if it differs.
+    #[arg(long, value_name = "file")]
+    check_spec: Option<String>,
+    /// Snapshot number to check against the cluster spec, instead of the live cluster (use with --check-spec)
+    #[arg(long, value_name = "snapshot number")]
+    spec_snapshot: Option<String>,
+    /// Print the security settings per server for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    security_report: Option<Option<String>>,
+    /// Match the log lines, versions and gflags with known issue signatures for the given snapshot number, or get current.
+    #[arg(long, value_name = "snapshot number")]
+    diagnose: Option<Option<String>>,
+    /// Output setting: a JSON file with signatures that extend or replace the shipped signatures (use with --diagnose)
+    #[arg(long, value_name = "file")]
+    diagnose_signatures: Option<String>,
+    /// Print the cluster timeline of raft and tablet lifecycle events using a begin and end snapshot number (table-name-match for table or tablet)
+    #[arg(long)]
+    cluster_timeline: bool,
     /// Print gflags for the given snapshot number, or get current.
     #[arg(long, value_name = "snapshot number")]
     print_gflags: Option<Option<String>>,
@@ -210,6 +361,21 @@ pub struct Opts {
     /// UUID for table-detail
     #[arg(long, value_name = "uuid", default_value = "")]
     uuid: String,
+    /// Sample thread stacks from /threadz for --profile-duration, and save them as folded stacks and flamegraph in a new snapshot.
+    #[arg(long)]
+    threads_profile: bool,
+    /// Capture a CPU profile from /pprof/profile for --profile-duration on all servers at the same time, and save it with a flamegraph in a new snapshot.
+    #[arg(long)]
+    pprof_profile: bool,
+    /// Print the top functions of the CPU profile for the given snapshot number.
+    #[arg(long, value_name = "snapshot number")]
+    print_pprof_profile: Option<String>,
+    /// Output setting for the duration of profiling in seconds (use with --threads-profile, --pprof-profile)
+    #[arg(long, value_name = "seconds", default_value = "10")]
+    profile_duration: u64,
+    /// Output setting for the time between /threadz samples (use with --threads-profile)
+    #[arg(long, value_name = "ms", default_value = "1000")]
+    threads_profile_interval: u64,
     /// Snapshot disable gathering of thread stacks from /threadz
     #[arg(long)]
     disable_threads: bool,
@@ -247,25 +413,58 @@ async fn main() -> Result<()>
         Opts { masters_diff, ..             } if *masters_diff                   => masters::masters_diff(&options).await?,
         Opts { tablet_servers_diff, ..             } if *tablet_servers_diff                   => tablet_servers::tablet_servers_diff(&options).await?,
         Opts { vars_diff, ..             } if *vars_diff                   => vars::vars_diff(&options).await?,
+        Opts { gflags_diff, ..           } if *gflags_diff                 => gflags::gflags_diff(&options).await?,
         Opts { node_exporter_diff, ..             } if *node_exporter_diff                   => node_exporter::node_exporter_diff(&options).await?,
         Opts { statements_diff, ..             } if *statements_diff                   => statements::statements_diff(&options).await?,
         Opts { versions_diff, ..            } if *versions_diff                  => versions::versions_diff(&options).await?,
+        Opts { threads_diff, ..             } if *threads_diff                   => threads::threads_diff(&options).await?,
+        Opts { memtrackers_diff, ..         } if *memtrackers_diff               => memtrackers::memtrackers_diff(&options).await?,
+        Opts { mems_diff, ..                } if *mems_diff                      => mems::mems_diff(&options).await?,
+        Opts { pprof_growth_diff, ..        } if *pprof_growth_diff              => pprof::pprof_growth_diff(&options).await?,
         Opts { print_memtrackers, ..        } if print_memtrackers.is_some()     => memtrackers::print_memtrackers(hosts, ports, parallel, &options).await?,
+        Opts { print_mems, ..               } if print_mems.is_some()            => mems::print_mems(hosts, ports, parallel, &options).await?,
+        Opts { print_pprof_growth, ..       } if print_pprof_growth.is_some()    => pprof::print_pprof_growth(hosts, ports, parallel, &options).await?,
+        Opts { print_memory_accounting, ..  } if print_memory_accounting.is_some() => memory_accounting::print_memory_accounting(hosts, ports, parallel, &options).await?,
         Opts { print_version, ..            } if print_version.is_some()         => versions::print_version(hosts, ports, parallel, &options).await?,
         Opts { print_threads, ..            } if print_threads.is_some()         => threads::print_threads(hosts, ports, parallel, &options).await?,
+        Opts { threads_profile, ..          } if *threads_profile                => threads::threads_profile(hosts, ports, parallel, &options).await?,
+        Opts { pprof_profile, ..            } if *pprof_profile                  => pprof::pprof_profile(hosts, ports, parallel, &options).await?,
+        Opts { print_pprof_profile, ..      } if print_pprof_profile.is_some()   => pprof::print_pprof_profile(&options).await?,
         Opts { print_entities, ..           } if print_entities.is_some()        => entities::print_entities(hosts, ports, parallel, &options).await?,
+        Opts { leader_churn, ..             } if *leader_churn                   => entities::leader_churn(&options).await?,
+        Opts { sharding_advice, ..          } if sharding_advice.is_some()       => sharding::print_sharding_advice(hosts, ports, parallel, &options).await?,
+        Opts { hot_tablets, ..              } if *hot_tablets                    => hot_tablets::hot_tablets(&options).await?,
+        Opts { print_balance, ..            } if print_balance.is_some()         => entities::print_balance(hosts, ports, parallel, &options).await?,
+        Opts { check_placement, ..          } if check_placement.is_some()       => placement::print_check_placement(hosts, ports, parallel, &options).await?,
+        Opts { locate_key, ..               } if locate_key.is_some()            => partitions::locate_key(hosts, ports, parallel, &options).await?,
+        Opts { check_partitions, ..         } if check_partitions.is_some()      => partitions::print_check_partitions(hosts, ports, parallel, &options).await?,
         Opts { print_masters, ..            } if print_masters.is_some()         => masters::print_masters(hosts, ports, parallel, &options).await?,
         Opts { print_tablet_servers, ..     } if print_tablet_servers.is_some()  => tablet_servers::print_tablet_servers(hosts, ports, parallel, &options).await?,
         Opts { print_vars, ..               } if print_vars.is_some()            => vars::print_vars(hosts, ports, parallel, &options).await?,
         Opts { print_clocks, ..             } if print_clocks.is_some()          => clocks::print_clocks(hosts, ports, parallel, &options).await?,
         Opts { print_latencies, ..          } if print_latencies.is_some()       => clocks::print_latencies(hosts, ports, parallel, &options).await?,
         Opts { print_rpcs, ..               } if print_rpcs.is_some()            => rpcs::print_rpcs(hosts, ports, parallel, &options).await?,
+        Opts { print_slow_rpcs, ..          } if print_slow_rpcs.is_some()       => rpcs::print_slow_rpcs(hosts, ports, parallel, &options).await?,
         Opts { print_log, ..                } if print_log.is_some()             => loglines::print_loglines(hosts, ports, parallel, &options).await?,
         Opts { tail_log, ..                 } if *tail_log                       => loglines::tail_loglines(hosts, ports, parallel, &options).await?,
+        Opts { collect_log, ..              } if *collect_log                    => loglines::collect_loglines(hosts, ports, parallel, &options).await?,
+        Opts { log_diff, ..                 } if *log_diff                       => loglines::print_log_archive(&options).await?,
+        Opts { print_ysql_slow_statements, .. } if print_ysql_slow_statements.is_some() => loglines::print_ysql_slow_statements(hosts, ports, parallel, &options).await?,
+        Opts { import_log, ..               } if !import_log.is_empty()          => loglines::import_loglines(&options).await?,
+        Opts { print_log_templates, ..      } if print_log_templates.is_some()   => loglines::print_log_templates(hosts, ports, parallel, &options).await?,
+        Opts { log_templates_diff, ..       } if *log_templates_diff             => loglines::log_templates_diff(&options).await?,
+        Opts { adhoc_log_templates_diff, .. } if *adhoc_log_templates_diff       => snapshot::adhoc_log_templates_diff(hosts, ports, parallel, &options).await?,
         Opts { adhoc_metrics_diff, ..       } if *adhoc_metrics_diff             => snapshot::adhoc_metrics_diff(hosts, ports, parallel, &options).await?,
         Opts { adhoc_node_exporter_diff, ..       } if *adhoc_node_exporter_diff             => snapshot::adhoc_node_exporter_diff(hosts, ports, parallel, &options).await?,
+        Opts { adhoc_threads_diff, ..       } if *adhoc_threads_diff             => snapshot::adhoc_threads_diff(hosts, ports, parallel, &options).await?,
         Opts { adhoc_nonmetrics_diff, ..    } if *adhoc_nonmetrics_diff          => snapshot::adhoc_nonmetrics_diff(hosts, ports, parallel, &options).await?,
         Opts { print_gflags, ..             } if print_gflags.is_some()          => gflags::print_gflags(hosts, ports, parallel, &options).await?,
+        Opts { print_gflags_drift, ..       } if print_gflags_drift.is_some()    => gflags::print_gflags_drift(hosts, ports, parallel, &options).await?,
+        Opts { lint, ..                     } if lint.is_some()                  => lint::print_lint(hosts, ports, parallel, &options).await?,
+        Opts { check_spec, ..               } if check_spec.is_some()            => cluster_spec::check_spec(hosts, ports, parallel, &options).await?,
+        Opts { security_report, ..          } if security_report.is_some()       => security::print_security_report(hosts, ports, parallel, &options).await?,
+        Opts { diagnose, ..                 } if diagnose.is_some()              => diagnose::print_diagnose(hosts, ports, parallel, &options).await?,
+        Opts { cluster_timeline, ..         } if *cluster_timeline               => timeline::cluster_timeline(&options).await?,
         Opts { print_cluster_config, ..     } if print_cluster_config.is_some()  => cluster_config::print_cluster_config(hosts, ports, parallel, &options).await?,
         Opts { print_health_check, ..       } if print_health_check.is_some()    => health_check::print_health_check(hosts, ports, parallel, &options).await?,
         Opts { print_drives, ..       } if print_drives.is_some()    => drives::print_drives(hosts, ports, parallel, &options).await?,
@@ -0,0 +1,346 @@
+//! The impls and functions
+//!
+use regex::Regex;
+use colored::*;
+use anyhow::Result;
+use crate::utility;
+use crate::snapshot;
+use crate::memtrackers::AllMemTrackers;
+use crate::mems::AllMems;
+use crate::node_exporter::AllNodeExporter;
+use crate::memory_accounting::{AllMemoryAccounting, ProcessMemory};
+use crate::Opts;
+
+impl ProcessMemory {
+    /// The memory that is allocated by the application according to tcmalloc, but not tracked by the root memtracker.
+    /// A negative number means the memtrackers track more than tcmalloc has allocated.
+    pub fn untracked(&self) -> Option<i64>
+    {
+        match (self.tcmalloc_in_use, self.root_consumption) {
+            (Some(tcmalloc_in_use), Some(root_consumption)) => Some(tcmalloc_in_use as i64 - root_consumption as i64),
+            _ => None,
+        }
+    }
+    /// The untracked memory as percentage of the memory in use according to tcmalloc.
+    pub fn untracked_percentage(&self) -> Option<f64>
+    {
+        match (self.untracked(), self.tcmalloc_in_use) {
+            (Some(untracked), Some(tcmalloc_in_use)) if tcmalloc_in_use > 0 => Some(untracked as f64 / tcmalloc_in_use as f64 * 100_f64),
+            _ => None,
+        }
+    }
+}
+
+/// The hostname of a hostname:port, which is used to line up the server processes with the node.
+fn hostname(hostname_port: &str) -> &str
+{
+    hostname_port.rsplit_once(':').map(|(hostname, _)| hostname).unwrap_or(hostname_port)
+}
+
+/// Format an optional number of bytes human-readable, or empty if there is no number.
+fn optional_bytes(bytes: Option<u64>) -> String
+{
+    bytes.map(utility::bytes_to_human_readable).unwrap_or_default()
+}
+
+/// Format a signed number of bytes human-readable.
+fn signed_bytes(bytes: i64) -> String
+{
+    if bytes < 0
+    {
+        format!("-{}", utility::bytes_to_human_readable(bytes.unsigned_abs()))
+    }
+    else
+    {
+        utility::bytes_to_human_readable(bytes as u64)
+    }
+}
+
+impl AllMemoryAccounting {
+    pub fn new() -> Self { Default::default() }
+    /// Line up the memtrackers, tcmalloc statistics and node_exporter data.
+    pub fn reconcile(
+        allmemtrackers: &AllMemTrackers,
+        allmems: &AllMems,
+        allnodeexporter: &AllNodeExporter,
+    ) -> AllMemoryAccounting
+    {
+        let mut allmemoryaccounting = AllMemoryAccounting::new();
+
+        for memtracker in allmemtrackers.memtrackers
+            .iter()
+            .filter(|memtracker| memtracker.id == "root")
+        {
+            let process = allmemoryaccounting.processes
+                .entry(memtracker.hostname_port.clone())
+                .or_default();
+            process.root_consumption = memtracker.current_consumption_bytes();
+            process.root_limit = memtracker.limit_bytes();
+        }
+        for mems in &allmems.mems
+        {
+            let process = allmemoryaccounting.processes
+                .entry(mems.hostname_port.clone())
+                .or_default();
+            process.tcmalloc_in_use = Some(mems.bytes_in_use_by_application);
+            process.tcmalloc_overhead = Some(mems.allocator_overhead());
+            process.tcmalloc_actual_memory_used = Some(mems.actual_memory_used);
+        }
+        for nodeexporter in &allnodeexporter.nodeexporter
+        {
+            let hostname = hostname(&nodeexporter.hostname_port).to_string();
+            match nodeexporter.name.as_str()
+            {
+                "node_memory_MemTotal_bytes" => allmemoryaccounting.nodes.entry(hostname).or_default().mem_total = Some(nodeexporter.value as u64),
+                "node_memory_MemAvailable_bytes" => allmemoryaccounting.nodes.entry(hostname).or_default().mem_available = Some(nodeexporter.value as u64),
+                // process-exporter: the labels are the values of groupname and memtype, for example "_resident_yb-tserver".
+                "namedprocess_namegroup_memory_bytes" if nodeexporter.labels.contains("_resident")
+                    && nodeexporter.labels.contains("_yb-") => {
+                    let node = allmemoryaccounting.nodes.entry(hostname).or_default();
+                    node.yb_resident = Some(node.yb_resident.unwrap_or_default() + nodeexporter.value as u64);
+                },
+                _ => {},
+            }
+        }
+
+        allmemoryaccounting
+    }
+    pub fn read_snapshot(
+        snapshot_number: &String,
+    ) -> Result<AllMemoryAccounting>
+    {
+        let mut allmemtrackers = AllMemTrackers::new();
+        allmemtrackers.memtrackers = snapshot::read_snapshot_json(snapshot_number, "memtrackers")?;
+        let mut allmems = AllMems::new();
+        allmems.mems = snapshot::read_snapshot_json(snapshot_number, "mems")?;
+        let mut allnodeexporter = AllNodeExporter::new();
+        allnodeexporter.nodeexporter = snapshot::read_snapshot_json(snapshot_number, "nodeexporter")?;
+
+        Ok(AllMemoryAccounting::reconcile(&allmemtrackers, &allmems, &allnodeexporter))
+    }
+    pub async fn read_http(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> Result<AllMemoryAccounting>
+    {
+        let allmemtrackers = AllMemTrackers::read_memtrackers(hosts, ports, parallel).await;
+        let allmems = AllMems::read_mems(hosts, ports, parallel).await?;
+        let allnodeexporter = AllNodeExporter::read_nodeexporter(hosts, ports, parallel).await;
+
+        Ok(AllMemoryAccounting::reconcile(&allmemtrackers, &allmems, &allnodeexporter))
+    }
+    /// The sum of the tcmalloc actual memory used of the server processes on a node.
+    /// None if no server process with tcmalloc statistics is found for the hostname of the node.
+    fn processes_actual_memory_used(
+        &self,
+        hostname: &str,
+    ) -> Option<u64>
+    {
+        self.processes
+            .iter()
+            .filter(|(hostname_port, _)| self::hostname(hostname_port) == hostname)
+            .filter_map(|(_, process)| process.tcmalloc_actual_memory_used)
+            .reduce(|total, actual_memory_used| total + actual_memory_used)
+    }
+    /// The memory used on the node according to the OS that is not used by the server processes according to tcmalloc,
+    /// and that as percentage of the memory used on the node.
+    fn node_other(
+        &self,
+        hostname: &str,
+    ) -> Option<(i64, f64)>
+    {
+        let node = self.nodes.get(hostname)?;
+        let mem_used = node.mem_total?.saturating_sub(node.mem_available?);
+        let other = mem_used as i64 - self.processes_actual_memory_used(hostname)? as i64;
+        let other_percentage = if mem_used > 0 { other as f64 / mem_used as f64 * 100_f64 } else { 0_f64 };
+        Some((other, other_percentage))
+    }
+    /// Print the memory accounting per server process, and per node.
+    /// Server processes where the untracked memory is more than gap_percentage of the tcmalloc in use memory are highlighted,
+    /// and nodes where the memory used according to the OS differs more than gap_percentage from the tcmalloc actual memory used
+    /// of the server processes are highlighted.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        gap_percentage: f64,
+    )
+    {
+        println!("{:30} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                 "hostname_port",
+                 "tracked",
+                 "limit",
+                 "tc_in_use",
+                 "tc_overhead",
+                 "tc_actual",
+                 "untracked",
+                 "untracked%");
+        for (hostname_port, process) in self.processes
+            .iter()
+            .filter(|(hostname_port, _)| hostname_filter.is_match(hostname_port))
+        {
+            let untracked_percentage = process.untracked_percentage()
+                .map(|percentage| format!("{:10.1}", percentage))
+                .unwrap_or_default();
+            let diverges = process.untracked_percentage()
+                .map(|percentage| percentage.abs() > gap_percentage)
+                .unwrap_or(false);
+            println!("{:30} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                     hostname_port,
+                     optional_bytes(process.root_consumption),
+                     optional_bytes(process.root_limit),
+                     optional_bytes(process.tcmalloc_in_use),
+                     optional_bytes(process.tcmalloc_overhead),
+                     optional_bytes(process.tcmalloc_actual_memory_used),
+                     process.untracked().map(signed_bytes).unwrap_or_default(),
+                     if diverges { untracked_percentage.red() } else { untracked_percentage.normal() },
+            );
+        }
+        if self.nodes.is_empty()
+        {
+            return;
+        }
+        println!();
+        println!("{:30} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                 "node",
+                 "mem_total",
+                 "mem_used",
+                 "yb_rss",
+                 "yb_actual",
+                 "other",
+                 "other%");
+        for (hostname, node) in self.nodes
+            .iter()
+            .filter(|(hostname, _)| hostname_filter.is_match(hostname))
+        {
+            let mem_used = match (node.mem_total, node.mem_available) {
+                (Some(mem_total), Some(mem_available)) => Some(mem_total.saturating_sub(mem_available)),
+                _ => None,
+            };
+            let processes_actual_memory_used = self.processes_actual_memory_used(hostname);
+            let node_other = self.node_other(hostname);
+            let other_percentage = node_other
+                .map(|(_, percentage)| format!("{:10.1}", percentage))
+                .unwrap_or_default();
+            let diverges = node_other
+                .map(|(_, percentage)| percentage.abs() > gap_percentage)
+                .unwrap_or(false);
+            println!("{:30} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                     hostname,
+                     optional_bytes(node.mem_total),
+                     optional_bytes(mem_used),
+                     optional_bytes(node.yb_resident),
+                     optional_bytes(processes_actual_memory_used),
+                     node_other.map(|(other, _)| signed_bytes(other)).unwrap_or_default(),
+                     if diverges { other_percentage.red() } else { other_percentage.normal() },
+            );
+            if processes_actual_memory_used.is_none()
+            {
+                println!("{:30} {}", "", format!("No server process with tcmalloc statistics found for node {}", hostname).yellow());
+            }
+        }
+    }
+}
+
+pub async fn print_memory_accounting(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    match options.print_memory_accounting.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let allmemoryaccounting = AllMemoryAccounting::read_snapshot(snapshot_number)?;
+            allmemoryaccounting.print(&hostname_filter, options.memory_gap_pct);
+        }
+        None => {
+            let allmemoryaccounting = AllMemoryAccounting::read_http(&hosts, &ports, parallel).await?;
+            allmemoryaccounting.print(&hostname_filter, options.memory_gap_pct);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memtrackers::MemTrackers;
+    use crate::mems::Mems;
+    use crate::node_exporter::NodeExporter;
+
+    #[test]
+    fn unit_reconcile_memory_accounting() {
+        let mut allmemtrackers = AllMemTrackers::new();
+        allmemtrackers.memtrackers.push(MemTrackers {
+            hostname_port: "192.168.66.80:9000".to_string(),
+            id: "root".to_string(),
+            current_consumption: "80.00M".to_string(),
+            limit: "1.00G".to_string(),
+            depth: "0".to_string(),
+            ..Default::default()
+        });
+        let mut allmems = AllMems::new();
+        allmems.mems.push(Mems {
+            hostname_port: "192.168.66.80:9000".to_string(),
+            bytes_in_use_by_application: 100 * 1024 * 1024,
+            actual_memory_used: 120 * 1024 * 1024,
+            ..Default::default()
+        });
+        let mut allnodeexporter = AllNodeExporter::new();
+        for (name, value) in [("node_memory_MemTotal_bytes", 8_f64 * 1024_f64.powi(3)), ("node_memory_MemAvailable_bytes", 6_f64 * 1024_f64.powi(3))] {
+            allnodeexporter.nodeexporter.push(NodeExporter {
+                hostname_port: "192.168.66.80:9300".to_string(),
+                name: name.to_string(),
+                value,
+                ..Default::default()
+            });
+        }
+
+        let allmemoryaccounting = AllMemoryAccounting::reconcile(&allmemtrackers, &allmems, &allnodeexporter);
+        let process = &allmemoryaccounting.processes["192.168.66.80:9000"];
+        assert_eq!(process.untracked(), Some(20 * 1024 * 1024));
+        assert_eq!(process.untracked_percentage(), Some(20_f64));
+        assert_eq!(process.tcmalloc_overhead, Some(20 * 1024 * 1024));
+        assert_eq!(allmemoryaccounting.nodes["192.168.66.80"].mem_total, Some(8 * 1024 * 1024 * 1024));
+        assert_eq!(allmemoryaccounting.processes_actual_memory_used("192.168.66.80"), Some(120 * 1024 * 1024));
+        assert_eq!(allmemoryaccounting.processes_actual_memory_used("192.168.66.81"), None);
+    }
+    #[test]
+    fn unit_node_memory_gap() {
+        let mut allmems = AllMems::new();
+        allmems.mems.push(Mems {
+            hostname_port: "192.168.66.80:9000".to_string(),
+            bytes_in_use_by_application: 900 * 1024 * 1024,
+            actual_memory_used: 1024 * 1024 * 1024,
+            ..Default::default()
+        });
+        let mut allnodeexporter = AllNodeExporter::new();
+        for (name, labels, value) in [
+            ("node_memory_MemTotal_bytes", "", 8_f64 * 1024_f64.powi(3)),
+            ("node_memory_MemAvailable_bytes", "", 6_f64 * 1024_f64.powi(3)),
+            ("namedprocess_namegroup_memory_bytes", "_resident_yb-tserver", 1.5_f64 * 1024_f64.powi(3)),
+            ("namedprocess_namegroup_memory_bytes", "_virtual_yb-tserver", 4_f64 * 1024_f64.powi(3)),
+            ("namedprocess_namegroup_memory_bytes", "_resident_node_exporter", 0.1_f64 * 1024_f64.powi(3)),
+        ] {
+            allnodeexporter.nodeexporter.push(NodeExporter {
+                hostname_port: "192.168.66.80:9300".to_string(),
+                name: name.to_string(),
+                labels: labels.to_string(),
+                value,
+                ..Default::default()
+            });
+        }
+
+        let allmemoryaccounting = AllMemoryAccounting::reconcile(&AllMemTrackers::new(), &allmems, &allnodeexporter);
+        assert_eq!(allmemoryaccounting.nodes["192.168.66.80"].yb_resident, Some(1536 * 1024 * 1024));
+        // the node uses 2G, the tablet server 1G: the other memory is 50% of the used memory, which exceeds the default gap of 20%.
+        let (other, other_percentage) = allmemoryaccounting.node_other("192.168.66.80").unwrap();
+        assert_eq!(other, 1024 * 1024 * 1024);
+        assert_eq!(other_percentage, 50_f64);
+        assert!(other_percentage.abs() > 20_f64);
+    }
+}
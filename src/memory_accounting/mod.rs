@@ -0,0 +1,17 @@
+//! Module for reconciling the memory accounting of the masters and tablet servers.
+//!
+//! The memory of a server process is reported in three unconnected places:
+//! - the memtrackers (`/mem-trackers`), which is the memory that YugabyteDB tracks: [crate::memtrackers].
+//! - the tcmalloc statistics (`/memz`), which is the memory that the allocator has handed out and keeps: [crate::mems].
+//! - node_exporter (`/metrics` on port 9300), which is the memory usage of the node: [crate::node_exporter].
+//!
+//! This module lines these up per server process and per node, and shows the memory that is not accounted for.
+//!
+//! The memory accounting functionality is called from:
+//! - [crate] -> [print_memory_accounting] (print adhoc (live) or snapshot memory accounting)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
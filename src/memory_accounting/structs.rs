@@ -0,0 +1,33 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+/// The memory accounting of a single server process (hostname:port).
+///
+/// The memtracker values are taken from the `root` memtracker,
+/// the tcmalloc values are taken from the `/memz` statistics.
+#[derive(Debug, Default)]
+pub struct ProcessMemory {
+    pub root_consumption: Option<u64>,
+    pub root_limit: Option<u64>,
+    pub tcmalloc_in_use: Option<u64>,
+    pub tcmalloc_overhead: Option<u64>,
+    pub tcmalloc_actual_memory_used: Option<u64>,
+}
+/// The memory of a node as reported by node_exporter.
+///
+/// The resident memory of the YugabyteDB processes is only available if node_exporter provides process metrics
+/// (`namedprocess_namegroup_memory_bytes` of process-exporter).
+#[derive(Debug, Default)]
+pub struct NodeMemory {
+    pub mem_total: Option<u64>,
+    pub mem_available: Option<u64>,
+    pub yb_resident: Option<u64>,
+}
+/// Wrapper struct for holding the memory accounting.
+///
+/// The processes are stored per hostname:port, the nodes are stored per hostname.
+#[derive(Debug, Default)]
+pub struct AllMemoryAccounting {
+    pub processes: BTreeMap<String, ProcessMemory>,
+    pub nodes: BTreeMap<String, NodeMemory>,
+}
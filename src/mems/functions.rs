@@ -1,13 +1,54 @@
-//! Module for reading the /memz output for the master and tablet server.
+//! The impls and functions.
 //!
+use chrono::Local;
 use std::{fs, io::Write, sync::mpsc::channel, time::Instant, env};
+use regex::Regex;
 use log::*;
 use anyhow::{Result, Context};
 use crate::utility;
-
-pub struct Mems;
+use crate::snapshot;
+use crate::mems::{Mems, AllMems, MemsDiff, MemsDiffFields};
+use crate::Opts;
 
 impl Mems {
+    /// The tcmalloc statistics as name and value, in the order of the `/memz` page.
+    /// The allocator overhead is the memory that is used by tcmalloc, but not by the application.
+    pub fn fields(&self) -> Vec<(&'static str, u64)>
+    {
+        vec![
+            ("Bytes in use by application", self.bytes_in_use_by_application),
+            ("Bytes in page heap freelist", self.bytes_in_page_heap_freelist),
+            ("Bytes in central cache freelist", self.bytes_in_central_cache_freelist),
+            ("Bytes in transfer cache freelist", self.bytes_in_transfer_cache_freelist),
+            ("Bytes in thread cache freelists", self.bytes_in_thread_cache_freelists),
+            ("Bytes in malloc metadata", self.bytes_in_malloc_metadata),
+            ("Actual memory used (physical + swap)", self.actual_memory_used),
+            ("Bytes released to OS (aka unmapped)", self.bytes_released_to_os),
+            ("Virtual address space used", self.virtual_address_space_used),
+            ("Allocator overhead", self.allocator_overhead()),
+        ]
+    }
+    /// The memory used by tcmalloc that is not in use by the application: freelists, caches and metadata.
+    pub fn allocator_overhead(&self) -> u64
+    {
+        self.actual_memory_used.saturating_sub(self.bytes_in_use_by_application)
+    }
+    /// The allocator overhead as percentage of the actual memory used.
+    pub fn allocator_overhead_percentage(&self) -> f64
+    {
+        if self.actual_memory_used == 0
+        {
+            0_f64
+        }
+        else
+        {
+            self.allocator_overhead() as f64 / self.actual_memory_used as f64 * 100_f64
+        }
+    }
+}
+
+impl AllMems {
+    pub fn new() -> Self { Default::default() }
     pub async fn perform_snapshot(
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
@@ -18,18 +59,21 @@ impl Mems {
         info!("begin snapshot");
         let timer = Instant::now();
 
-        Mems::read_and_write_mems(hosts, ports, snapshot_number, parallel).await?;
+        let allmems = AllMems::read_and_write_mems(hosts, ports, snapshot_number, parallel).await?;
+        snapshot::save_snapshot_json(snapshot_number, "mems", allmems.mems)?;
 
         info!("end snapshot: {:?}", timer.elapsed());
 
         Ok(())
     }
+    /// Read `/memz`, and write the raw result as `mems_<hostname:port>.txt` into the snapshot if snapshot_number is not negative.
+    /// The tcmalloc statistics are parsed and returned.
     pub async fn read_and_write_mems(
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
         snapshot_number: i32,
         parallel: usize,
-    ) -> Result<()>
+    ) -> Result<AllMems>
     {
         info!("begin parallel http read");
         let timer = Instant::now();
@@ -42,8 +86,9 @@ impl Mems {
                 for port in ports {
                     let tx = tx.clone();
                     s.spawn(move |_| {
-                        let mems_data = Mems::read_http(host, port);
-                        tx.send((format!("{}:{}", host, port), mems_data)).expect("error sending data via tx (mems)");
+                        let detail_snapshot_time = Local::now();
+                        let mems_data = AllMems::read_http(host, port);
+                        tx.send((format!("{}:{}", host, port), detail_snapshot_time, mems_data)).expect("error sending data via tx (mems)");
                     });
                 }
             }
@@ -51,25 +96,42 @@ impl Mems {
 
         info!("end parallel http read {:?}", timer.elapsed());
 
-        for (hostname_port, mems_data) in rx {
+        let mut allmems = AllMems::new();
+
+        for (hostname_port, detail_snapshot_time, mems_data) in rx {
             if mems_data.starts_with("------------------------------------------------")
-                && snapshot_number >= 0
             {
-                let current_directory = env::current_dir()?;
-                let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number.to_string());
-
-                let mems_file = &current_snapshot_directory.join(format!("mems_{}.txt", hostname_port));
-                let mut file = fs::OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .open(mems_file)
-                    .with_context(|| format!("Cannot create file: {}", mems_file.display()))?;
-
-                file.write_all(mems_data.as_bytes())
-                    .with_context(|| format!("Error writing file: {}", mems_file.display()))?;
+                if snapshot_number >= 0
+                {
+                    let current_directory = env::current_dir()?;
+                    let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number.to_string());
+
+                    let mems_file = &current_snapshot_directory.join(format!("mems_{}.txt", hostname_port));
+                    let mut file = fs::OpenOptions::new()
+                        .create(true)
+                        .write(true)
+                        .truncate(true)
+                        .open(mems_file)
+                        .with_context(|| format!("Cannot create file: {}", mems_file.display()))?;
+
+                    file.write_all(mems_data.as_bytes())
+                        .with_context(|| format!("Error writing file: {}", mems_file.display()))?;
+                }
+                let mut mems = AllMems::parse_mems(&mems_data);
+                mems.hostname_port = hostname_port;
+                mems.timestamp = detail_snapshot_time;
+                allmems.mems.push(mems);
             }
         }
-        Ok(())
+        Ok(allmems)
+    }
+    pub async fn read_mems(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> Result<AllMems>
+    {
+        AllMems::read_and_write_mems(hosts, ports, -1, parallel).await
     }
     fn read_http(
         host: &str,
@@ -78,6 +140,206 @@ impl Mems {
     {
         utility::http_get(host, port, "memz?raw=true")
     }
+    fn parse_mems(
+        mems_data: &str,
+    ) -> Mems
+    {
+        let mut mems = Mems::default();
+        let regular_expression = Regex::new(r"^MALLOC:\s+[+=]?\s*(\d+)\s+(?:\(\s*[0-9.]+ MiB\)\s+)?(.+?)\s*$").unwrap();
+        for line in mems_data.lines()
+        {
+            if let Some(captures) = regular_expression.captures(line)
+            {
+                let value = captures[1].parse::<u64>().unwrap_or_default();
+                match &captures[2]
+                {
+                    "Bytes in use by application" => mems.bytes_in_use_by_application = value,
+                    "Bytes in page heap freelist" => mems.bytes_in_page_heap_freelist = value,
+                    "Bytes in central cache freelist" => mems.bytes_in_central_cache_freelist = value,
+                    "Bytes in transfer cache freelist" => mems.bytes_in_transfer_cache_freelist = value,
+                    "Bytes in thread cache freelists" => mems.bytes_in_thread_cache_freelists = value,
+                    "Bytes in malloc metadata" => mems.bytes_in_malloc_metadata = value,
+                    "Actual memory used (physical + swap)" => mems.actual_memory_used = value,
+                    "Bytes released to OS (aka unmapped)" => mems.bytes_released_to_os = value,
+                    "Virtual address space used" => mems.virtual_address_space_used = value,
+                    "Spans in use" => mems.spans_in_use = value,
+                    "Thread heaps in use" => mems.thread_heaps_in_use = value,
+                    "Tcmalloc page size" => mems.tcmalloc_page_size = value,
+                    other => debug!("Unknown tcmalloc statistic: {}", other),
+                }
+            }
+        }
+        mems
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        for mems in &self.mems
+        {
+            if hostname_filter.is_match(&mems.hostname_port)
+            {
+                println!("{}", "-".repeat(80));
+                println!("Host: {}, Snapshot time: {}", mems.hostname_port, mems.timestamp);
+                println!("{}", "-".repeat(80));
+                for (name, value) in mems.fields()
+                {
+                    println!("{:40} {:>15} {:>12}", name, value, utility::bytes_to_human_readable(value));
+                }
+                println!("{:40} {:>15}", "Spans in use", mems.spans_in_use);
+                println!("{:40} {:>15}", "Thread heaps in use", mems.thread_heaps_in_use);
+                println!("{:40} {:>15}", "Tcmalloc page size", mems.tcmalloc_page_size);
+                println!("{:40} {:>14.1}%", "Allocator overhead of actual memory", mems.allocator_overhead_percentage());
+            }
+        }
+    }
+}
+
+impl MemsDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<MemsDiff>
+    {
+        let mut memsdiff = MemsDiff::new();
+
+        let mut allmems = AllMems::new();
+        allmems.mems = snapshot::read_snapshot_json(begin_snapshot, "mems")?;
+        memsdiff.first_snapshot(allmems);
+
+        let mut allmems = AllMems::new();
+        allmems.mems = snapshot::read_snapshot_json(end_snapshot, "mems")?;
+        memsdiff.second_snapshot(allmems);
+
+        Ok(memsdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allmems: AllMems,
+    )
+    {
+        for mems in allmems.mems
+        {
+            self.btreememsdiff
+                .entry(mems.hostname_port.clone())
+                .and_modify(|_| error!("Duplicate hostname:port entry: {}", mems.hostname_port))
+                .or_insert(MemsDiffFields {
+                    first: Some(mems),
+                    ..Default::default()
+                });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allmems: AllMems,
+    )
+    {
+        for mems in allmems.mems
+        {
+            self.btreememsdiff
+                .entry(mems.hostname_port.clone())
+                .and_modify(|memsdifffields| memsdifffields.second = Some(mems.clone()))
+                .or_insert(MemsDiffFields {
+                    second: Some(mems),
+                    ..Default::default()
+                });
+        }
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        for (hostname_port, row) in &self.btreememsdiff
+        {
+            if !hostname_filter.is_match(hostname_port)
+            {
+                continue;
+            }
+            let (first, second) = match (&row.first, &row.second) {
+                (Some(first), Some(second)) => (first, second),
+                _ => {
+                    println!("{}", "-".repeat(96));
+                    println!("Host: {}, only found in the {} snapshot", hostname_port, if row.first.is_some() { "first" } else { "second" });
+                    continue;
+                }
+            };
+            println!("{}", "-".repeat(96));
+            println!("Host: {}, time between snapshots: {:8.3} seconds", hostname_port, (second.timestamp - first.timestamp).num_milliseconds() as f64 / 1000_f64);
+            println!("{}", "-".repeat(96));
+            println!("{:40} {:>12} {:>12} {:>12}", "", "first", "second", "difference");
+            for ((name, first_value), (_, second_value)) in first.fields().into_iter().zip(second.fields())
+            {
+                let difference = if second_value >= first_value
+                {
+                    format!("+{}", utility::bytes_to_human_readable(second_value - first_value))
+                }
+                else
+                {
+                    format!("-{}", utility::bytes_to_human_readable(first_value - second_value))
+                };
+                println!("{:40} {:>12} {:>12} {:>12}",
+                         name,
+                         utility::bytes_to_human_readable(first_value),
+                         utility::bytes_to_human_readable(second_value),
+                         difference,
+                );
+            }
+            println!("{:40} {:>12} {:>12} {:>+12}", "Spans in use", first.spans_in_use, second.spans_in_use, second.spans_in_use as i64 - first.spans_in_use as i64);
+            println!("{:40} {:>12} {:>12} {:>+12}", "Thread heaps in use", first.thread_heaps_in_use, second.thread_heaps_in_use, second.thread_heaps_in_use as i64 - first.thread_heaps_in_use as i64);
+            println!("{:40} {:>11.1}% {:>11.1}% {:>+11.1}%",
+                     "Allocator overhead of actual memory",
+                     first.allocator_overhead_percentage(),
+                     second.allocator_overhead_percentage(),
+                     second.allocator_overhead_percentage() - first.allocator_overhead_percentage(),
+            );
+        }
+    }
+}
+
+pub async fn mems_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let memsdiff = MemsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    memsdiff.print(&hostname_filter);
+
+    Ok(())
+}
+
+pub async fn print_mems(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    match options.print_mems.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allmems = AllMems::new();
+            allmems.mems = snapshot::read_snapshot_json(snapshot_number, "mems")?;
+            allmems.print(&hostname_filter);
+        }
+        None => {
+            let allmems = AllMems::read_mems(&hosts, &ports, parallel).await?;
+            allmems.print(&hostname_filter);
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -85,23 +347,61 @@ mod tests {
     use super::*;
     //use crate::utility_test::*;
 
+    #[test]
+    fn unit_parse_mems_tcmalloc_statistics()
+    {
+        let mems_data = r#"------------------------------------------------
+MALLOC:       51613192 (   49.2 MiB) Bytes in use by application
+MALLOC: +      3465216 (    3.3 MiB) Bytes in page heap freelist
+MALLOC: +      1282784 (    1.2 MiB) Bytes in central cache freelist
+MALLOC: +      1931776 (    1.8 MiB) Bytes in transfer cache freelist
+MALLOC: +      4041136 (    3.9 MiB) Bytes in thread cache freelists
+MALLOC: +      2752512 (    2.6 MiB) Bytes in malloc metadata
+MALLOC:   ------------
+MALLOC: =     65086616 (   62.1 MiB) Actual memory used (physical + swap)
+MALLOC: +     10616832 (   10.1 MiB) Bytes released to OS (aka unmapped)
+MALLOC:   ------------
+MALLOC: =     75703448 (   72.2 MiB) Virtual address space used
+MALLOC:
+MALLOC:            789              Spans in use
+MALLOC:             63              Thread heaps in use
+MALLOC:           8192              Tcmalloc page size
+------------------------------------------------
+Call ReleaseFreeMemory() to release freelist memory to the OS (via madvise()).
+Bytes released to the OS take up virtual address space but no physical memory.
+"#;
+        let result = AllMems::parse_mems(mems_data);
+        assert_eq!(result.bytes_in_use_by_application, 51613192);
+        assert_eq!(result.bytes_in_page_heap_freelist, 3465216);
+        assert_eq!(result.bytes_in_thread_cache_freelists, 4041136);
+        assert_eq!(result.actual_memory_used, 65086616);
+        assert_eq!(result.bytes_released_to_os, 10616832);
+        assert_eq!(result.virtual_address_space_used, 75703448);
+        assert_eq!(result.spans_in_use, 789);
+        assert_eq!(result.thread_heaps_in_use, 63);
+        assert_eq!(result.tcmalloc_page_size, 8192);
+        assert_eq!(result.allocator_overhead(), 13473424);
+    }
+
     #[tokio::test]
     async fn integration_parse_mems_tserver()
     {
-        // currently, the mems "parsing" is not much parsing.
-        // What currently is done, is that the hostname:port/memz output is stored in a file in the snapshot directory named <hostname>:<port>_mems.
+        // The hostname:port/memz output is parsed for the tcmalloc statistics.
+        // The raw output is stored in a file in the snapshot directory named mems_<hostname>:<port>.txt if a snapshot number is given.
         let hostname = utility::get_hostname_tserver();
         let port = utility::get_port_tserver();
-        Mems::read_and_write_mems(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
+        let allmems = AllMems::read_and_write_mems(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
+        assert!(allmems.mems[0].bytes_in_use_by_application > 0);
     }
 
     #[tokio::test]
     async fn integration_parse_mems_master()
     {
-        // currently, the mems "parsing" is not much parsing.
-        // What currently is done, is that the hostname:port/memz output is stored in a file in the snapshot directory named <hostname>:<port>_mems.
+        // The hostname:port/memz output is parsed for the tcmalloc statistics.
+        // The raw output is stored in a file in the snapshot directory named mems_<hostname>:<port>.txt if a snapshot number is given.
         let hostname = utility::get_hostname_master();
         let port = utility::get_port_master();
-        Mems::read_and_write_mems(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
+        let allmems = AllMems::read_and_write_mems(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
+        assert!(allmems.mems[0].bytes_in_use_by_application > 0);
     }
-}
\ No newline at end of file
+}
@@ -3,11 +3,19 @@
 //! The `/memz` endpoint contains two memory overviews.
 //! It is available on all master and tablet server endpoints, default port numbers 7000 (master), 9000, 12000 (tablet server).
 //!
-//! yb_stats doesn't do anything other than:
-//! - perform a HTTP Get and collect the result from the endpoint.
-//! - check if the returned data start with "------------------------------------------------".
-//! - If so, save the result as "mems_*hostname:port*"
+//! yb_stats:
+//! - performs a HTTP Get and collects the result from the endpoint.
+//! - checks if the returned data start with "------------------------------------------------".
+//! - If so, saves the result as "mems_*hostname:port*.txt".
+//! - parses the tcmalloc statistics into [Mems], which are saved as "mems.json".
 //!
+//! The mems functionality is called from:
+//! - [crate::snapshot::perform_snapshot] -> [AllMems::perform_snapshot] (general snapshot, saves mems data)
+//! - [crate] -> [print_mems] (print adhoc (live) or snapshot tcmalloc statistics)
+//! - [crate] -> [mems_diff] (print the difference of the tcmalloc statistics between two snapshots)
+//!
+mod structs;
 mod functions;
 
+pub use structs::*;
 pub use functions::*;
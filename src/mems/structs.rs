@@ -0,0 +1,67 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+use chrono::{DateTime, Local};
+/// The struct for the parsed tcmalloc statistics of `/memz?raw=true`.
+///
+/// The tcmalloc statistics look like this:
+/// ```text
+/// ------------------------------------------------
+/// MALLOC:       51613192 (   49.2 MiB) Bytes in use by application
+/// MALLOC: +      3465216 (    3.3 MiB) Bytes in page heap freelist
+/// MALLOC: +      1282784 (    1.2 MiB) Bytes in central cache freelist
+/// MALLOC: +      1931776 (    1.8 MiB) Bytes in transfer cache freelist
+/// MALLOC: +      4041136 (    3.9 MiB) Bytes in thread cache freelists
+/// MALLOC: +      2752512 (    2.6 MiB) Bytes in malloc metadata
+/// MALLOC:   ------------
+/// MALLOC: =     65086616 (   62.1 MiB) Actual memory used (physical + swap)
+/// MALLOC: +     10616832 (   10.1 MiB) Bytes released to OS (aka unmapped)
+/// MALLOC:   ------------
+/// MALLOC: =     75703448 (   72.2 MiB) Virtual address space used
+/// MALLOC:
+/// MALLOC:            789              Spans in use
+/// MALLOC:             63              Thread heaps in use
+/// MALLOC:           8192              Tcmalloc page size
+/// ------------------------------------------------
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Mems {
+    /// yb_stats added to allow understanding the source host.
+    pub hostname_port: String,
+    /// yb_stats added to allow understanding the timestamp.
+    pub timestamp: DateTime<Local>,
+    pub bytes_in_use_by_application: u64,
+    pub bytes_in_page_heap_freelist: u64,
+    pub bytes_in_central_cache_freelist: u64,
+    pub bytes_in_transfer_cache_freelist: u64,
+    pub bytes_in_thread_cache_freelists: u64,
+    pub bytes_in_malloc_metadata: u64,
+    pub actual_memory_used: u64,
+    pub bytes_released_to_os: u64,
+    pub virtual_address_space_used: u64,
+    pub spans_in_use: u64,
+    pub thread_heaps_in_use: u64,
+    pub tcmalloc_page_size: u64,
+}
+/// Wrapper struct for holding the different mems structs.
+#[derive(Debug, Default)]
+pub struct AllMems {
+    pub mems: Vec<Mems>,
+}
+// diff
+/// BTreeMap for storing a mems diff struct per hostname_port
+type BTreeMemsDiff = BTreeMap<String, MemsDiffFields>;
+/// Wrapper struct for holding the btreemap
+#[derive(Debug, Default)]
+pub struct MemsDiff {
+    pub btreememsdiff: BTreeMemsDiff,
+}
+/// The mems diff struct.
+///
+/// The hostname:port is the key of the btreemap,
+/// This struct holds the first and second tcmalloc statistics.
+#[derive(Debug, Default)]
+pub struct MemsDiffFields {
+    pub first: Option<Mems>,
+    pub second: Option<Mems>,
+}
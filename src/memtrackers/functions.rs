@@ -5,12 +5,19 @@ use regex::Regex;
 use std::{sync::mpsc::channel, time::Instant};
 use scraper::{Html, Selector};
 use log::*;
+use colored::*;
 use anyhow::Result;
 use crate::snapshot;
-use crate::memtrackers::{MemTrackers, AllMemTrackers};
+use crate::memtrackers::{MemTrackers, AllMemTrackers, MemTrackersDiff, MemTrackersDiffFields};
 use crate::Opts;
 use crate::utility;
 
+impl MemTrackers {
+    pub fn current_consumption_bytes(&self) -> Option<u64> { utility::human_readable_to_bytes(&self.current_consumption) }
+    pub fn peak_consumption_bytes(&self) -> Option<u64> { utility::human_readable_to_bytes(&self.peak_consumption) }
+    pub fn limit_bytes(&self) -> Option<u64> { utility::human_readable_to_bytes(&self.limit) }
+}
+
 impl AllMemTrackers {
     pub async fn perform_snapshot(
         hosts: &Vec<&str>,
@@ -184,6 +191,249 @@ impl AllMemTrackers {
     }
 }
 
+impl MemTrackersDiffFields {
+    /// The percentage of the limit that is currently consumed, if the memtracker has a limit.
+    pub fn limit_percentage(&self) -> Option<f64>
+    {
+        match (self.second_current_consumption, self.limit) {
+            (Some(current_consumption), Some(limit)) if limit > 0 => Some(current_consumption as f64 / limit as f64 * 100_f64),
+            _ => None,
+        }
+    }
+    /// Did the current consumption grow in every snapshot of the range?
+    /// This requires at least 3 snapshots, otherwise it's just the difference between begin and end.
+    pub fn is_growing(&self) -> bool
+    {
+        self.range_current_consumption.len() >= 3
+            && self.range_current_consumption.windows(2).all(|pair| pair[0] <= pair[1])
+            && self.range_current_consumption.last() > self.range_current_consumption.first()
+    }
+    fn difference(&self) -> i64
+    {
+        self.second_current_consumption.unwrap_or_default() as i64 - self.first_current_consumption.unwrap_or_default() as i64
+    }
+}
+
+impl MemTrackersDiff {
+    pub fn new() -> Self { Default::default() }
+    /// Read the memtrackers of all the snapshots from begin to end.
+    /// The begin and end snapshots are used for the difference, all the snapshots in between are used to determine monotonic growth.
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<MemTrackersDiff>
+    {
+        let mut memtrackersdiff = MemTrackersDiff::new();
+
+        for snapshot_number in snapshot::Snapshot::read_snapshot_range(begin_snapshot, end_snapshot)?
+        {
+            let mut allmemtrackers = AllMemTrackers::new();
+            allmemtrackers.memtrackers = match snapshot::read_snapshot_json(&snapshot_number, "memtrackers")
+            {
+                Ok(memtrackers) => memtrackers,
+                // a snapshot in the range can be taken without memtrackers, such as a snapshot with imported log lines.
+                Err(error) if snapshot_number != *begin_snapshot && snapshot_number != *end_snapshot => {
+                    warn!("Skipping snapshot {}: {:#}", snapshot_number, error);
+                    continue;
+                },
+                Err(error) => return Err(error),
+            };
+            if snapshot_number == *begin_snapshot
+            {
+                memtrackersdiff.first_snapshot(allmemtrackers);
+            }
+            else if snapshot_number == *end_snapshot
+            {
+                memtrackersdiff.second_snapshot(allmemtrackers);
+            }
+            else
+            {
+                memtrackersdiff.range_snapshot(allmemtrackers);
+            }
+        }
+
+        Ok(memtrackersdiff)
+    }
+    /// The memtracker ids are not unique: MemTable, operation_tracker, RegularDB and others are repeated per tablet.
+    /// Therefore the memtrackers are identified by their path from root, which is built from the depth hierarchy of the page.
+    fn paths(allmemtrackers: &AllMemTrackers) -> Vec<String>
+    {
+        let mut parents: Vec<&str> = Vec::new();
+        let mut previous_hostname_port = "";
+        allmemtrackers.memtrackers
+            .iter()
+            .map(|memtracker| {
+                if memtracker.hostname_port != previous_hostname_port
+                {
+                    parents.clear();
+                    previous_hostname_port = &memtracker.hostname_port;
+                }
+                if memtracker.id.contains("->")
+                {
+                    // older versions show the parents in the id.
+                    parents = memtracker.id.split("->").collect();
+                }
+                else
+                {
+                    parents.truncate(memtracker.depth.parse::<usize>().unwrap_or_default());
+                    parents.push(&memtracker.id);
+                }
+                parents.join("->")
+            })
+            .collect()
+    }
+    fn first_snapshot(
+        &mut self,
+        allmemtrackers: AllMemTrackers,
+    )
+    {
+        let paths = MemTrackersDiff::paths(&allmemtrackers);
+        for (position, (memtracker, path)) in allmemtrackers.memtrackers.iter().zip(paths).enumerate()
+        {
+            self.btreememtrackersdiff
+                .entry((memtracker.hostname_port.clone(), path.clone()))
+                .and_modify(|_| error!("Duplicate hostname:port + memtracker path entry: {}, {}", memtracker.hostname_port, path))
+                .or_insert(MemTrackersDiffFields {
+                    position,
+                    id: memtracker.id.clone(),
+                    depth: memtracker.depth.parse::<usize>().unwrap_or_default(),
+                    first_current_consumption: memtracker.current_consumption_bytes(),
+                    limit: memtracker.limit_bytes(),
+                    range_current_consumption: memtracker.current_consumption_bytes().into_iter().collect(),
+                    ..Default::default()
+                });
+        }
+    }
+    fn range_snapshot(
+        &mut self,
+        allmemtrackers: AllMemTrackers,
+    )
+    {
+        let paths = MemTrackersDiff::paths(&allmemtrackers);
+        for (memtracker, path) in allmemtrackers.memtrackers.into_iter().zip(paths)
+        {
+            if let (Some(memtrackersdifffields), Some(current_consumption)) = (self.btreememtrackersdiff.get_mut(&(memtracker.hostname_port.clone(), path)), memtracker.current_consumption_bytes())
+            {
+                memtrackersdifffields.range_current_consumption.push(current_consumption);
+            }
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allmemtrackers: AllMemTrackers,
+    )
+    {
+        let paths = MemTrackersDiff::paths(&allmemtrackers);
+        for (position, (memtracker, path)) in allmemtrackers.memtrackers.iter().zip(paths).enumerate()
+        {
+            self.btreememtrackersdiff
+                .entry((memtracker.hostname_port.clone(), path))
+                .and_modify(|memtrackersdifffields| {
+                    memtrackersdifffields.position = position;
+                    memtrackersdifffields.second_current_consumption = memtracker.current_consumption_bytes();
+                    memtrackersdifffields.second_peak_consumption = memtracker.peak_consumption_bytes();
+                    memtrackersdifffields.limit = memtracker.limit_bytes();
+                    memtrackersdifffields.range_current_consumption.extend(memtracker.current_consumption_bytes());
+                })
+                .or_insert(MemTrackersDiffFields {
+                    position,
+                    id: memtracker.id.clone(),
+                    depth: memtracker.depth.parse::<usize>().unwrap_or_default(),
+                    second_current_consumption: memtracker.current_consumption_bytes(),
+                    second_peak_consumption: memtracker.peak_consumption_bytes(),
+                    limit: memtracker.limit_bytes(),
+                    range_current_consumption: memtracker.current_consumption_bytes().into_iter().collect(),
+                    ..Default::default()
+                });
+        }
+    }
+    /// Print the memtrackers that changed, in the order of the `/mem-trackers` page to keep the depth hierarchy.
+    /// Memtrackers that consume more than limit_percentage_threshold of their limit, or that grew in every snapshot are highlighted,
+    /// and are always printed.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        stat_name_filter: &Regex,
+        details_enable: &bool,
+        limit_percentage_threshold: f64,
+    )
+    {
+        let mut rows: Vec<_> = self.btreememtrackersdiff
+            .iter()
+            .filter(|((hostname_port, _), row)| hostname_filter.is_match(hostname_port) && stat_name_filter.is_match(&row.id))
+            .collect();
+        rows.sort_by(|((a_hostname_port, _), a), ((b_hostname_port, _), b)| a_hostname_port.cmp(b_hostname_port).then(a.position.cmp(&b.position)));
+
+        let mut previous_hostname_port = String::new();
+        for ((hostname_port, _), row) in rows
+        {
+            let over_limit = row.limit_percentage().map(|percentage| percentage >= limit_percentage_threshold).unwrap_or(false);
+            if row.difference() == 0
+                && !over_limit
+                && !row.is_growing()
+                && !*details_enable
+            {
+                continue;
+            }
+            if *hostname_port != previous_hostname_port
+            {
+                println!("{}", "-".repeat(174));
+                println!("Host: {}", hostname_port);
+                println!("{}", "-".repeat(174));
+                println!("{:90} {:>12} {:>12} {:>12} {:>12} {:>12} {:>8}",
+                         "id",
+                         "first",
+                         "second",
+                         "difference",
+                         "peak",
+                         "limit",
+                         "limit%");
+                println!("{}", "-".repeat(174));
+                previous_hostname_port = hostname_port.to_string();
+            }
+            let difference = if row.difference() < 0
+            {
+                format!("-{}", utility::bytes_to_human_readable(row.difference().unsigned_abs()))
+            }
+            else
+            {
+                format!("+{}", utility::bytes_to_human_readable(row.difference() as u64))
+            };
+            let limit_percentage = row.limit_percentage().map(|percentage| format!("{:8.1}", percentage)).unwrap_or_default();
+            println!("{:90} {:>12} {:>12} {:>12} {:>12} {:>12} {:>8} {}",
+                     " ".repeat(row.depth) + &row.id,
+                     row.first_current_consumption.map(utility::bytes_to_human_readable).unwrap_or_default(),
+                     row.second_current_consumption.map(utility::bytes_to_human_readable).unwrap_or_default(),
+                     if row.is_growing() { difference.yellow() } else { difference.normal() },
+                     row.second_peak_consumption.map(utility::bytes_to_human_readable).unwrap_or_default(),
+                     row.limit.map(utility::bytes_to_human_readable).unwrap_or_else(|| "none".to_string()),
+                     if over_limit { limit_percentage.red() } else { limit_percentage.normal() },
+                     if row.is_growing() { "growing".yellow() } else { "".normal() },
+            );
+        }
+    }
+}
+
+pub async fn memtrackers_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let memtrackersdiff = MemTrackersDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    memtrackersdiff.print(&hostname_filter, &stat_name_filter, &options.details_enable, options.memtrackers_limit_pct);
+
+    Ok(())
+}
+
 pub async fn print_memtrackers(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -212,10 +462,8 @@ pub async fn print_memtrackers(
 mod tests {
     use super::*;
 
-    #[test]
-    fn unit_parse_memtrackers_data() {
-        // This is what /mem-trackers returns.
-        let memtrackers = r#"
+    /// This is what /mem-trackers of a tablet server returns.
+    const TSERVER_MEMTRACKERS: &str = r#"
 <!DOCTYPE html><html>  <head>    <title>YugabyteDB</title>    <link rel='shortcut icon' href='/favicon.ico'>    <link href='/bootstrap/css/bootstrap.min.css' rel='stylesheet' media='screen' />    <link href='/bootstrap/css/bootstrap-theme.min.css' rel='stylesheet' media='screen' />    <link href='/font-awesome/css/font-awesome.min.css' rel='stylesheet' media='screen' />    <link href='/yb.css' rel='stylesheet' media='screen' />  </head>
 <body>
   <nav class="navbar navbar-fixed-top navbar-inverse sidebar-wrapper" role="navigation">    <ul class="nav sidebar-nav">      <li><a href='/'><img src='/logo.png' alt='YugabyteDB' class='nav-logo' /></a></li>
@@ -1267,11 +1515,54 @@ mod tests {
 <div class='yb-bottom-spacer'></div></div>
 <footer class='footer'><div class='yb-footer container text-muted'><pre class='message'><i class="fa-lg fa fa-gift" aria-hidden="true"></i> Congratulations on installing YugabyteDB. We'd like to welcome you to the community with a free t-shirt and pack of stickers! Please claim your reward here: <a href='https://www.yugabyte.com/community-rewards/'>https://www.yugabyte.com/community-rewards/</a></pre><pre>version 2.11.2.0 build 89 revision d142556567b5e1c83ea5c915ec7b9964492b2321 build_type RELEASE built at 25 Jan 2022 17:51:08 UTC
 server uuid 05b8d17620eb4cd79eddaddb2fbcbb42</pre></div></footer></body></html>
-"#.to_string();
-        let result = AllMemTrackers::parse_memtrackers(memtrackers);
+"#;
+
+    #[test]
+    fn unit_parse_memtrackers_data() {
+        let result = AllMemTrackers::parse_memtrackers(TSERVER_MEMTRACKERS.to_string());
         assert_eq!(result.len(), 345);
     }
 
+    #[test]
+    fn unit_memtrackers_diff_unique_paths() {
+        let mut allmemtrackers = AllMemTrackers::new();
+        allmemtrackers.memtrackers = AllMemTrackers::parse_memtrackers(TSERVER_MEMTRACKERS.to_string());
+        // ids such as MemTable and operation_tracker are repeated for every tablet.
+        assert!(allmemtrackers.memtrackers.iter().filter(|memtracker| memtracker.id == "MemTable").count() > 1);
+        let mut memtrackersdiff = MemTrackersDiff::new();
+        memtrackersdiff.first_snapshot(allmemtrackers);
+        assert_eq!(memtrackersdiff.btreememtrackersdiff.len(), 345);
+        assert!(memtrackersdiff.btreememtrackersdiff.contains_key(&("".to_string(), "root->server->BlockBasedTable".to_string())));
+    }
+
+    #[test]
+    fn unit_memtrackers_diff_growing_and_limit() {
+        let memtracker = |current_consumption: &str| MemTrackers {
+            hostname_port: "192.168.66.80:9000".to_string(),
+            id: "root->server".to_string(),
+            current_consumption: current_consumption.to_string(),
+            peak_consumption: current_consumption.to_string(),
+            limit: "100.00M".to_string(),
+            depth: "1".to_string(),
+            ..Default::default()
+        };
+        let mut memtrackersdiff = MemTrackersDiff::new();
+        for (snapshot, current_consumption) in ["10.00M", "50.00M", "90.00M"].iter().enumerate() {
+            let mut allmemtrackers = AllMemTrackers::new();
+            allmemtrackers.memtrackers.push(memtracker(current_consumption));
+            match snapshot {
+                0 => memtrackersdiff.first_snapshot(allmemtrackers),
+                2 => memtrackersdiff.second_snapshot(allmemtrackers),
+                _ => memtrackersdiff.range_snapshot(allmemtrackers),
+            }
+        }
+        let row = &memtrackersdiff.btreememtrackersdiff[&("192.168.66.80:9000".to_string(), "root->server".to_string())];
+        assert_eq!(row.depth, 1);
+        assert_eq!(row.difference(), 80 * 1024 * 1024);
+        assert!(row.is_growing());
+        assert_eq!(row.limit_percentage(), Some(90_f64));
+    }
+
     #[tokio::test]
     async fn integration_parse_memtrackers_master() {
         let hostname = utility::get_hostname_master();
@@ -1,5 +1,6 @@
 //! The structs
 //!
+use std::collections::BTreeMap;
 use chrono::{DateTime, Local};
 
 /// The root struct for deserializing the memtrackers HTML table.
@@ -21,3 +22,29 @@ pub struct MemTrackers {
 pub struct AllMemTrackers {
     pub memtrackers: Vec<MemTrackers>,
 }
+// diff
+/// BTreeMap for storing a memtrackers diff struct per hostname_port, path
+type BTreeMemTrackersDiff = BTreeMap<(String, String), MemTrackersDiffFields>;
+/// Wrapper struct for holding the btreemap
+#[derive(Debug, Default)]
+pub struct MemTrackersDiff {
+    pub btreememtrackersdiff: BTreeMemTrackersDiff,
+}
+/// The memtrackers diff struct.
+///
+/// The hostname:port and the path of the memtracker from root (root->server->BlockBasedTable) is the key of the btreemap,
+/// because the same id is used by the memtrackers of every tablet.
+/// The consumption and limit values are the human-readable values parsed into bytes.
+/// `position` is the row number of the memtracker on the `/mem-trackers` page, which keeps the order of the depth hierarchy.
+/// `range_current_consumption` holds the current consumption of every snapshot in the range, in snapshot order.
+#[derive(Debug, Default)]
+pub struct MemTrackersDiffFields {
+    pub position: usize,
+    pub id: String,
+    pub depth: usize,
+    pub first_current_consumption: Option<u64>,
+    pub second_current_consumption: Option<u64>,
+    pub second_peak_consumption: Option<u64>,
+    pub limit: Option<u64>,
+    pub range_current_consumption: Vec<u64>,
+}
@@ -0,0 +1,864 @@
+//! The impls and functions
+//!
+use std::{cmp::Ordering, collections::BTreeMap, fmt};
+use regex::Regex;
+use colored::*;
+use log::*;
+use anyhow::{bail, Context, Result};
+use crate::utility;
+use crate::snapshot;
+use crate::isleader::AllIsLeader;
+use crate::entities::AllEntities;
+use crate::tablet_servers::AllTabletServers;
+use crate::clocks::AllClocks;
+use crate::table_detail::{AllTables, Column, TableBasic};
+use crate::tablet_detail::AllTablets;
+use crate::placement::Location;
+use crate::partitions::{AllPartitionCoverage, CoverageTablet, KeyLocation, KeyReplica, Partition, PartitionCoverage, RangeValue};
+use crate::Opts;
+
+/// The number of characters of the hash key space map, every character is 1024 hash values.
+const KEY_SPACE_MAP_WIDTH: u32 = 64;
+
+/// The 64 bit mix function of Bob Jenkins' lookup8.
+fn mix(
+    a: &mut u64,
+    b: &mut u64,
+    c: &mut u64,
+)
+{
+    *a = a.wrapping_sub(*b).wrapping_sub(*c); *a ^= *c >> 43;
+    *b = b.wrapping_sub(*c).wrapping_sub(*a); *b ^= *a << 9;
+    *c = c.wrapping_sub(*a).wrapping_sub(*b); *c ^= *b >> 8;
+    *a = a.wrapping_sub(*b).wrapping_sub(*c); *a ^= *c >> 38;
+    *b = b.wrapping_sub(*c).wrapping_sub(*a); *b ^= *a << 23;
+    *c = c.wrapping_sub(*a).wrapping_sub(*b); *c ^= *b >> 5;
+    *a = a.wrapping_sub(*b).wrapping_sub(*c); *a ^= *c >> 35;
+    *b = b.wrapping_sub(*c).wrapping_sub(*a); *b ^= *a << 49;
+    *c = c.wrapping_sub(*a).wrapping_sub(*b); *c ^= *b >> 11;
+    *a = a.wrapping_sub(*b).wrapping_sub(*c); *a ^= *c >> 12;
+    *b = b.wrapping_sub(*c).wrapping_sub(*a); *b ^= *a << 18;
+    *c = c.wrapping_sub(*a).wrapping_sub(*b); *c ^= *b >> 22;
+}
+
+/// Hash64StringWithSeed of `src/yb/gutil/hash/hash.cc`: the Jenkins lookup8 hash.
+pub fn hash64_string_with_seed(
+    bytes: &[u8],
+    seed: u64,
+) -> u64
+{
+    // little endian load of (at most) 8 bytes.
+    let word = |bytes: &[u8]| -> u64 {
+        bytes.iter().take(8).enumerate().fold(0_u64, |word, (nr, byte)| word | (*byte as u64) << (8 * nr))
+    };
+    let mut a: u64 = 0xe08c1d668b756f82;
+    let mut b: u64 = 0xe08c1d668b756f82;
+    let mut c: u64 = seed;
+
+    let mut chunks = bytes.chunks_exact(24);
+    for chunk in chunks.by_ref()
+    {
+        a = a.wrapping_add(word(&chunk[0..8]));
+        b = b.wrapping_add(word(&chunk[8..16]));
+        c = c.wrapping_add(word(&chunk[16..24]));
+        mix(&mut a, &mut b, &mut c);
+    }
+    let rest = chunks.remainder();
+    c = c.wrapping_add(bytes.len() as u64);
+    // the first byte of c is reserved for the length.
+    if rest.len() > 16
+    {
+        c = c.wrapping_add(word(&rest[16..]) << 8);
+    }
+    b = b.wrapping_add(word(rest.get(8..).unwrap_or_default()));
+    a = a.wrapping_add(word(rest));
+    mix(&mut a, &mut b, &mut c);
+    c
+}
+
+/// The hash value of an encoded hash key: YBPartition::HashColumnCompoundValue of `src/yb/common/partition.cc`.
+/// This is the value of `yb_hash_code()` in YSQL and `partition_hash()` in YCQL.
+pub fn hash_code(
+    encoded_hash_key: &[u8],
+) -> u16
+{
+    let hash_value = hash64_string_with_seed(encoded_hash_key, 97);
+    let h1 = hash_value >> 48;
+    let h2 = 3 * (hash_value >> 32);
+    let h3 = 5 * (hash_value >> 16);
+    let h4 = 7 * (hash_value & 0xffff);
+    ((h1 ^ h2 ^ h3 ^ h4) & 0xffff) as u16
+}
+
+/// Encode the values of the hash columns in the way YugabyteDB does before hashing them:
+/// integers are big endian, strings and binaries are the bytes.
+/// The hash columns are (column name, column type) as shown on the `/table?id=` page.
+pub fn encode_hash_key(
+    hash_columns: &[(&str, &str)],
+    values: &[&str],
+) -> Result<Vec<u8>>
+{
+    if hash_columns.len() != values.len()
+    {
+        bail!("The table has {} hash column(s) ({}), the key has {} value(s)",
+            hash_columns.len(),
+            hash_columns.iter().map(|(column, _)| *column).collect::<Vec<_>>().join(", "),
+            values.len()
+        );
+    }
+    let mut encoded_hash_key: Vec<u8> = Vec::new();
+    for ((column, column_type), value) in hash_columns.iter().zip(values)
+    {
+        let invalid_value = || format!("Invalid value {} for column {} of type {}", value, column, column_type);
+        match *column_type
+        {
+            "int8" => encoded_hash_key.extend(value.parse::<i8>().with_context(invalid_value)?.to_be_bytes()),
+            "int16" => encoded_hash_key.extend(value.parse::<i16>().with_context(invalid_value)?.to_be_bytes()),
+            "int32" => encoded_hash_key.extend(value.parse::<i32>().with_context(invalid_value)?.to_be_bytes()),
+            "int64" => encoded_hash_key.extend(value.parse::<i64>().with_context(invalid_value)?.to_be_bytes()),
+            "string" | "binary" => encoded_hash_key.extend(value.as_bytes()),
+            unsupported => bail!("Unsupported type {} of hash column {}", unsupported, column),
+        }
+    }
+    Ok(encoded_hash_key)
+}
+
+/// True if a tablet is part of the key space of the table: not hidden (such as a split parent), deleted or replaced.
+pub fn is_active_tablet(
+    state: &str,
+    hidden: &str,
+) -> bool
+{
+    !hidden.eq_ignore_ascii_case("true")
+        && !matches!(state.to_uppercase().as_str(), "DELETED" | "REPLACED" | "TOMBSTONED")
+}
+
+/// The sort order of the range columns from the column types of table_detail: true for a descending column.
+/// The range columns are the key columns after the hash columns, a descending range column has DESC in its type,
+/// the value columns that follow never have.
+pub fn range_columns_descending(
+    columns: &[Option<Column>],
+) -> Vec<bool>
+{
+    columns
+        .iter()
+        .flatten()
+        .filter(|column| !column.column_type.ends_with("PARTITION KEY") || column.column_type.ends_with("NOT A PARTITION KEY"))
+        .map(|column| column.column_type.contains("DESC"))
+        .collect()
+}
+
+/// Split text on the commas outside of parentheses, brackets and double quotes.
+fn split_top_level(
+    text: &str,
+) -> Vec<&str>
+{
+    let mut parts: Vec<&str> = Vec::new();
+    let mut depth = 0;
+    let mut in_quotes = false;
+    let mut begin = 0;
+    let mut previous = ' ';
+    for (position, character) in text.char_indices()
+    {
+        match character
+        {
+            '"' if previous != '\\' => in_quotes = !in_quotes,
+            '(' | '[' if !in_quotes => depth += 1,
+            ')' | ']' if !in_quotes => depth -= 1,
+            ',' if !in_quotes && depth == 0 => {
+                parts.push(text[begin..position].trim());
+                begin = position + 1;
+            },
+            _ => {},
+        }
+        previous = character;
+    }
+    parts.push(text[begin..].trim());
+    parts
+}
+
+/// Compare two range keys per column value, a key that is the prefix of another key is lower.
+/// The order of a value is reversed for a descending column, see [range_columns_descending].
+pub fn compare_range_keys(
+    a: &[RangeValue],
+    b: &[RangeValue],
+    descending: &[bool],
+) -> Ordering
+{
+    for (column_nr, (value_a, value_b)) in a.iter().zip(b).enumerate()
+    {
+        let ordering = match (value_a, value_b)
+        {
+            (RangeValue::Int(a), RangeValue::Int(b)) => a.cmp(b),
+            (value_a, value_b) => value_a.to_string().cmp(&value_b.to_string()),
+        };
+        let ordering = if descending.get(column_nr) == Some(&true) { ordering.reverse() } else { ordering };
+        if ordering.is_ne()
+        {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+impl RangeValue {
+    pub fn parse(
+        value: &str,
+    ) -> RangeValue
+    {
+        let value = value.trim();
+        match value.parse::<i64>()
+        {
+            Ok(int) => RangeValue::Int(int),
+            Err(_) => RangeValue::Text(value.strip_prefix('"').and_then(|value| value.strip_suffix('"')).unwrap_or(value).to_string()),
+        }
+    }
+}
+
+impl fmt::Display for RangeValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self
+        {
+            RangeValue::Int(int) => write!(f, "{}", int),
+            RangeValue::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl Partition {
+    /// Parse the partition text: `hash_split: [0x0000, 0x5554]` or `range: [<start>, DocKey([], [100]))`.
+    pub fn parse(
+        partition: &str,
+    ) -> Option<Partition>
+    {
+        let partition = partition.trim();
+        if let Some(captures) = Regex::new(r"^hash_split: \[0x([0-9A-Fa-f]{1,4}), 0x([0-9A-Fa-f]{1,4})\]$").unwrap().captures(partition)
+        {
+            return Some(Partition::Hash {
+                start: u16::from_str_radix(&captures[1], 16).ok()?,
+                end: u16::from_str_radix(&captures[2], 16).ok()?,
+            });
+        }
+        let bounds = partition.strip_prefix("range: [")?.strip_suffix(')')?;
+        // a bound is <start>, <end>, or a DocKey with the range column values as the last list: DocKey([], [100, "a"])
+        let bound = |bound: &str| -> Option<Vec<RangeValue>> {
+            let values = &bound[bound.rfind('[')? + 1..bound.rfind(']')?];
+            Some(split_top_level(values).into_iter().filter(|value| !value.is_empty()).map(RangeValue::parse).collect())
+        };
+        match split_top_level(bounds)[..]
+        {
+            [start, end] => Some(Partition::Range {
+                start: if start == "<start>" { None } else { Some(bound(start)?) },
+                end: if end == "<end>" { None } else { Some(bound(end)?) },
+            }),
+            _ => None,
+        }
+    }
+    pub fn contains_hash(
+        &self,
+        hash: u16,
+    ) -> bool
+    {
+        matches!(self, Partition::Hash { start, end } if *start <= hash && hash <= *end)
+    }
+    pub fn contains_range_key(
+        &self,
+        range_key: &[RangeValue],
+        descending: &[bool],
+    ) -> bool
+    {
+        match self
+        {
+            Partition::Range { start, end } => {
+                start.as_ref().map(|start| compare_range_keys(start, range_key, descending).is_le()).unwrap_or(true)
+                    && end.as_ref().map(|end| compare_range_keys(range_key, end, descending).is_lt()).unwrap_or(true)
+            },
+            Partition::Hash { .. } => false,
+        }
+    }
+}
+
+impl KeyLocation {
+    /// Find the tablet for the key of the table, with the replicas of the tablet from the entities of the master leader.
+    pub fn locate(
+        alltables: &AllTables,
+        allentities: &AllEntities,
+        alltabletservers: &AllTabletServers,
+        allclocks: &AllClocks,
+        master_leader: &str,
+        table_name: &str,
+        key: &str,
+    ) -> Result<KeyLocation>
+    {
+        let table = alltables.table
+            .iter()
+            .find(|table| table.hostname_port.as_deref() == Some(master_leader))
+            .with_context(|| format!("No table data found for the master leader {}", master_leader))?;
+        if table.tabledetail.is_empty()
+        {
+            bail!("No table detail found, locate key requires a snapshot taken with the --extra-data switch");
+        }
+        let tables: Vec<&TableBasic> = table.tablebasic
+            .iter()
+            .filter(|tablebasic| tablebasic.table_name == table_name || format!("{}.{}", tablebasic.keyspace, tablebasic.table_name) == table_name)
+            .collect();
+        let tablebasic = match tables[..]
+        {
+            [] => bail!("Table not found: {}", table_name),
+            [tablebasic] => tablebasic,
+            _ => bail!("Table name {} is found in multiple keyspaces, use keyspace.table_name: {}", table_name, tables.iter().map(|tablebasic| format!("{}.{}", tablebasic.keyspace, tablebasic.table_name)).collect::<Vec<_>>().join(", ")),
+        };
+        let table_detail = table.tabledetail
+            .iter()
+            .flatten()
+            .find(|table_detail| table_detail.uuid == tablebasic.uuid)
+            .with_context(|| format!("No table detail found for table {}.{}", tablebasic.keyspace, tablebasic.table_name))?;
+
+        let tablets: Vec<(&crate::table_detail::Tablet, Partition)> = table_detail.tablets
+            .iter()
+            .flatten()
+            .filter(|tablet| is_active_tablet(&tablet.state, &tablet.hidden))
+            .filter_map(|tablet| Partition::parse(&tablet.partition).map(|partition| (tablet, partition)))
+            .collect();
+        if tablets.is_empty()
+        {
+            bail!("No tablets with a partition found for table {}.{}", tablebasic.keyspace, tablebasic.table_name);
+        }
+
+        let mut keylocation = KeyLocation {
+            table: format!("{}.{}", tablebasic.keyspace, tablebasic.table_name),
+            table_id: tablebasic.uuid.clone(),
+            key: key.to_string(),
+            ..Default::default()
+        };
+        let values: Vec<&str> = key.split(',').map(str::trim).collect();
+        let tablet = if matches!(tablets[0].1, Partition::Hash { .. })
+        {
+            let hash_columns: Vec<(&str, &str)> = table_detail.columns
+                .iter()
+                .flatten()
+                .filter(|column| column.column_type.ends_with("PARTITION KEY") && !column.column_type.ends_with("NOT A PARTITION KEY"))
+                .map(|column| (column.column.as_str(), column.column_type.split_whitespace().next().unwrap_or_default()))
+                .collect();
+            let hash = hash_code(&encode_hash_key(&hash_columns, &values)?);
+            keylocation.hash = Some(hash);
+            tablets.iter().find(|(_, partition)| partition.contains_hash(hash))
+        }
+        else
+        {
+            let range_key: Vec<RangeValue> = values.iter().map(|value| RangeValue::parse(value)).collect();
+            let descending = range_columns_descending(&table_detail.columns);
+            tablets.iter().find(|(_, partition)| partition.contains_range_key(&range_key, &descending))
+        };
+        let (tablet, _) = tablet.with_context(|| format!("No tablet found for key {} of table {}", key, keylocation.table))?;
+        keylocation.tablet_id = tablet.id.clone();
+        keylocation.partition = tablet.partition.clone();
+        keylocation.raftconfig = tablet.raftconfig.clone();
+
+        let tablet_server_locations = Location::tablet_server_locations(alltabletservers, allclocks);
+        if let Some(entities_tablet) = allentities.entities
+            .iter()
+            .filter(|entities| entities.hostname_port.as_deref() == Some(master_leader))
+            .flat_map(|entities| entities.tablets.iter())
+            .find(|entities_tablet| entities_tablet.tablet_id == tablet.id)
+        {
+            for replica in entities_tablet.replicas.as_deref().unwrap_or_default()
+            {
+                keylocation.replicas.push(KeyReplica {
+                    role: match replica.replica_type.as_str()
+                    {
+                        _ if entities_tablet.leader.as_ref() == Some(&replica.server_uuid) => "LEADER".to_string(),
+                        "VOTER" => "FOLLOWER".to_string(),
+                        replica_type => replica_type.to_string(),
+                    },
+                    addr: replica.addr.clone(),
+                    location: tablet_server_locations.get(&replica.server_uuid).cloned().unwrap_or_else(|| {
+                        warn!("Replica server {} ({}) not found in the tablet servers, its location is unknown", replica.server_uuid, replica.addr);
+                        Location::unknown()
+                    }),
+                });
+            }
+            keylocation.replicas.sort_by_key(|replica| replica.role != "LEADER");
+        }
+        Ok(keylocation)
+    }
+    pub fn print(&self)
+    {
+        println!("{:10} {} ({})", "Table:", self.table, self.table_id);
+        match self.hash
+        {
+            Some(hash) => println!("{:10} {}, hash: 0x{:04X} ({})", "Key:", self.key, hash, hash),
+            None => println!("{:10} {}", "Key:", self.key),
+        }
+        println!("{:10} {} {}", "Tablet:", self.tablet_id, self.partition);
+        if self.replicas.is_empty()
+        {
+            println!("{:10} {}", "Raft:", self.raftconfig);
+        }
+        for replica in &self.replicas
+        {
+            println!("  {:12} {:30} {}", replica.role, replica.addr, replica.location);
+        }
+    }
+}
+
+/// The letter of a tablet in the key space map.
+fn map_letter(
+    nr: usize,
+) -> char
+{
+    let letters = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    letters[nr % letters.len()] as char
+}
+
+/// Format a range bound, None is the start or the end of the key space.
+fn format_range_bound(
+    bound: &Option<Vec<RangeValue>>,
+    start_or_end: &str,
+) -> String
+{
+    match bound
+    {
+        Some(values) => format!("[{}]", values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ")),
+        None => start_or_end.to_string(),
+    }
+}
+
+impl PartitionCoverage {
+    /// The tablets with a known partition, ordered by the start of the partition.
+    fn ordered_tablets(&self) -> Vec<(&CoverageTablet, Partition)>
+    {
+        let mut tablets: Vec<(&CoverageTablet, Partition)> = self.tablets
+            .iter()
+            .filter_map(|tablet| Partition::parse(&tablet.partition).map(|partition| (tablet, partition)))
+            .collect();
+        tablets.sort_by(|(_, a), (_, b)| match (a, b)
+        {
+            (Partition::Hash { start: a, .. }, Partition::Hash { start: b, .. }) => a.cmp(b),
+            (Partition::Range { start: a, .. }, Partition::Range { start: b, .. }) => match (a, b)
+            {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (Some(a), Some(b)) => compare_range_keys(a, b, &self.range_descending),
+            },
+            (Partition::Hash { .. }, Partition::Range { .. }) => Ordering::Less,
+            (Partition::Range { .. }, Partition::Hash { .. }) => Ordering::Greater,
+        });
+        tablets
+    }
+    /// Find the gaps and overlaps in the key space of the table.
+    pub fn check(&mut self)
+    {
+        let mut issues: Vec<String> = self.tablets
+            .iter()
+            .filter(|tablet| Partition::parse(&tablet.partition).is_none())
+            .map(|tablet| format!("tablet {} has an unknown partition: {}", tablet.tablet_id, tablet.partition))
+            .collect();
+        let tablets = self.ordered_tablets();
+
+        if tablets.iter().any(|(_, partition)| matches!(partition, Partition::Hash { .. }))
+            && tablets.iter().any(|(_, partition)| matches!(partition, Partition::Range { .. }))
+        {
+            issues.push("the tablets have both hash and range partitions".to_string());
+        }
+        else if matches!(tablets.first(), Some((_, Partition::Hash { .. })))
+        {
+            // the first hash value that is not covered yet.
+            let mut next: u32 = 0;
+            for (tablet, partition) in &tablets
+            {
+                let Partition::Hash { start, end } = partition else { continue };
+                let (start, end) = (*start as u32, *end as u32);
+                if start > next
+                {
+                    issues.push(format!("gap 0x{:04X}-0x{:04X} before tablet {}", next, start - 1, tablet.tablet_id));
+                }
+                if start < next
+                {
+                    issues.push(format!("overlap 0x{:04X}-0x{:04X} of tablet {} with the previous tablets", start, end.min(next - 1), tablet.tablet_id));
+                }
+                next = next.max(end + 1);
+            }
+            if next <= 0xFFFF
+            {
+                issues.push(format!("gap 0x{:04X}-0xFFFF at the end", next));
+            }
+        }
+        else
+        {
+            // None: no tablet yet, Some(None): the previous tablet ends at the end of the key space.
+            let mut previous_end: Option<&Option<Vec<RangeValue>>> = None;
+            for (tablet, partition) in &tablets
+            {
+                let Partition::Range { start, end } = partition else { continue };
+                match (previous_end, start)
+                {
+                    (None, Some(_)) => issues.push(format!("gap <start>-{} before tablet {}", format_range_bound(start, "<start>"), tablet.tablet_id)),
+                    (Some(None), _) | (Some(Some(_)), None) => issues.push(format!("overlap of tablet {} with the previous tablets", tablet.tablet_id)),
+                    (Some(Some(previous)), Some(next)) => match compare_range_keys(previous, next, &self.range_descending)
+                    {
+                        Ordering::Less => issues.push(format!("gap {}-{} before tablet {}", format_range_bound(&Some(previous.clone()), ""), format_range_bound(start, ""), tablet.tablet_id)),
+                        Ordering::Greater => issues.push(format!("overlap {}-{} of tablet {} with the previous tablets", format_range_bound(start, ""), format_range_bound(&Some(previous.clone()), ""), tablet.tablet_id)),
+                        Ordering::Equal => {},
+                    },
+                    (None, None) => {},
+                }
+                previous_end = Some(end);
+            }
+            if let Some(Some(end)) = previous_end
+            {
+                issues.push(format!("gap {}-<end> at the end", format_range_bound(&Some(end.clone()), "")));
+            }
+        }
+        if tablets.is_empty() && issues.is_empty()
+        {
+            issues.push("no tablets".to_string());
+        }
+        self.issues = issues;
+    }
+    /// The key space map of the table: a line with a letter per tablet, and a legend line per tablet.
+    /// For a hash partitioned table, the width of a tablet is its part of the hash space, a gap is '.' and an overlap is '#'.
+    /// For a range partitioned table, the tablets are shown in order between their bounds.
+    pub fn key_space_map(&self) -> Vec<String>
+    {
+        let tablets = self.ordered_tablets();
+        let mut lines: Vec<String> = Vec::new();
+
+        if matches!(tablets.first(), Some((_, Partition::Hash { .. })))
+        {
+            let map: String = (0..KEY_SPACE_MAP_WIDTH)
+                .map(|column| {
+                    let hash = (column * (0x10000 / KEY_SPACE_MAP_WIDTH)) as u16;
+                    let covering: Vec<usize> = tablets
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (_, partition))| partition.contains_hash(hash))
+                        .map(|(nr, _)| nr)
+                        .collect();
+                    match covering[..]
+                    {
+                        [] => '.',
+                        [nr] => map_letter(nr),
+                        _ => '#',
+                    }
+                })
+                .collect();
+            lines.push(format!("0x0000 |{}| 0xFFFF", map));
+        }
+        else
+        {
+            let mut map: Vec<String> = Vec::new();
+            let mut previous_end: Option<&Option<Vec<RangeValue>>> = None;
+            for (nr, (_, partition)) in tablets.iter().enumerate()
+            {
+                let Partition::Range { start, end } = partition else { continue };
+                if previous_end != Some(start)
+                {
+                    map.push(format_range_bound(start, "<start>"));
+                }
+                map.push(format!("|{}|", map_letter(nr)));
+                map.push(format_range_bound(end, "<end>"));
+                previous_end = Some(end);
+            }
+            lines.push(map.join(" "));
+        }
+        for (nr, (tablet, partition)) in tablets.iter().enumerate()
+        {
+            let share = match partition
+            {
+                Partition::Hash { start, end } => format!("{:5.1}%", (*end as f64 - *start as f64 + 1.) / 65536. * 100.),
+                Partition::Range { .. } => String::new(),
+            };
+            lines.push(format!("{} {:32} {:40} {:6} {}", map_letter(nr), tablet.tablet_id, tablet.partition, share, tablet.on_disk_size));
+        }
+        lines
+    }
+}
+
+impl AllPartitionCoverage {
+    pub fn new() -> Self { Default::default() }
+    /// Check the partition coverage of the tables using the tablets of the master leader (table_detail),
+    /// and using the tablets of the tablet servers (tablet_detail).
+    pub fn new_from_data(
+        alltables: &AllTables,
+        alltablets: &AllTablets,
+        master_leader: &str,
+    ) -> AllPartitionCoverage
+    {
+        let mut allpartitioncoverage = AllPartitionCoverage::new();
+
+        // tablet_id, total on disk size
+        let on_disk_sizes: BTreeMap<&String, String> = alltablets.tablet
+            .iter()
+            .flat_map(|tablet| tablet.tabletbasic.iter())
+            .map(|tabletbasic| (
+                &tabletbasic.tablet_id,
+                tabletbasic.on_disk_size.strip_prefix("Total: ").and_then(|size| size.split_whitespace().next()).unwrap_or(&tabletbasic.on_disk_size).to_string(),
+            ))
+            .collect();
+
+        // table uuid, sort order of the range columns, for the tablets of the tablet servers too.
+        let mut range_descending: BTreeMap<&String, Vec<bool>> = BTreeMap::new();
+        for table in alltables.table.iter().filter(|table| table.hostname_port.as_deref() == Some(master_leader))
+        {
+            for table_detail in table.tabledetail.iter().flatten()
+            {
+                range_descending.insert(&table_detail.uuid, range_columns_descending(&table_detail.columns));
+                let tablets: Vec<CoverageTablet> = table_detail.tablets
+                    .iter()
+                    .flatten()
+                    .filter(|tablet| is_active_tablet(&tablet.state, &tablet.hidden))
+                    .map(|tablet| CoverageTablet {
+                        tablet_id: tablet.id.clone(),
+                        partition: tablet.partition.clone(),
+                        on_disk_size: on_disk_sizes.get(&tablet.id).cloned().unwrap_or_default(),
+                    })
+                    .collect();
+                if tablets.is_empty()
+                {
+                    continue;
+                }
+                allpartitioncoverage.coverage.push(PartitionCoverage {
+                    table: table.tablebasic
+                        .iter()
+                        .find(|tablebasic| tablebasic.uuid == table_detail.uuid)
+                        .map(|tablebasic| format!("{}.{}", tablebasic.keyspace, tablebasic.table_name))
+                        .unwrap_or_else(|| table_detail.uuid.clone()),
+                    source: "master".to_string(),
+                    tablets,
+                    range_descending: range_descending.get(&table_detail.uuid).cloned().unwrap_or_default(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        // the replicas of a tablet are on multiple tablet servers, a tablet is added once.
+        let mut tserver_coverage: BTreeMap<&String, PartitionCoverage> = BTreeMap::new();
+        for tabletbasic in alltablets.tablet
+            .iter()
+            .flat_map(|tablet| tablet.tabletbasic.iter())
+            .filter(|tabletbasic| is_active_tablet(&tabletbasic.state, &tabletbasic.hidden))
+        {
+            let partitioncoverage = tserver_coverage.entry(&tabletbasic.table_uuid).or_insert_with(|| PartitionCoverage {
+                table: format!("{}.{}", tabletbasic.namespace, tabletbasic.table_name),
+                source: "tserver".to_string(),
+                range_descending: range_descending.get(&tabletbasic.table_uuid).cloned().unwrap_or_default(),
+                ..Default::default()
+            });
+            if !partitioncoverage.tablets.iter().any(|tablet| tablet.tablet_id == tabletbasic.tablet_id)
+            {
+                partitioncoverage.tablets.push(CoverageTablet {
+                    tablet_id: tabletbasic.tablet_id.clone(),
+                    partition: tabletbasic.partition.clone(),
+                    on_disk_size: on_disk_sizes.get(&tabletbasic.tablet_id).cloned().unwrap_or_default(),
+                });
+            }
+        }
+        allpartitioncoverage.coverage.extend(tserver_coverage.into_values());
+
+        for partitioncoverage in allpartitioncoverage.coverage.iter_mut()
+        {
+            partitioncoverage.check();
+        }
+        allpartitioncoverage.coverage.sort_by(|a, b| (&a.table, &a.source).cmp(&(&b.table, &b.source)));
+        allpartitioncoverage
+    }
+    pub fn print(
+        &self,
+        table_name_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        let mut tables_with_issues = 0;
+        let mut tables = 0;
+        for partitioncoverage in self.coverage.iter().filter(|partitioncoverage| table_name_filter.is_match(&partitioncoverage.table))
+        {
+            tables += 1;
+            if partitioncoverage.issues.is_empty()
+            {
+                println!("{:50} {:8} {:>6} tablets {}", partitioncoverage.table, partitioncoverage.source, partitioncoverage.tablets.len(), "OK".green());
+            }
+            else
+            {
+                tables_with_issues += 1;
+                println!("{:50} {:8} {:>6} tablets {}", partitioncoverage.table, partitioncoverage.source, partitioncoverage.tablets.len(), "NOT COVERED".red());
+                for issue in &partitioncoverage.issues
+                {
+                    println!("  {}", issue.red());
+                }
+            }
+            if *details_enable || !partitioncoverage.issues.is_empty()
+            {
+                for line in partitioncoverage.key_space_map()
+                {
+                    println!("  {}", line);
+                }
+            }
+        }
+        println!("{} of {} tables have gaps or overlaps in their key space.", tables_with_issues, tables);
+    }
+}
+
+pub async fn locate_key(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let table_name = options.table.as_ref().unwrap();
+    let key = options.key.as_ref().unwrap();
+
+    let keylocation = match options.locate_key.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut alltables = AllTables::new();
+            alltables.table = snapshot::read_snapshot_json(snapshot_number, "tables")?;
+            let mut allentities = AllEntities::new();
+            allentities.entities = snapshot::read_snapshot_json(snapshot_number, "entities")?;
+            let mut alltabletservers = AllTabletServers::new();
+            alltabletservers.tabletservers = snapshot::read_snapshot_json(snapshot_number, "tablet_servers")?;
+            let mut allclocks = AllClocks::new();
+            allclocks.clocks = snapshot::read_snapshot_json(snapshot_number, "clocks")?;
+            let leader_hostname = AllIsLeader::return_leader_snapshot(snapshot_number)?;
+            KeyLocation::locate(&alltables, &allentities, &alltabletservers, &allclocks, &leader_hostname, table_name, key)?
+        },
+        None => {
+            // the table detail with the tablets is read with extra data.
+            let alltables = AllTables::read_tables(&hosts, &ports, parallel, &true).await;
+            let allentities = AllEntities::read_entities(&hosts, &ports, parallel).await;
+            let alltabletservers = AllTabletServers::read_tabletservers(&hosts, &ports, parallel).await;
+            let allclocks = AllClocks::read_clocks(&hosts, &ports, parallel).await?;
+            let leader_hostname = AllIsLeader::return_leader_http(&hosts, &ports, parallel).await;
+            KeyLocation::locate(&alltables, &allentities, &alltabletservers, &allclocks, &leader_hostname, table_name, key)?
+        },
+    };
+    keylocation.print();
+
+    Ok(())
+}
+
+pub async fn print_check_partitions(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+
+    let allpartitioncoverage = match options.check_partitions.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            // the master table detail is only in a snapshot taken with --extra-data.
+            let mut alltables = AllTables::new();
+            alltables.table = snapshot::read_snapshot_json(snapshot_number, "tables").unwrap_or_default();
+            let mut alltablets = AllTablets::new();
+            alltablets.tablet = snapshot::read_snapshot_json(snapshot_number, "tablets").unwrap_or_default();
+            if alltables.table.is_empty() && alltablets.tablet.is_empty()
+            {
+                bail!("No table or tablet data found in snapshot {}", snapshot_number);
+            }
+            let leader_hostname = AllIsLeader::return_leader_snapshot(snapshot_number)?;
+            AllPartitionCoverage::new_from_data(&alltables, &alltablets, &leader_hostname)
+        },
+        None => {
+            // the table detail with the tablets is read with extra data.
+            let alltables = AllTables::read_tables(&hosts, &ports, parallel, &true).await;
+            let alltablets = AllTablets::read_tablets(&hosts, &ports, parallel, &false).await;
+            let leader_hostname = AllIsLeader::return_leader_http(&hosts, &ports, parallel).await;
+            AllPartitionCoverage::new_from_data(&alltables, &alltablets, &leader_hostname)
+        },
+    };
+    allpartitioncoverage.print(&table_name_filter, &options.details_enable);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_hash_code() {
+        // SELECT yb_hash_code(1::int), yb_hash_code(2::int);
+        assert_eq!(hash_code(&encode_hash_key(&[("k", "int32")], &["1"]).unwrap()), 4624);
+        assert_eq!(hash_code(&encode_hash_key(&[("k", "int32")], &["2"]).unwrap()), 49348);
+        assert!(encode_hash_key(&[("k", "int32")], &["a"]).is_err());
+        assert!(encode_hash_key(&[("k", "int32"), ("v", "string")], &["1"]).is_err());
+        // a remainder of 17 to 23 bytes, and a full block of 24 bytes.
+        assert_eq!(hash_code(&encode_hash_key(&[("k", "string")], &["abcdefghijklmnopqrst"]).unwrap()), 40810);
+        assert_eq!(hash_code(&encode_hash_key(&[("k", "string")], &["abcdefghijklmnopqrstuvwxyz0123"]).unwrap()), 192);
+    }
+
+    #[test]
+    fn unit_parse_partition() {
+        let partition = Partition::parse("hash_split: [0x5555, 0xAAA9]").unwrap();
+        assert_eq!(partition, Partition::Hash { start: 0x5555, end: 0xAAA9 });
+        assert!(partition.contains_hash(0x5555));
+        assert!(partition.contains_hash(0xAAA9));
+        assert!(!partition.contains_hash(0xAAAA));
+
+        let partition = Partition::parse(r#"range: [DocKey([], [100, "a"]), <end>)"#).unwrap();
+        assert_eq!(partition, Partition::Range { start: Some(vec![RangeValue::Int(100), RangeValue::Text("a".to_string())]), end: None });
+        assert!(partition.contains_range_key(&[RangeValue::Int(100), RangeValue::Text("b".to_string())], &[]));
+        assert!(!partition.contains_range_key(&[RangeValue::Int(99)], &[]));
+
+        let partition = Partition::parse("range: [<start>, DocKey([], [100]))").unwrap();
+        assert!(partition.contains_range_key(&[RangeValue::Int(99)], &[]));
+        assert!(!partition.contains_range_key(&[RangeValue::Int(100)], &[]));
+        // a descending column: the tablet holds the values above 100.
+        assert!(partition.contains_range_key(&[RangeValue::Int(101)], &[true]));
+        assert!(!partition.contains_range_key(&[RangeValue::Int(99)], &[true]));
+        assert!(Partition::parse("something else").is_none());
+    }
+    #[test]
+    fn unit_partition_coverage() {
+        let coverage = |partitions: &[&str]| -> PartitionCoverage {
+            let mut partitioncoverage = PartitionCoverage {
+                tablets: partitions.iter().enumerate().map(|(nr, partition)| CoverageTablet { tablet_id: format!("tablet{}", nr), partition: partition.to_string(), ..Default::default() }).collect(),
+                ..Default::default()
+            };
+            partitioncoverage.check();
+            partitioncoverage
+        };
+
+        let partitioncoverage = coverage(&["hash_split: [0x5555, 0xAAA9]", "hash_split: [0x0000, 0x5554]", "hash_split: [0xAAAA, 0xFFFF]"]);
+        assert!(partitioncoverage.issues.is_empty());
+        assert_eq!(partitioncoverage.key_space_map()[0], format!("0x0000 |{}{}{}| 0xFFFF", "a".repeat(22), "b".repeat(21), "c".repeat(21)));
+
+        // a split parent left in place next to its children, and a missing last tablet.
+        let partitioncoverage = coverage(&["hash_split: [0x0000, 0x7FFF]", "hash_split: [0x0000, 0x3FFF]", "hash_split: [0x4000, 0x7FFF]", "hash_split: [0x8000, 0xBFFF]"]);
+        assert_eq!(partitioncoverage.issues, vec![
+            "overlap 0x0000-0x3FFF of tablet tablet1 with the previous tablets".to_string(),
+            "overlap 0x4000-0x7FFF of tablet tablet2 with the previous tablets".to_string(),
+            "gap 0xC000-0xFFFF at the end".to_string(),
+        ]);
+        assert!(partitioncoverage.key_space_map()[0].contains('#'));
+        assert!(partitioncoverage.key_space_map()[0].ends_with("....| 0xFFFF"));
+
+        let partitioncoverage = coverage(&["range: [DocKey([], [100]), <end>)", "range: [<start>, DocKey([], [100]))"]);
+        assert!(partitioncoverage.issues.is_empty());
+        assert_eq!(partitioncoverage.key_space_map()[0], "<start> |a| [100] |b| <end>");
+
+        let partitioncoverage = coverage(&["range: [DocKey([], [200]), <end>)", "range: [<start>, DocKey([], [100]))"]);
+        assert_eq!(partitioncoverage.issues, vec!["gap [100]-[200] before tablet tablet0".to_string()]);
+
+        // a descending range column: the tablets are split at 200 and 100, from the highest value to the lowest.
+        let columns = vec![
+            Some(Column { column: "k".to_string(), id: "0".to_string(), column_type: "int32 NOT NULL NOT A PARTITION KEY DESC".to_string() }),
+            Some(Column { column: "v".to_string(), id: "1".to_string(), column_type: "string NULLABLE NOT A PARTITION KEY".to_string() }),
+        ];
+        assert_eq!(range_columns_descending(&columns), vec![true, false]);
+        let partitions = ["range: [<start>, DocKey([], [200]))", "range: [DocKey([], [200]), DocKey([], [100]))", "range: [DocKey([], [100]), <end>)"];
+        assert_eq!(coverage(&partitions).issues.len(), 3);
+        let mut partitioncoverage = PartitionCoverage {
+            tablets: partitions.iter().enumerate().map(|(nr, partition)| CoverageTablet { tablet_id: format!("tablet{}", nr), partition: partition.to_string(), ..Default::default() }).collect(),
+            range_descending: range_columns_descending(&columns),
+            ..Default::default()
+        };
+        partitioncoverage.check();
+        assert!(partitioncoverage.issues.is_empty());
+    }
+}
@@ -0,0 +1,16 @@
+//! Module for the partitions (key space) of the tablets of a table.
+//!
+//! `--locate-key` prints the tablet and replicas that hold a key,
+//! for a hash partitioned table by hashing the key in the same way as `yb_hash_code()` (YSQL) and `partition_hash()` (YCQL).
+//! `--check-partitions` checks that the tablets of a table cover the whole key space, without gaps or overlaps.
+//! The partitions of the tables are only known in a snapshot taken with `--extra-data`.
+//!
+//! The partitions functionality is called from:
+//! - [crate] -> [locate_key] (locate a key in a snapshot, or live data)
+//! - [crate] -> [print_check_partitions] (check a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
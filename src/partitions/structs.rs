@@ -0,0 +1,74 @@
+//! The structs
+//!
+use crate::placement::Location;
+
+/// The partition of a tablet, parsed from the partition text of table_detail or tablet_detail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Partition {
+    /// The start and the end hash value, both included.
+    Hash {
+        start: u16,
+        end: u16,
+    },
+    /// The start key (included) and the end key (not included), None is the start or end of the key space.
+    Range {
+        start: Option<Vec<RangeValue>>,
+        end: Option<Vec<RangeValue>>,
+    },
+}
+/// A column value of a range key.
+/// Integers are ordered numerically, other values (and an integer with another value) are ordered as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeValue {
+    Int(i64),
+    Text(String),
+}
+/// A replica of the tablet of a located key.
+#[derive(Debug, Default)]
+pub struct KeyReplica {
+    /// LEADER, or the replica type.
+    pub role: String,
+    pub addr: String,
+    pub location: Location,
+}
+/// The tablet of a located key.
+#[derive(Debug, Default)]
+pub struct KeyLocation {
+    /// keyspace.table_name
+    pub table: String,
+    pub table_id: String,
+    pub key: String,
+    /// The hash value of the key, for a hash partitioned table.
+    pub hash: Option<u16>,
+    pub tablet_id: String,
+    pub partition: String,
+    pub replicas: Vec<KeyReplica>,
+    /// The raft config of table_detail, printed if the tablet is not found in the entities.
+    pub raftconfig: String,
+}
+/// A tablet of a table for the partition coverage check.
+#[derive(Debug, Default)]
+pub struct CoverageTablet {
+    pub tablet_id: String,
+    /// The partition text, as shown on the web page.
+    pub partition: String,
+    /// The total on disk size, only available from the tablet servers.
+    pub on_disk_size: String,
+}
+/// The tablets of a table from a source, and the gaps and overlaps in the key space of the table.
+#[derive(Debug, Default)]
+pub struct PartitionCoverage {
+    /// keyspace.table_name
+    pub table: String,
+    /// master (table_detail) or tserver (tablet_detail).
+    pub source: String,
+    pub tablets: Vec<CoverageTablet>,
+    /// Per range column, true if the column is sorted descending.
+    pub range_descending: Vec<bool>,
+    pub issues: Vec<String>,
+}
+/// Wrapper struct for holding the partition coverage of the tables.
+#[derive(Debug, Default)]
+pub struct AllPartitionCoverage {
+    pub coverage: Vec<PartitionCoverage>,
+}
@@ -0,0 +1,439 @@
+//! The impls and functions
+//!
+use std::{collections::{BTreeMap, BTreeSet}, sync::OnceLock};
+use regex::Regex;
+use colored::*;
+use log::*;
+use anyhow::Result;
+use crate::utility;
+use crate::snapshot;
+use crate::isleader::AllIsLeader;
+use crate::masters::Masters;
+use crate::tablet_servers::AllTabletServers;
+use crate::clocks::AllClocks;
+use crate::gflags::AllGFlags;
+use crate::cluster_config::{AllSysClusterConfigEntryPB, PlacementInfoPB};
+use crate::entities::{AllEntities, Replicas};
+use crate::table_detail::AllTables;
+use crate::placement::{AllPlacementViolations, Location, PlacementBlock, PlacementPolicy, PlacementViolation};
+use crate::Opts;
+
+impl Location {
+    /// True if the location is in the location of a placement block, an empty field matches every value.
+    fn is_in(
+        &self,
+        block_location: &Location,
+    ) -> bool
+    {
+        (block_location.cloud.is_empty() || block_location.cloud == self.cloud)
+            && (block_location.region.is_empty() || block_location.region == self.region)
+            && (block_location.zone.is_empty() || block_location.zone == self.zone)
+    }
+}
+
+impl Location {
+    /// The location of the tablet servers by server uuid, which is the server_uuid of a replica.
+    /// The uuid of a tablet server hostname_port is taken from the clocks.
+    pub fn tablet_server_locations(
+        alltabletservers: &AllTabletServers,
+        allclocks: &AllClocks,
+    ) -> BTreeMap<String, Location>
+    {
+        let locations: BTreeMap<&str, Location> = alltabletservers.tabletservers
+            .iter()
+            .map(|tabletserver| (
+                tabletserver.tablet_server_hostname_port.as_deref().unwrap_or_default(),
+                Location { cloud: tabletserver.cloud.clone(), region: tabletserver.region.clone(), zone: tabletserver.zone.clone() },
+            ))
+            .collect();
+        allclocks.tablet_server_uuids()
+            .into_iter()
+            .filter_map(|(uuid, hostname_port)| locations.get(hostname_port.as_str()).map(|location| (uuid, location.clone())))
+            .collect()
+    }
+    /// The location for an unknown replica addr.
+    pub fn unknown() -> Location
+    {
+        Location { cloud: "unknown".to_string(), region: "unknown".to_string(), zone: "unknown".to_string() }
+    }
+}
+
+impl PlacementPolicy {
+    pub fn from_placement_info(
+        placement_info: &PlacementInfoPB,
+        source: &str,
+    ) -> PlacementPolicy
+    {
+        PlacementPolicy {
+            source: source.to_string(),
+            num_replicas: placement_info.num_replicas.map(|num_replicas| num_replicas.max(0) as usize),
+            placement_blocks: placement_info.placement_blocks
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|placement_block| PlacementBlock {
+                    location: Location {
+                        cloud: placement_block.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_cloud.clone()).unwrap_or_default(),
+                        region: placement_block.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_region.clone()).unwrap_or_default(),
+                        zone: placement_block.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_zone.clone()).unwrap_or_default(),
+                    },
+                    min_num_replicas: placement_block.min_num_replicas.unwrap_or_default().max(0) as usize,
+                })
+                .collect(),
+        }
+    }
+    /// Parse the live replicas of the replication info of a table on the `/table?id=` page, which is in protobuf text format:
+    /// `live_replicas { num_replicas: 3 placement_blocks { cloud_info { placement_cloud: "c" placement_region: "r" placement_zone: "z" } min_num_replicas: 1 } }`
+    pub fn parse_replication_info(
+        replication_info: &str,
+        source: &str,
+    ) -> Option<PlacementPolicy>
+    {
+        static LIVE_REPLICAS: OnceLock<Regex> = OnceLock::new();
+        static PLACEMENT_BLOCK: OnceLock<Regex> = OnceLock::new();
+        static CLOUD_INFO_FIELD: OnceLock<Regex> = OnceLock::new();
+        let live_replicas = LIVE_REPLICAS.get_or_init(|| Regex::new(r"live_replicas \{\s*num_replicas: (\d+)").unwrap());
+        let placement_block = PLACEMENT_BLOCK.get_or_init(|| Regex::new(r"placement_blocks \{\s*cloud_info \{([^}]*)\}\s*min_num_replicas: (\d+)").unwrap());
+        let cloud_info_field_regex = CLOUD_INFO_FIELD.get_or_init(|| Regex::new(r#"(\w+): "([^"]*)""#).unwrap());
+        let cloud_info_field = |cloud_info: &str, field: &str| -> String {
+            cloud_info_field_regex
+                .captures_iter(cloud_info)
+                .find(|captures| &captures[1] == field)
+                .map(|captures| captures[2].to_string())
+                .unwrap_or_default()
+        };
+
+        let captures = live_replicas.captures(replication_info)?;
+        // the read replicas follow the live replicas, and have placement blocks too.
+        let live_replicas_text = replication_info.split("read_replicas").next().unwrap_or_default();
+        Some(PlacementPolicy {
+            source: source.to_string(),
+            num_replicas: Some(captures[1].parse().ok()?),
+            placement_blocks: placement_block
+                .captures_iter(live_replicas_text)
+                .map(|captures| PlacementBlock {
+                    location: Location {
+                        cloud: cloud_info_field(&captures[1], "placement_cloud"),
+                        region: cloud_info_field(&captures[1], "placement_region"),
+                        zone: cloud_info_field(&captures[1], "placement_zone"),
+                    },
+                    min_num_replicas: captures[2].parse().unwrap_or_default(),
+                })
+                .collect(),
+        })
+    }
+    /// Check the locations of the replicas of a tablet against the policy.
+    /// The number of zones is the number of different locations of the tablet servers.
+    pub fn check(
+        &self,
+        replicas: &[(&String, Location)],
+        number_of_zones: usize,
+    ) -> Vec<String>
+    {
+        let mut messages: Vec<String> = Vec::new();
+
+        if let Some(num_replicas) = self.num_replicas.filter(|num_replicas| *num_replicas != replicas.len())
+        {
+            messages.push(format!("{} replicas, the policy has {} replicas", replicas.len(), num_replicas));
+        }
+        for placement_block in &self.placement_blocks
+        {
+            let replicas_in_block = replicas.iter().filter(|(_, location)| location.is_in(&placement_block.location)).count();
+            if replicas_in_block < placement_block.min_num_replicas
+            {
+                messages.push(format!("{} replicas in {}, the policy requires at least {}", replicas_in_block, placement_block.location, placement_block.min_num_replicas));
+            }
+        }
+        if !self.placement_blocks.is_empty()
+        {
+            for (addr, location) in replicas.iter().filter(|(_, location)| !self.placement_blocks.iter().any(|placement_block| location.is_in(&placement_block.location)))
+            {
+                messages.push(format!("replica {} in {} is outside the placement blocks", addr, location));
+            }
+        }
+        else if number_of_zones >= replicas.len()
+        {
+            let mut replicas_per_location: BTreeMap<&Location, usize> = BTreeMap::new();
+            for (_, location) in replicas
+            {
+                *replicas_per_location.entry(location).or_default() += 1;
+            }
+            for (location, replicas_in_location) in replicas_per_location.iter().filter(|(_, replicas_in_location)| **replicas_in_location > 1)
+            {
+                messages.push(format!("{} replicas in {}, while the tablet servers are in {} zones", replicas_in_location, location, number_of_zones));
+            }
+        }
+        messages
+    }
+}
+
+impl AllPlacementViolations {
+    pub fn new() -> Self { Default::default() }
+    /// Check the replica placement of the running tablets of the user tables of the master leader.
+    /// The tablet server locations are by server uuid, see [Location::tablet_server_locations].
+    pub fn check_placement(
+        allentities: &AllEntities,
+        masters: &Masters,
+        tablet_server_locations: &BTreeMap<String, Location>,
+        allsysclusterconfigentrypb: &AllSysClusterConfigEntryPB,
+        allgflags: &AllGFlags,
+        alltables: &AllTables,
+        master_leader: &str,
+    ) -> AllPlacementViolations
+    {
+        let mut allplacementviolations = AllPlacementViolations::new();
+
+        // masters are matched on the rpc address, which is the replica addr, tablet servers on the server uuid.
+        let mut master_locations: BTreeMap<String, Location> = BTreeMap::new();
+        for registration in masters.masters.iter().filter_map(|master| master.registration.as_ref())
+        {
+            let location = Location {
+                cloud: registration.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_cloud.clone()).unwrap_or_default(),
+                region: registration.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_region.clone()).unwrap_or_default(),
+                zone: registration.cloud_info.as_ref().and_then(|cloud_info| cloud_info.placement_zone.clone()).unwrap_or_default(),
+            };
+            for rpc_address in registration.private_rpc_addresses.as_deref().unwrap_or_default()
+            {
+                master_locations.insert(format!("{}:{}", rpc_address.host, rpc_address.port), location.clone());
+            }
+        }
+        let number_of_zones = tablet_server_locations.values().collect::<BTreeSet<_>>().len();
+        // server_uuid, addr
+        let mut unmatched_replicas: BTreeMap<String, String> = BTreeMap::new();
+        let mut location = |replica: &Replicas| -> Location {
+            master_locations.get(&replica.addr)
+                .or_else(|| tablet_server_locations.get(&replica.server_uuid))
+                .cloned()
+                .unwrap_or_else(|| {
+                    unmatched_replicas.insert(replica.server_uuid.clone(), replica.addr.clone());
+                    Location::unknown()
+                })
+        };
+
+        let replication_info = allsysclusterconfigentrypb.sysclusterconfigentrypb
+            .iter()
+            .find_map(|cluster_config| cluster_config.replication_info.as_ref());
+        // without live replicas (or their num_replicas) in the cluster config, the replication_factor gflag of the master leader is used,
+        // and without placement blocks, the replicas must be in different zones.
+        let replication_factor = allgflags.gflags
+            .iter()
+            .find(|gflag| gflag.hostname_port.as_deref() == Some(master_leader) && gflag.name == "replication_factor")
+            .and_then(|gflag| gflag.value.parse::<usize>().ok());
+        let mut cluster_policy = replication_info
+            .and_then(|replication_info| replication_info.live_replicas.as_ref())
+            .map(|live_replicas| PlacementPolicy::from_placement_info(live_replicas, "cluster"))
+            .unwrap_or_else(|| PlacementPolicy { source: "cluster".to_string(), ..Default::default() });
+        cluster_policy.num_replicas = cluster_policy.num_replicas.or(replication_factor);
+        let read_replicas_policy = replication_info
+            .and_then(|replication_info| replication_info.read_replicas.as_ref())
+            .filter(|read_replicas| !read_replicas.is_empty())
+            .map(|read_replicas| {
+                let mut read_replicas_policy = PlacementPolicy { source: "read replicas".to_string(), num_replicas: Some(0), ..Default::default() };
+                for read_replica in read_replicas
+                {
+                    let policy = PlacementPolicy::from_placement_info(read_replica, "read replicas");
+                    read_replicas_policy.num_replicas = read_replicas_policy.num_replicas.zip(policy.num_replicas).map(|(num_replicas, policy_num_replicas)| num_replicas + policy_num_replicas);
+                    read_replicas_policy.placement_blocks.extend(policy.placement_blocks);
+                }
+                read_replicas_policy
+            });
+        // table_id, policy of the tablespace of the table.
+        let tablespace_policies: BTreeMap<&String, PlacementPolicy> = alltables.table
+            .iter()
+            .flat_map(|table| table.tabledetail.iter().flatten())
+            .filter_map(|table_detail| PlacementPolicy::parse_replication_info(&table_detail.replication_info, "tablespace").map(|policy| (&table_detail.uuid, policy)))
+            .collect();
+
+        for entities in allentities.entities
+            .iter()
+            .filter(|entities| entities.hostname_port.as_deref() == Some(master_leader))
+        {
+            let user_tables: BTreeMap<&String, String> = entities.tables
+                .iter()
+                .filter(|table| entities.is_user_table(table))
+                .map(|table| {
+                    let keyspace_name = entities.keyspaces.iter().find(|keyspace| keyspace.keyspace_id == table.keyspace_id).map(|keyspace| keyspace.keyspace_name.as_str()).unwrap_or_default();
+                    (&table.table_id, format!("{}.{}", keyspace_name, table.table_name))
+                })
+                .collect();
+            for tablet in entities.tablets.iter().filter(|tablet| tablet.state == "RUNNING")
+            {
+                let Some(table_name) = user_tables.get(&tablet.table_id) else { continue };
+                allplacementviolations.tablets_checked += 1;
+                let replicas = tablet.replicas.as_deref().unwrap_or_default();
+                let (live_replicas, read_replicas): (Vec<_>, Vec<_>) = replicas
+                    .iter()
+                    .map(|replica| (replica, (&replica.addr, location(replica))))
+                    .partition(|(replica, _)| matches!(replica.replica_type.as_str(), "VOTER" | "PRE_VOTER"));
+                let live_replicas: Vec<_> = live_replicas.into_iter().map(|(_, addr_location)| addr_location).collect();
+                let read_replicas: Vec<_> = read_replicas.into_iter().map(|(_, addr_location)| addr_location).collect();
+
+                let live_policy = tablespace_policies.get(&tablet.table_id).unwrap_or(&cluster_policy);
+                let mut policy_checks = vec![(live_policy, live_replicas)];
+                if let Some(read_replicas_policy) = &read_replicas_policy
+                {
+                    policy_checks.push((read_replicas_policy, read_replicas));
+                }
+                for (policy, replicas) in policy_checks
+                {
+                    for message in policy.check(&replicas, number_of_zones)
+                    {
+                        allplacementviolations.violations.push(PlacementViolation {
+                            table: table_name.clone(),
+                            tablet_id: tablet.tablet_id.clone(),
+                            policy: policy.source.clone(),
+                            message,
+                        });
+                    }
+                }
+            }
+        }
+        for (server_uuid, addr) in unmatched_replicas
+        {
+            warn!("Replica server {} ({}) not found in the masters and tablet servers, its location is unknown", server_uuid, addr);
+        }
+        allplacementviolations
+    }
+    pub fn print(
+        &self,
+        table_name_filter: &Regex,
+    )
+    {
+        let violations: Vec<&PlacementViolation> = self.violations
+            .iter()
+            .filter(|violation| table_name_filter.is_match(&violation.table))
+            .collect();
+        let violating_tablets = violations.iter().map(|violation| &violation.tablet_id).collect::<BTreeSet<_>>().len();
+
+        if violations.is_empty()
+        {
+            println!("{}", format!("All {} tablets comply with the placement policy.", self.tablets_checked).green());
+            return;
+        }
+        println!("{:40} {:32} {:15} violation", "table", "tablet", "policy");
+        for violation in violations
+        {
+            println!("{:40} {:32} {:15} {}", violation.table, violation.tablet_id, violation.policy, violation.message.red());
+        }
+        println!("{} of {} tablets do not comply with the placement policy.", violating_tablets, self.tablets_checked);
+    }
+}
+
+pub async fn print_check_placement(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+
+    let allplacementviolations = match options.check_placement.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allentities = AllEntities::new();
+            allentities.entities = snapshot::read_snapshot_json(snapshot_number, "entities")?;
+            let mut masters = Masters::new();
+            masters.masters = snapshot::read_snapshot_json(snapshot_number, "masters")?;
+            let mut alltabletservers = AllTabletServers::new();
+            alltabletservers.tabletservers = snapshot::read_snapshot_json(snapshot_number, "tablet_servers")?;
+            let mut allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::new();
+            allsysclusterconfigentrypb.sysclusterconfigentrypb = snapshot::read_snapshot_json(snapshot_number, "cluster-config")?;
+            // the table details are only in a snapshot taken with --extra-data.
+            let mut alltables = AllTables::new();
+            alltables.table = snapshot::read_snapshot_json(snapshot_number, "tables").unwrap_or_default();
+            let mut allclocks = AllClocks::new();
+            allclocks.clocks = snapshot::read_snapshot_json(snapshot_number, "clocks")?;
+            let mut allgflags = AllGFlags::new();
+            allgflags.gflags = snapshot::read_snapshot_json(snapshot_number, "gflags")?;
+            let leader_hostname = AllIsLeader::return_leader_snapshot(snapshot_number)?;
+            let tablet_server_locations = Location::tablet_server_locations(&alltabletservers, &allclocks);
+            AllPlacementViolations::check_placement(&allentities, &masters, &tablet_server_locations, &allsysclusterconfigentrypb, &allgflags, &alltables, &leader_hostname)
+        },
+        None => {
+            let allentities = AllEntities::read_entities(&hosts, &ports, parallel).await;
+            let masters = Masters::read_masters(&hosts, &ports, parallel).await;
+            let alltabletservers = AllTabletServers::read_tabletservers(&hosts, &ports, parallel).await;
+            let allclocks = AllClocks::read_clocks(&hosts, &ports, parallel).await?;
+            let allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::read_cluster_config(&hosts, &ports, parallel).await;
+            let allgflags = AllGFlags::read_gflags(&hosts, &ports, parallel).await;
+            let alltables = AllTables::read_tables(&hosts, &ports, parallel, &options.extra_data).await;
+            let leader_hostname = AllIsLeader::return_leader_http(&hosts, &ports, parallel).await;
+            let tablet_server_locations = Location::tablet_server_locations(&alltabletservers, &allclocks);
+            AllPlacementViolations::check_placement(&allentities, &masters, &tablet_server_locations, &allsysclusterconfigentrypb, &allgflags, &alltables, &leader_hostname)
+        },
+    };
+    allplacementviolations.print(&table_name_filter);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Entities;
+    use crate::gflags::GFlag;
+
+    #[test]
+    fn unit_placement_policy_check() {
+        let location = |zone: &str| Location { cloud: "aws".to_string(), region: "us-east-1".to_string(), zone: zone.to_string() };
+        let addr = "yb-1.local:9100".to_string();
+
+        let policy = PlacementPolicy::parse_replication_info(r#"live_replicas { num_replicas: 3 placement_blocks { cloud_info { placement_cloud: "aws" placement_region: "us-east-1" placement_zone: "us-east-1a" } min_num_replicas: 1 } placement_blocks { cloud_info { placement_cloud: "aws" placement_region: "us-east-1" placement_zone: "us-east-1b" } min_num_replicas: 1 } placement_blocks { cloud_info { placement_cloud: "aws" placement_region: "us-east-1" placement_zone: "us-east-1c" } min_num_replicas: 1 } }"#, "tablespace").unwrap();
+        assert_eq!(policy.num_replicas, Some(3));
+        assert_eq!(policy.placement_blocks.len(), 3);
+        assert_eq!(policy.placement_blocks[2].location, location("us-east-1c"));
+        assert!(PlacementPolicy::parse_replication_info("", "tablespace").is_none());
+
+        // compliant
+        assert!(policy.check(&[(&addr, location("us-east-1a")), (&addr, location("us-east-1b")), (&addr, location("us-east-1c"))], 3).is_empty());
+        // two replicas in one zone, and a missing zone
+        assert_eq!(policy.check(&[(&addr, location("us-east-1a")), (&addr, location("us-east-1a")), (&addr, location("us-east-1c"))], 3),
+                   vec!["0 replicas in aws.us-east-1.us-east-1b, the policy requires at least 1".to_string()]);
+        // a replica outside of the placement blocks
+        assert_eq!(policy.check(&[(&addr, location("us-east-1a")), (&addr, location("us-east-1b")), (&addr, location("us-east-1d"))], 4).len(), 2);
+
+        // without placement blocks, two replicas in one zone while there are enough zones
+        let cluster_policy = PlacementPolicy { source: "cluster".to_string(), num_replicas: Some(3), ..Default::default() };
+        assert_eq!(cluster_policy.check(&[(&addr, location("us-east-1a")), (&addr, location("us-east-1a")), (&addr, location("us-east-1c"))], 3),
+                   vec!["2 replicas in aws.us-east-1.us-east-1a, while the tablet servers are in 3 zones".to_string()]);
+        assert!(cluster_policy.check(&[(&addr, location("us-east-1a")), (&addr, location("us-east-1a")), (&addr, location("us-east-1a"))], 1).is_empty());
+    }
+
+    #[test]
+    fn unit_check_placement_default_policy() {
+        let mut entities: Entities = serde_json::from_str(r#"
+{
+  "keyspaces": [ { "keyspace_id": "3e7e4b6a0e9d4c5e8f2a1b0c9d8e7f6a", "keyspace_name": "test", "keyspace_type": "ycql" } ],
+  "tables": [ { "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "keyspace_id": "3e7e4b6a0e9d4c5e8f2a1b0c9d8e7f6a", "table_name": "t", "state": "RUNNING" } ],
+  "tablets": [
+    { "table_id": "a1da3fb4b3be4bd4860253e723d11b97", "tablet_id": "235b5b031f094ec3bf6be2a023abebba", "state": "RUNNING",
+      "replicas": [ { "type": "VOTER", "server_uuid": "a3f5a16532bb4ed4a061e794831168f8", "addr": "yb-1.local:9100" },
+                    { "type": "VOTER", "server_uuid": "e7a4a66ae7f94eb6a75b0ce3a90ab5ba", "addr": "yb-2.local:9100" } ],
+      "leader": "a3f5a16532bb4ed4a061e794831168f8" }
+  ]
+}
+        "#).unwrap();
+        entities.hostname_port = Some("yb-1.local:7000".to_string());
+        let mut allentities = AllEntities::new();
+        allentities.entities.push(entities);
+        let location = |zone: &str| Location { cloud: "local".to_string(), region: "local".to_string(), zone: zone.to_string() };
+        let tablet_server_locations: BTreeMap<String, Location> = BTreeMap::from([
+            ("a3f5a16532bb4ed4a061e794831168f8".to_string(), location("local1")),
+            ("e7a4a66ae7f94eb6a75b0ce3a90ab5ba".to_string(), location("local1")),
+            ("5c6a7f4d2e1b4c3a9d8e7f6a5b4c3d2e".to_string(), location("local2")),
+        ]);
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags.push(GFlag { hostname_port: Some("yb-1.local:7000".to_string()), name: "replication_factor".to_string(), value: "3".to_string(), ..Default::default() });
+
+        // without a cluster config, the replication factor of the master leader is the number of replicas, and the replicas must be in different zones.
+        let allplacementviolations = AllPlacementViolations::check_placement(&allentities, &Masters::new(), &tablet_server_locations, &AllSysClusterConfigEntryPB::new(), &allgflags, &AllTables::new(), "yb-1.local:7000");
+        assert_eq!(allplacementviolations.tablets_checked, 1);
+        assert_eq!(allplacementviolations.violations.iter().map(|violation| violation.message.as_str()).collect::<Vec<_>>(),
+                   vec!["2 replicas, the policy has 3 replicas", "2 replicas in local.local.local1, while the tablet servers are in 2 zones"]);
+
+        // without a replication factor, the number of replicas is not checked.
+        let allplacementviolations = AllPlacementViolations::check_placement(&allentities, &Masters::new(), &tablet_server_locations, &AllSysClusterConfigEntryPB::new(), &AllGFlags::new(), &AllTables::new(), "yb-1.local:7000");
+        assert_eq!(allplacementviolations.violations.len(), 1);
+    }
+}
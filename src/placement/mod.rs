@@ -0,0 +1,14 @@
+//! Module for checking the replica placement of the tablets against the placement policy (`--check-placement`).
+//!
+//! The voters of a tablet are checked against the live replicas policy (of the tablespace if set, otherwise of the cluster),
+//! the observers against the read replicas policies.
+//! A table with a tablespace placement is only known in a snapshot taken with `--extra-data`.
+//!
+//! The placement functionality is called from:
+//! - [crate] -> [print_check_placement] (check a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
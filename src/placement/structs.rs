@@ -0,0 +1,47 @@
+//! The structs
+//!
+use std::fmt;
+
+/// The location of a server.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub cloud: String,
+    pub region: String,
+    pub zone: String,
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.cloud, self.region, self.zone)
+    }
+}
+/// A placement block: a location with the minimal number of replicas.
+/// An empty cloud, region or zone matches every cloud, region or zone.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlacementBlock {
+    pub location: Location,
+    pub min_num_replicas: usize,
+}
+/// A placement policy of the cluster, a read replica cluster or a tablespace.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlacementPolicy {
+    /// The origin of the policy: cluster, read replicas or tablespace.
+    pub source: String,
+    /// The number of replicas, unknown if the cluster config has no live replicas, or the num_replicas is not set.
+    pub num_replicas: Option<usize>,
+    pub placement_blocks: Vec<PlacementBlock>,
+}
+/// A tablet whose replica placement does not comply with a placement policy.
+#[derive(Debug, Default)]
+pub struct PlacementViolation {
+    /// keyspace_name.table_name
+    pub table: String,
+    pub tablet_id: String,
+    pub policy: String,
+    pub message: String,
+}
+/// Wrapper struct for holding the violations.
+#[derive(Debug, Default)]
+pub struct AllPlacementViolations {
+    pub violations: Vec<PlacementViolation>,
+    pub tablets_checked: usize,
+}
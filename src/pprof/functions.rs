@@ -1,13 +1,129 @@
 //! The impls and functions.
 //!
-use std::{fs, io::Write, sync::mpsc::channel, time::Instant, env};
+use chrono::Local;
+use std::{collections::{BTreeMap, BTreeSet, HashMap}, fs, io::Write, sync::mpsc::channel, time::{Duration, Instant}, env};
+use regex::Regex;
 use log::*;
 use anyhow::{Result, Context};
 use crate::utility;
+use crate::snapshot;
+use crate::pprof::{PprofGrowth, PprofGrowthSample, AllPprofGrowth, PprofGrowthDiff, PprofProfile, PprofProfileSample, AllPprofProfile};
+use crate::Opts;
 
-pub struct Pprof;
+/// The number of allocation sites per host that is printed without details_enable.
+const TOP_ALLOCATION_SITES: usize = 20;
+/// The number of functions per host that is printed without details_enable.
+const TOP_FUNCTIONS: usize = 20;
+/// The time allowed for a CPU profile request on top of the profile duration.
+const PROFILE_TIMEOUT_MARGIN: Duration = Duration::from_secs(30);
 
-impl Pprof {
+/// Parse a hexadecimal address such as `0x7f3a4c1f2e0d` or `00007f3a4c1f2e0d` into a number.
+fn parse_address(address: &str) -> Option<u64>
+{
+    u64::from_str_radix(address.trim().trim_start_matches("0x"), 16).ok()
+}
+
+/// The address to resolve for the frame at position in a stack, innermost frame first.
+///
+/// Except for the innermost frame, the addresses are return addresses,
+/// which point to the instruction after the call, and therefore can belong to the next function.
+/// For these frames, the address minus one is resolved.
+fn lookup_address(
+    position: usize,
+    address: &str,
+) -> Option<u64>
+{
+    parse_address(address).map(|address| if position == 0 { address } else { address.saturating_sub(1) })
+}
+
+/// The addresses to resolve for the stacks, sorted and deduplicated, formatted for `/pprof/symbol`.
+fn lookup_addresses<'a>(stacks: impl Iterator<Item = &'a Vec<String>>) -> Vec<String>
+{
+    stacks
+        .flat_map(|addresses| addresses.iter().enumerate().filter_map(|(position, address)| lookup_address(position, address)))
+        .collect::<BTreeSet<u64>>()
+        .iter()
+        .map(|address| format!("{:#x}", address))
+        .collect()
+}
+
+/// The function names of a stack, an address that is not resolved is kept.
+fn stack_symbols(
+    addresses: &[String],
+    symbols: &HashMap<u64, String>,
+) -> Vec<String>
+{
+    addresses
+        .iter()
+        .enumerate()
+        .map(|(position, address)| lookup_address(position, address)
+            .and_then(|address| symbols.get(&address).cloned())
+            .unwrap_or_else(|| address.to_string()))
+        .collect()
+}
+
+/// Resolve addresses into function names using the `/pprof/symbol` endpoint of the server.
+///
+/// The addresses are posted as `0x1234+0x5678`, the endpoint returns a line per address: `0x1234<tab>function`.
+/// The function returns a map with the parsed address and the function name.
+pub fn symbolize(
+    host: &str,
+    port: &str,
+    addresses: &[String],
+) -> HashMap<u64, String>
+{
+    let mut symbols = HashMap::new();
+    if addresses.is_empty()
+    {
+        return symbols;
+    }
+    let data_from_http = utility::http_post(host, port, "pprof/symbol", addresses.join("+"));
+    for line in data_from_http.lines()
+    {
+        if let Some((address, symbol)) = line.split_once('\t')
+        {
+            if let Some(address) = parse_address(address)
+            {
+                symbols.insert(address, symbol.trim().to_string());
+            }
+        }
+    }
+    symbols
+}
+
+impl PprofGrowthSample {
+    /// The allocation site is the first function in the stack that is not part of the memory allocator.
+    /// If the stack is not symbolized, the first address is used.
+    pub fn allocation_site(&self) -> String
+    {
+        let allocator = Regex::new(r"^(tcmalloc::|TCMalloc|MallocExtension|MallocHook|operator new|operator delete|malloc|calloc|realloc|memalign|posix_memalign|valloc|pvalloc|__libc_|\(anonymous namespace\)::do_)").unwrap();
+        self.symbols
+            .iter()
+            .find(|symbol| !allocator.is_match(symbol))
+            .or_else(|| self.symbols.first())
+            .or_else(|| self.addresses.first())
+            .cloned()
+            .unwrap_or_else(|| "[unknown]".to_string())
+    }
+}
+
+impl PprofGrowth {
+    /// The in use bytes and objects per allocation site.
+    pub fn allocation_sites(&self) -> BTreeMap<String, (u64, u64)>
+    {
+        let mut allocation_sites: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+        for sample in &self.samples
+        {
+            let (bytes, objects) = allocation_sites.entry(sample.allocation_site()).or_default();
+            *bytes += sample.in_use_bytes;
+            *objects += sample.in_use_objects;
+        }
+        allocation_sites
+    }
+}
+
+impl AllPprofGrowth {
+    pub fn new() -> Self { Default::default() }
     pub async fn perform_snapshot(
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
@@ -18,18 +134,22 @@ impl Pprof {
         info!("begin snapshot");
         let timer = Instant::now();
 
-        Pprof::read_and_write_pprof(hosts, ports, snapshot_number, parallel).await?;
+        let allpprofgrowth = AllPprofGrowth::read_and_write_pprof(hosts, ports, snapshot_number, parallel).await?;
+        snapshot::save_snapshot_json(snapshot_number, "pprof_growth", allpprofgrowth.pprofgrowth)?;
 
         info!("end snapshot: {:?}", timer.elapsed());
 
         Ok(())
     }
+    /// Read `/pprof/growth`, and write the raw result as `pprof_growth_<hostname:port>.txt` into the snapshot if snapshot_number is not negative.
+    /// The heap growth profile is parsed, symbolized via `/pprof/symbol` of the same server, and returned.
+    /// The symbols are resolved at snapshot time, because the addresses are only valid for the process that produced the profile.
     pub async fn read_and_write_pprof(
         hosts: &Vec<&str>,
         ports: &Vec<&str>,
         snapshot_number: i32,
         parallel: usize,
-    ) -> Result<()>
+    ) -> Result<AllPprofGrowth>
     {
         info!("begin parallel http read");
         let timer = Instant::now();
@@ -42,8 +162,21 @@ impl Pprof {
                 for port in ports {
                     let tx = tx.clone();
                     s.spawn(move |_| {
-                        let pprof_data = Pprof::read_http(host, port);
-                        tx.send((format!("{}:{}", host, port), pprof_data)).expect("error sending data via tx (pprof)");
+                        let detail_snapshot_time = Local::now();
+                        let pprof_data = AllPprofGrowth::read_http(host, port);
+                        let pprofgrowth = if pprof_data.starts_with("heap profile")
+                        {
+                            let mut pprofgrowth = AllPprofGrowth::parse_pprof_growth(&pprof_data);
+                            pprofgrowth.hostname_port = format!("{}:{}", host, port);
+                            pprofgrowth.timestamp = detail_snapshot_time;
+                            pprofgrowth.symbolize(host, port);
+                            Some(pprofgrowth)
+                        }
+                        else
+                        {
+                            None
+                        };
+                        tx.send((format!("{}:{}", host, port), pprof_data, pprofgrowth)).expect("error sending data via tx (pprof)");
                     });
                 }
             }
@@ -51,7 +184,9 @@ impl Pprof {
 
         info!("end parallel http read {:?}", timer.elapsed());
 
-        for (hostname_port, pprof_data) in rx {
+        let mut allpprofgrowth = AllPprofGrowth::new();
+
+        for (hostname_port, pprof_data, pprofgrowth) in rx {
             if pprof_data.starts_with("heap profile")
                 && snapshot_number >= 0
             {
@@ -68,8 +203,20 @@ impl Pprof {
                 file.write_all(pprof_data.as_bytes())
                     .with_context(|| format!("Error writing file: {}", pprof_file.display()))?;
             };
+            if let Some(pprofgrowth) = pprofgrowth
+            {
+                allpprofgrowth.pprofgrowth.push(pprofgrowth);
+            }
         }
-        Ok(())
+        Ok(allpprofgrowth)
+    }
+    pub async fn read_pprof_growth(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> Result<AllPprofGrowth>
+    {
+        AllPprofGrowth::read_and_write_pprof(hosts, ports, -1, parallel).await
     }
     fn read_http(
         host: &str,
@@ -78,26 +225,607 @@ impl Pprof {
     {
         utility::http_get(host, port, "pprof/growth")
     }
+    fn parse_pprof_growth(
+        pprof_data: &str,
+    ) -> PprofGrowth
+    {
+        let mut pprofgrowth = PprofGrowth::default();
+        let regular_expression = Regex::new(r"^\s*(\d+):\s+(\d+)\s+\[\s*(\d+):\s+(\d+)\]\s+@\s+(.*)$").unwrap();
+        // the first line is the header with the totals, and starts with "heap profile:", which does not match.
+        for line in pprof_data.lines()
+        {
+            // the stacks are followed by the mapped libraries.
+            if line.starts_with("MAPPED_LIBRARIES")
+            {
+                break;
+            }
+            if let Some(captures) = regular_expression.captures(line)
+            {
+                pprofgrowth.samples.push(PprofGrowthSample {
+                    in_use_objects: captures[1].parse::<u64>().unwrap_or_default(),
+                    in_use_bytes: captures[2].parse::<u64>().unwrap_or_default(),
+                    alloc_objects: captures[3].parse::<u64>().unwrap_or_default(),
+                    alloc_bytes: captures[4].parse::<u64>().unwrap_or_default(),
+                    addresses: captures[5].split_whitespace().map(|address| address.to_string()).collect(),
+                    ..Default::default()
+                });
+            }
+        }
+        pprofgrowth
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        for pprofgrowth in self.pprofgrowth
+            .iter()
+            .filter(|pprofgrowth| hostname_filter.is_match(&pprofgrowth.hostname_port))
+        {
+            let mut allocation_sites: Vec<_> = pprofgrowth.allocation_sites().into_iter().collect();
+            allocation_sites.sort_by(|(_, (a_bytes, _)), (_, (b_bytes, _))| b_bytes.cmp(a_bytes));
+            let total_bytes: u64 = allocation_sites.iter().map(|(_, (bytes, _))| bytes).sum();
+
+            println!("{}", "-".repeat(120));
+            println!("Host: {}, Snapshot time: {}, heap growth: {}", pprofgrowth.hostname_port, pprofgrowth.timestamp, utility::bytes_to_human_readable(total_bytes));
+            println!("{}", "-".repeat(120));
+            println!("{:>12} {:>7} {:>10} allocation site", "bytes", "%", "objects");
+            for (allocation_site, (bytes, objects)) in allocation_sites
+                .iter()
+                .take(if *details_enable { usize::MAX } else { TOP_ALLOCATION_SITES })
+            {
+                println!("{:>12} {:>7.1} {:>10} {}",
+                         utility::bytes_to_human_readable(*bytes),
+                         if total_bytes > 0 { *bytes as f64 / total_bytes as f64 * 100_f64 } else { 0_f64 },
+                         objects,
+                         allocation_site,
+                );
+            }
+        }
+    }
+}
+
+impl PprofGrowth {
+    /// Resolve the addresses of all samples via the `/pprof/symbol` endpoint of the server.
+    /// Except for the innermost frame, the return address minus one is resolved (see [lookup_address]).
+    fn symbolize(
+        &mut self,
+        host: &str,
+        port: &str,
+    )
+    {
+        let addresses = lookup_addresses(self.samples.iter().map(|sample| &sample.addresses));
+        let symbols = symbolize(host, port, &addresses);
+        if symbols.is_empty()
+        {
+            debug!("{}:{} no symbols returned from /pprof/symbol", host, port);
+            return;
+        }
+        for sample in self.samples.iter_mut()
+        {
+            sample.symbols = stack_symbols(&sample.addresses, &symbols);
+        }
+    }
+}
+
+impl PprofGrowthDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<PprofGrowthDiff>
+    {
+        let mut pprofgrowthdiff = PprofGrowthDiff::new();
+
+        let mut allpprofgrowth = AllPprofGrowth::new();
+        allpprofgrowth.pprofgrowth = snapshot::read_snapshot_json(begin_snapshot, "pprof_growth")?;
+        pprofgrowthdiff.first_snapshot(allpprofgrowth);
+
+        let mut allpprofgrowth = AllPprofGrowth::new();
+        allpprofgrowth.pprofgrowth = snapshot::read_snapshot_json(end_snapshot, "pprof_growth")?;
+        pprofgrowthdiff.second_snapshot(allpprofgrowth);
+
+        Ok(pprofgrowthdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allpprofgrowth: AllPprofGrowth,
+    )
+    {
+        for pprofgrowth in allpprofgrowth.pprofgrowth
+        {
+            for (allocation_site, (bytes, objects)) in pprofgrowth.allocation_sites()
+            {
+                let row = self.btreepprofgrowthdiff
+                    .entry((pprofgrowth.hostname_port.clone(), allocation_site))
+                    .or_default();
+                row.first_in_use_bytes = bytes;
+                row.first_in_use_objects = objects;
+            }
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allpprofgrowth: AllPprofGrowth,
+    )
+    {
+        for pprofgrowth in allpprofgrowth.pprofgrowth
+        {
+            for (allocation_site, (bytes, objects)) in pprofgrowth.allocation_sites()
+            {
+                let row = self.btreepprofgrowthdiff
+                    .entry((pprofgrowth.hostname_port.clone(), allocation_site))
+                    .or_default();
+                row.second_in_use_bytes = bytes;
+                row.second_in_use_objects = objects;
+            }
+        }
+    }
+    /// Print the allocation sites that changed per host, the biggest growth first.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        let mut rows: Vec<_> = self.btreepprofgrowthdiff
+            .iter()
+            .filter(|((hostname_port, _), row)| hostname_filter.is_match(hostname_port) && row.first_in_use_bytes != row.second_in_use_bytes)
+            .collect();
+        rows.sort_by(|((a_hostname_port, _), a), ((b_hostname_port, _), b)| {
+            a_hostname_port.cmp(b_hostname_port)
+                .then((b.second_in_use_bytes as i64 - b.first_in_use_bytes as i64).cmp(&(a.second_in_use_bytes as i64 - a.first_in_use_bytes as i64)))
+        });
+        let mut previous_hostname_port = String::new();
+        let mut printed = 0;
+        for ((hostname_port, allocation_site), row) in rows
+        {
+            if *hostname_port != previous_hostname_port
+            {
+                println!("{}", "-".repeat(120));
+                println!("Host: {}", hostname_port);
+                println!("{}", "-".repeat(120));
+                println!("{:>12} {:>12} {:>12} {:>10} allocation site", "first", "second", "difference", "objects");
+                previous_hostname_port = hostname_port.to_string();
+                printed = 0;
+            }
+            if printed >= TOP_ALLOCATION_SITES && !*details_enable
+            {
+                continue;
+            }
+            let difference = if row.second_in_use_bytes >= row.first_in_use_bytes
+            {
+                format!("+{}", utility::bytes_to_human_readable(row.second_in_use_bytes - row.first_in_use_bytes))
+            }
+            else
+            {
+                format!("-{}", utility::bytes_to_human_readable(row.first_in_use_bytes - row.second_in_use_bytes))
+            };
+            println!("{:>12} {:>12} {:>12} {:>+10} {}",
+                     utility::bytes_to_human_readable(row.first_in_use_bytes),
+                     utility::bytes_to_human_readable(row.second_in_use_bytes),
+                     difference,
+                     row.second_in_use_objects as i64 - row.first_in_use_objects as i64,
+                     allocation_site,
+            );
+            printed += 1;
+        }
+    }
+}
+
+impl PprofProfileSample {
+    /// The function names of the stack, innermost frame first.
+    /// If the stack is not symbolized, the addresses are used.
+    fn frames(&self) -> &Vec<String>
+    {
+        if self.symbols.is_empty() { &self.addresses } else { &self.symbols }
+    }
+}
+
+impl PprofProfile {
+    /// Resolve the program counters of all samples via the `/pprof/symbol` endpoint of the server.
+    /// Except for the innermost frame, the return address minus one is resolved (see [lookup_address]).
+    fn symbolize(
+        &mut self,
+        host: &str,
+        port: &str,
+    )
+    {
+        let addresses = lookup_addresses(self.samples.iter().map(|sample| &sample.addresses));
+        let symbols = symbolize(host, port, &addresses);
+        if symbols.is_empty()
+        {
+            debug!("{}:{} no symbols returned from /pprof/symbol", host, port);
+            return;
+        }
+        for sample in self.samples.iter_mut()
+        {
+            sample.symbols = stack_symbols(&sample.addresses, &symbols);
+        }
+    }
+    /// The total number of samples.
+    pub fn total_samples(&self) -> u64
+    {
+        self.samples.iter().map(|sample| sample.count).sum()
+    }
+    /// The self (flat) and cumulative number of samples per function.
+    /// Self is the number of samples in which the function is the innermost frame,
+    /// cumulative is the number of samples in which the function is found anywhere in the stack.
+    pub fn functions(&self) -> BTreeMap<String, (u64, u64)>
+    {
+        let mut functions: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+        for sample in &self.samples
+        {
+            let frames = sample.frames();
+            if let Some(innermost) = frames.first()
+            {
+                functions.entry(innermost.to_string()).or_default().0 += sample.count;
+            }
+            // a recursive function must be counted once per sample.
+            for function in frames.iter().collect::<BTreeSet<_>>()
+            {
+                functions.entry(function.to_string()).or_default().1 += sample.count;
+            }
+        }
+        functions
+    }
+}
+
+impl AllPprofProfile {
+    pub fn new() -> Self { Default::default() }
+    /// Read `/pprof/profile` for the duration on all hosts and ports in parallel, so the profiles cover the same time.
+    /// The raw profile is written as `pprof_profile_<hostname:port>.prof` into the snapshot if snapshot_number is not negative.
+    /// The CPU profile is parsed and symbolized, and returned.
+    pub async fn read_and_write_profile(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        snapshot_number: i32,
+        parallel: usize,
+        duration: Duration,
+    ) -> Result<AllPprofProfile>
+    {
+        info!("begin parallel http read");
+        let timer = Instant::now();
+
+        // all profiles must run at the same time, so every host and port needs a thread.
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(parallel.max(hosts.len() * ports.len())).build().unwrap();
+        let (tx, rx) = channel();
+
+        pool.scope(move |s| {
+            for host in hosts {
+                for port in ports {
+                    let tx = tx.clone();
+                    s.spawn(move |_| {
+                        let detail_snapshot_time = Local::now();
+                        let profile_data = AllPprofProfile::read_http(host, port, duration);
+                        let pprofprofile = AllPprofProfile::parse_pprof_profile(&profile_data)
+                            .map(|mut pprofprofile| {
+                                pprofprofile.hostname_port = format!("{}:{}", host, port);
+                                pprofprofile.timestamp = detail_snapshot_time;
+                                pprofprofile.symbolize(host, port);
+                                pprofprofile
+                            });
+                        tx.send((format!("{}:{}", host, port), profile_data, pprofprofile)).expect("error sending data via tx (pprof profile)");
+                    });
+                }
+            }
+        });
+
+        info!("end parallel http read {:?}", timer.elapsed());
+
+        let mut allpprofprofile = AllPprofProfile::new();
+
+        for (hostname_port, profile_data, pprofprofile) in rx {
+            match pprofprofile
+            {
+                Some(pprofprofile) => {
+                    if snapshot_number >= 0
+                    {
+                        let current_directory = env::current_dir()?;
+                        let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number.to_string());
+
+                        let profile_file = &current_snapshot_directory.join(format!("pprof_profile_{}.prof", hostname_port));
+                        fs::write(profile_file, &profile_data)
+                            .with_context(|| format!("Error writing file: {}", profile_file.display()))?;
+                    }
+                    allpprofprofile.pprofprofile.push(pprofprofile);
+                },
+                None => debug!("{} did not return a CPU profile ({} bytes)", hostname_port, profile_data.len()),
+            }
+        }
+        Ok(allpprofprofile)
+    }
+    fn read_http(
+        host: &str,
+        port: &str,
+        duration: Duration,
+    ) -> Vec<u8>
+    {
+        utility::http_get_bytes(host, port, &format!("pprof/profile?seconds={}", duration.as_secs()), duration + PROFILE_TIMEOUT_MARGIN)
+    }
+    /// Parse the gperftools legacy CPU profile format.
+    ///
+    /// The profile is a sequence of 64-bit words:
+    /// - header: `0`, `3` (header words), `0` (version), sampling period in microseconds, `0`.
+    /// - records: sample count, number of program counters, the program counters.
+    /// - trailer: `0`, `1`, `0`.
+    ///
+    /// The mapped libraries following the trailer are ignored.
+    /// If the data is not a CPU profile, None is returned.
+    fn parse_pprof_profile(
+        profile_data: &[u8],
+    ) -> Option<PprofProfile>
+    {
+        let mut words = profile_data
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()));
+
+        let header: Vec<u64> = words.by_ref().take(5).collect();
+        if header.len() != 5 || header[0] != 0 || header[1] != 3 || header[2] != 0
+        {
+            return None;
+        }
+        let mut pprofprofile = PprofProfile {
+            sampling_period_us: header[3],
+            ..Default::default()
+        };
+        while let (Some(count), Some(depth)) = (words.next(), words.next())
+        {
+            let addresses: Vec<String> = words.by_ref().take(depth as usize).map(|address| format!("{:#x}", address)).collect();
+            if addresses.len() != depth as usize
+            {
+                warn!("CPU profile is truncated");
+                break;
+            }
+            // the trailer is a record with a count of zero and a single zero program counter.
+            if count == 0
+            {
+                break;
+            }
+            pprofprofile.samples.push(PprofProfileSample {
+                count,
+                addresses,
+                ..Default::default()
+            });
+        }
+        Some(pprofprofile)
+    }
+    /// Produce the samples in folded stack format: `hostname_port;outermost frame;...;innermost frame count`.
+    pub fn folded_stacks(
+        &self,
+    ) -> Vec<String>
+    {
+        let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+        for pprofprofile in &self.pprofprofile
+        {
+            for sample in &pprofprofile.samples
+            {
+                let stack = sample.frames()
+                    .iter()
+                    .rev()
+                    .map(|frame| frame.replace(';', ":"))
+                    .collect::<Vec<_>>()
+                    .join(";");
+                *stacks.entry(format!("{};{}", pprofprofile.hostname_port, stack)).or_default() += sample.count;
+            }
+        }
+        stacks
+            .iter()
+            .map(|(stack, count)| format!("{} {}", stack, count))
+            .collect()
+    }
+    /// Print the functions with the most samples per hostname_port.
+    /// Without details_enable, the top 20 functions are printed.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        for pprofprofile in self.pprofprofile
+            .iter()
+            .filter(|pprofprofile| hostname_filter.is_match(&pprofprofile.hostname_port))
+        {
+            let total_samples = pprofprofile.total_samples();
+            let mut functions: Vec<_> = pprofprofile.functions().into_iter().collect();
+            // order by self samples, and then by cumulative samples, highest first.
+            functions.sort_by(|(_, (a_self, a_cumulative)), (_, (b_self, b_cumulative))| b_self.cmp(a_self).then(b_cumulative.cmp(a_cumulative)));
+            let percentage = |samples: u64| if total_samples > 0 { samples as f64 / total_samples as f64 * 100_f64 } else { 0_f64 };
+
+            println!("{}", "-".repeat(120));
+            println!("Host: {}, Snapshot time: {}, samples: {}, sampling period: {} us", pprofprofile.hostname_port, pprofprofile.timestamp, total_samples, pprofprofile.sampling_period_us);
+            println!("{}", "-".repeat(120));
+            println!("{:>8} {:>7} {:>8} {:>7} function", "self", "self%", "cum", "cum%");
+            for (function, (self_samples, cumulative_samples)) in functions
+                .iter()
+                .filter(|(_, (self_samples, _))| *self_samples > 0)
+                .take(if *details_enable { usize::MAX } else { TOP_FUNCTIONS })
+            {
+                println!("{:>8} {:>7.1} {:>8} {:>7.1} {}",
+                         self_samples,
+                         percentage(*self_samples),
+                         cumulative_samples,
+                         percentage(*cumulative_samples),
+                         function,
+                );
+            }
+        }
+    }
+}
+
+pub async fn pprof_profile(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let snapshot_comment = Some(options.snapshot_comment.clone().unwrap_or_else(|| "cpu profile".to_string()));
+    let snapshot_number = snapshot::Snapshot::insert_new_profile_snapshot_number(&snapshot_comment)?;
+
+    let allpprofprofile = AllPprofProfile::read_and_write_profile(&hosts, &ports, snapshot_number, parallel, Duration::from_secs(options.profile_duration)).await?;
+    snapshot::save_snapshot_flamegraph(snapshot_number, "pprof_profile", "CPU profile", &allpprofprofile.folded_stacks())?;
+
+    allpprofprofile.print(&hostname_filter, &options.details_enable);
+    snapshot::save_snapshot_json(snapshot_number, "pprof_profile", allpprofprofile.pprofprofile)?;
+    if !options.silent {
+        println!("snapshot number {}", snapshot_number);
+    }
+    Ok(())
+}
+
+pub async fn print_pprof_profile(
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let mut allpprofprofile = AllPprofProfile::new();
+    allpprofprofile.pprofprofile = snapshot::read_snapshot_json(options.print_pprof_profile.as_ref().unwrap(), "pprof_profile")?;
+    allpprofprofile.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+pub async fn pprof_growth_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let pprofgrowthdiff = PprofGrowthDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    pprofgrowthdiff.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+pub async fn print_pprof_growth(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    match options.print_pprof_growth.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut allpprofgrowth = AllPprofGrowth::new();
+            allpprofgrowth.pprofgrowth = snapshot::read_snapshot_json(snapshot_number, "pprof_growth")?;
+            allpprofgrowth.print(&hostname_filter, &options.details_enable);
+        }
+        None => {
+            let allpprofgrowth = AllPprofGrowth::read_pprof_growth(&hosts, &ports, parallel).await?;
+            allpprofgrowth.print(&hostname_filter, &options.details_enable);
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn unit_parse_pprof_growth() {
+        let pprof_data = r#"heap profile:    3:  5242880 [     3:  5242880] @ growthz
+     2:  4194304 [     2:  4194304] @ 0x7f3a4c1f2e0d 0x25c8d2a 0x25c9000
+     1:  1048576 [     1:  1048576] @ 0x7f3a4c1f2e0d 0x1a2b3c4
+
+MAPPED_LIBRARIES:
+00400000-04a9e000 r-xp 00000000 fd:00 1234  /home/yugabyte/bin/yb-tserver
+"#;
+        let mut result = AllPprofGrowth::parse_pprof_growth(pprof_data);
+        assert_eq!(result.samples.len(), 2);
+        assert_eq!(result.samples[0].in_use_objects, 2);
+        assert_eq!(result.samples[0].in_use_bytes, 4194304);
+        assert_eq!(result.samples[0].addresses, vec!["0x7f3a4c1f2e0d", "0x25c8d2a", "0x25c9000"]);
+        // without symbols, the allocation site is the first address.
+        assert_eq!(result.samples[1].allocation_site(), "0x7f3a4c1f2e0d");
+
+        result.samples[0].symbols = vec!["tcmalloc::PageHeap::GrowHeap()".to_string(), "yb::Arena::AllocateBlock()".to_string(), "yb::tablet::Tablet::Write()".to_string()];
+        assert_eq!(result.samples[0].allocation_site(), "yb::Arena::AllocateBlock()");
+        assert_eq!(result.allocation_sites()["yb::Arena::AllocateBlock()"], (4194304, 2));
+    }
+
+    #[test]
+    fn unit_parse_address() {
+        assert_eq!(parse_address("0x25c8d2a"), Some(0x25c8d2a));
+        assert_eq!(parse_address("00000000025c8d2a"), Some(0x25c8d2a));
+        assert_eq!(parse_address("yb::Tablet"), None);
+        // the innermost frame is resolved as is, the return addresses of the other frames minus one.
+        let addresses = vec!["0x25c8d2a".to_string(), "0x25c8d2a".to_string(), "0x1000".to_string()];
+        assert_eq!(lookup_addresses([&addresses].into_iter()), vec!["0xfff", "0x25c8d29", "0x25c8d2a"]);
+        let symbols = HashMap::from([(0x25c8d2a, "yb::Tablet::Write".to_string()), (0x25c8d29, "yb::Tablet::Apply".to_string())]);
+        assert_eq!(stack_symbols(&addresses, &symbols), vec!["yb::Tablet::Write", "yb::Tablet::Apply", "0x1000"]);
+    }
+
+    #[test]
+    fn unit_parse_pprof_profile() {
+        let words: Vec<u64> = vec![
+            // header
+            0, 3, 0, 10000, 0,
+            // 3 samples of leaf <- caller <- main
+            3, 3, 0x1000, 0x2005, 0x3005,
+            // 1 sample of caller <- main
+            1, 2, 0x2000, 0x3005,
+            // trailer
+            0, 1, 0,
+        ];
+        let mut profile_data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        profile_data.extend_from_slice(b"00400000-04a9e000 r-xp 00000000 fd:00 1234  /home/yugabyte/bin/yb-tserver\n");
+
+        let mut result = AllPprofProfile::parse_pprof_profile(&profile_data).unwrap();
+        assert_eq!(result.sampling_period_us, 10000);
+        assert_eq!(result.samples.len(), 2);
+        assert_eq!(result.samples[0].count, 3);
+        assert_eq!(result.samples[0].addresses, vec!["0x1000", "0x2005", "0x3005"]);
+        assert_eq!(result.total_samples(), 4);
+
+        result.hostname_port = "192.168.66.80:9000".to_string();
+        result.samples[0].symbols = vec!["leaf".to_string(), "caller".to_string(), "main".to_string()];
+        result.samples[1].symbols = vec!["caller".to_string(), "main".to_string()];
+        let functions = result.functions();
+        assert_eq!(functions["leaf"], (3, 3));
+        assert_eq!(functions["caller"], (1, 4));
+        assert_eq!(functions["main"], (0, 4));
+
+        let mut allpprofprofile = AllPprofProfile::new();
+        allpprofprofile.pprofprofile.push(result);
+        assert_eq!(allpprofprofile.folded_stacks(), vec!["192.168.66.80:9000;main;caller 1", "192.168.66.80:9000;main;caller;leaf 3"]);
+    }
+
+    #[test]
+    fn unit_parse_pprof_profile_invalid() {
+        assert!(AllPprofProfile::parse_pprof_profile(b"").is_none());
+        assert!(AllPprofProfile::parse_pprof_profile(b"Failed to start profiler, is it already running?").is_none());
+    }
+
     #[tokio::test]
     async fn integration_parse_pprof_growth_tserver() {
-        // currently, the pprof "parsing" is not much parsing.
-        // What currently is done, is that the hostname:port/pprof/growth output is stored in a file in the snapshot directory named <hostname>:<port>_pprof_growth.
+        // The hostname:port/pprof/growth output is parsed.
+        // The raw output is stored in a file in the snapshot directory named pprof_growth_<hostname>:<port>.txt if a snapshot number is given.
         let hostname = utility::get_hostname_tserver();
         let port = utility::get_port_tserver();
-        Pprof::read_and_write_pprof(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
+        AllPprofGrowth::read_and_write_pprof(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
     }
     #[tokio::test]
     async fn integration_parse_pprof_growth_master() {
-        // currently, the pprof "parsing" is not much parsing.
-        // What currently is done, is that the hostname:port/pprof/growth output is stored in a file in the snapshot directory named <hostname>:<port>_pprof_growth.
+        // The hostname:port/pprof/growth output is parsed.
+        // The raw output is stored in a file in the snapshot directory named pprof_growth_<hostname>:<port>.txt if a snapshot number is given.
         let hostname = utility::get_hostname_master();
         let port = utility::get_port_master();
-        Pprof::read_and_write_pprof(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
+        AllPprofGrowth::read_and_write_pprof(&vec![&hostname], &vec![&port], -1, 1).await.unwrap();
     }
 }
@@ -1,13 +1,29 @@
-//! Module for reading `/pprof/growth` on the masters and tablet servers.
+//! Module for reading `/pprof/growth` and `/pprof/profile` on the masters and tablet servers.
 //!
 //! The `/pprof/growth` endpoint contains gperftools heap-profiling delta (growth) information.
 //! It is available on all master and tablet server endpoints, default port numbers 7000 (master), 9000, 12000 (tablet server).
 //!
-//! yb_stats doesn't do anything other than:
-//! - perform a HTTP GET and collect the result from the endpoint.
-//! - check if the returned data starts with "heap profile".
-//! - if so, save the result as "pprof_growth_*hostname:port*".
+//! yb_stats:
+//! - performs a HTTP GET and collects the result from the endpoint.
+//! - checks if the returned data starts with "heap profile".
+//! - if so, saves the result as "pprof_growth_*hostname:port*.txt".
+//! - parses the stacks and bytes into [PprofGrowth], and resolves the addresses via the `/pprof/symbol` endpoint of the same server at snapshot time.
+//!   The symbolized profiles are saved as "pprof_growth.json", so printing and comparing only read the snapshot.
 //!
+//! `/pprof/profile?seconds=N` runs the gperftools CPU profiler for N seconds and returns the profile in the legacy binary format.
+//! The CPU profile is requested on all servers in parallel, so the profiles cover the same time,
+//! saved as "pprof_profile_*hostname:port*.prof", parsed into [PprofProfile] and symbolized,
+//! and saved as "pprof_profile.json", "pprof_profile.folded" and "pprof_profile.svg" (flamegraph) in a new snapshot.
+//!
+//! The pprof functionality is called from:
+//! - [crate::snapshot::perform_snapshot] -> [AllPprofGrowth::perform_snapshot] (general snapshot, saves heap growth data)
+//! - [crate] -> [print_pprof_growth] (print adhoc (live) or snapshot top allocation sites)
+//! - [crate] -> [pprof_profile] (capture a CPU profile in a new snapshot)
+//! - [crate] -> [print_pprof_profile] (print the top functions of a CPU profile snapshot)
+//! - [crate] -> [pprof_growth_diff] (print the growth of the allocation sites between two snapshots)
+//!
+mod structs;
 mod functions;
 
+pub use structs::*;
 pub use functions::*;
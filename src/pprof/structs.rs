@@ -0,0 +1,87 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+use chrono::{DateTime, Local};
+/// The parsed heap growth profile of `/pprof/growth`.
+///
+/// The heap growth profile looks like this:
+/// ```text
+/// heap profile:    6:  6291456 [     6:  6291456] @ growthz
+///      1:  1048576 [     1:  1048576] @ 0x7f3a4c1f2e0d 0x7f3a4c1f3a1b 0x25c8d2a
+///      ...
+/// MAPPED_LIBRARIES:
+/// ...
+/// ```
+/// Every sample line contains the in use objects and bytes, the allocated objects and bytes, and the stack as addresses.
+/// The addresses are symbolized via `/pprof/symbol` when the profile is read.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PprofGrowth {
+    /// yb_stats added to allow understanding the source host.
+    pub hostname_port: String,
+    /// yb_stats added to allow understanding the timestamp.
+    pub timestamp: DateTime<Local>,
+    pub samples: Vec<PprofGrowthSample>,
+}
+/// A single sample (stack) of the heap growth profile.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PprofGrowthSample {
+    pub in_use_objects: u64,
+    pub in_use_bytes: u64,
+    pub alloc_objects: u64,
+    pub alloc_bytes: u64,
+    pub addresses: Vec<String>,
+    /// The function names of the addresses, in the same order. Empty if symbolization failed.
+    pub symbols: Vec<String>,
+}
+/// Wrapper struct for holding the different heap growth profiles.
+#[derive(Debug, Default)]
+pub struct AllPprofGrowth {
+    pub pprofgrowth: Vec<PprofGrowth>,
+}
+// diff
+/// BTreeMap for storing a heap growth diff struct per hostname_port, allocation site
+type BTreePprofGrowthDiff = BTreeMap<(String, String), PprofGrowthDiffFields>;
+/// Wrapper struct for holding the btreemap
+#[derive(Debug, Default)]
+pub struct PprofGrowthDiff {
+    pub btreepprofgrowthdiff: BTreePprofGrowthDiff,
+}
+/// The heap growth diff struct.
+///
+/// The hostname:port and allocation site is the key of the btreemap,
+/// This struct holds the first and second in use bytes and objects of the allocation site.
+#[derive(Debug, Default)]
+pub struct PprofGrowthDiffFields {
+    pub first_in_use_bytes: u64,
+    pub first_in_use_objects: u64,
+    pub second_in_use_bytes: u64,
+    pub second_in_use_objects: u64,
+}
+/// The parsed CPU profile of `/pprof/profile`.
+///
+/// The CPU profile is the gperftools legacy binary format: a header with the sampling period,
+/// followed by records of the sample count and the stack as program counters (innermost frame first),
+/// and the mapped libraries as text.
+/// The program counters are symbolized via `/pprof/symbol` after the profile is read.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PprofProfile {
+    /// yb_stats added to allow understanding the source host.
+    pub hostname_port: String,
+    /// yb_stats added to allow understanding the timestamp.
+    pub timestamp: DateTime<Local>,
+    pub sampling_period_us: u64,
+    pub samples: Vec<PprofProfileSample>,
+}
+/// A single sample (stack) of the CPU profile.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PprofProfileSample {
+    pub count: u64,
+    pub addresses: Vec<String>,
+    /// The function names of the addresses, in the same order. Empty if symbolization failed.
+    pub symbols: Vec<String>,
+}
+/// Wrapper struct for holding the different CPU profiles.
+#[derive(Debug, Default)]
+pub struct AllPprofProfile {
+    pub pprofprofile: Vec<PprofProfile>,
+}
@@ -1,13 +1,14 @@
 //! The impls and functions
 //!
 use chrono::Local;
-use std::{fmt, sync::mpsc::channel, collections::BTreeMap, time::Instant};
+use std::{fmt, cmp::Reverse, sync::mpsc::channel, collections::BTreeMap, time::Instant};
 use log::*;
 use regex::Regex;
+use colored::*;
 use anyhow::Result;
 use crate::utility;
 use crate::snapshot;
-use crate::rpcs::{Rpcs, AllRpcs, CQLCallDetailsPB, RpcConnectionDetailsPB, RpcCallState, RequestHeader, StateType, CqlConnectionDetails, RemoteMethodPB};
+use crate::rpcs::{Rpcs, AllRpcs, CQLCallDetailsPB, RpcConnectionDetailsPB, RpcCallState, RequestHeader, StateType, CqlConnectionDetails, RemoteMethodPB, RpcCallInProgressPB, SlowRpc, TraceStep};
 use crate::rpcs::Rpcs::{Ysql, Rpc};
 use crate::Opts;
 
@@ -22,6 +23,35 @@ impl fmt::Display for StateType {
     }
 }
 
+/// A call in flight that has used this percentage of its timeout_millis is flagged as near timeout.
+const NEAR_TIMEOUT_PERCENTAGE: u64 = 80;
+
+impl RpcCallInProgressPB {
+    /// Parse the trace_buffer into timed steps.
+    /// Lines that do not contain a timed step, such as the header of a related (child) trace, are skipped.
+    pub fn parse_trace_buffer(&self) -> Vec<TraceStep>
+    {
+        let regular_expression = Regex::new(r"^\s*(\d{4} \d{2}:\d{2}:\d{2}\.\d{6})\s+\(\+\s*(\d+)us\)\s+([^\]]+)\]\s*(.*)$").unwrap();
+        let mut steps = Vec::new();
+        for line in self.trace_buffer
+            .as_ref()
+            .unwrap_or(&"".to_string())
+            .lines()
+        {
+            if let Some(captures) = regular_expression.captures(line)
+            {
+                steps.push(TraceStep {
+                    timestamp: captures[1].to_string(),
+                    delta_us: captures[2].parse::<u64>().unwrap_or_default(),
+                    source: captures[3].to_string(),
+                    message: captures[4].to_string(),
+                });
+            }
+        }
+        steps
+    }
+}
+
 impl AllRpcs {
     pub fn new() -> Self { Default::default() }
     pub async fn perform_snapshot(
@@ -590,6 +620,152 @@ impl AllRpcs {
             println!("{}", "-".repeat(120));
         }
     }
+    /// Collect the inbound and outbound calls in flight that take longer than threshold_millis,
+    /// grouped by `service_name.method_name`, or by `CQL.<call type>` for YCQL calls.
+    pub fn slow_calls(
+        &self,
+        threshold_millis: u64,
+        hostname_filter: &Regex,
+    ) -> BTreeMap<String, Vec<SlowRpc>>
+    {
+        let mut slow_calls: BTreeMap<String, Vec<SlowRpc>> = BTreeMap::new();
+        for rpcs in &self.rpcs
+        {
+            if let Rpc { inbound_connections, outbound_connections, hostname_port, .. } = rpcs
+            {
+                let hostname_port = hostname_port.clone().unwrap_or_default();
+                if !hostname_filter.is_match(&hostname_port)
+                {
+                    continue;
+                }
+                let inbound = inbound_connections
+                    .iter()
+                    .flatten()
+                    .map(|inbound| ("<-", &inbound.remote_ip, &inbound.calls_in_flight));
+                let outbound = outbound_connections
+                    .iter()
+                    .flatten()
+                    .map(|outbound| ("->", &outbound.remote_ip, &outbound.calls_in_flight));
+                for (direction, remote_ip, calls_in_flight) in inbound.chain(outbound)
+                {
+                    for call in calls_in_flight.iter().flatten()
+                    {
+                        if call.elapsed_millis.unwrap_or_default() < threshold_millis
+                        {
+                            continue;
+                        }
+                        // YCQL calls in flight do not have a header, and are grouped by `CQL.<call type>`.
+                        let remote_method = match (&call.header, &call.cql_details)
+                        {
+                            (Some(header), _) => header.remote_method
+                                .as_ref()
+                                .unwrap_or(&RemoteMethodPB::default())
+                                .to_string(),
+                            (None, Some(cql_details)) => format!("CQL.{}", cql_details.call_type.as_deref().unwrap_or("unknown")),
+                            (None, None) => "(no header)".to_string(),
+                        };
+                        slow_calls.entry(remote_method)
+                            .or_default()
+                            .push(SlowRpc {
+                                hostname_port: hostname_port.clone(),
+                                remote_ip: remote_ip.to_string(),
+                                direction: direction.to_string(),
+                                state: call.state.clone().unwrap_or_default(),
+                                elapsed_millis: call.elapsed_millis.unwrap_or_default(),
+                                timeout_millis: call.header
+                                    .as_ref()
+                                    .and_then(|header| header.timeout_millis)
+                                    .unwrap_or_default(),
+                                steps: call.parse_trace_buffer(),
+                            });
+                    }
+                }
+            }
+        }
+        for calls in slow_calls.values_mut()
+        {
+            calls.sort_by_key(|call| Reverse(call.elapsed_millis));
+        }
+        slow_calls
+    }
+    pub fn print_slow(
+        &self,
+        threshold_millis: u64,
+        details_enable: &bool,
+        hostname_filter: &Regex,
+    )
+    {
+        for (remote_method, calls) in self.slow_calls(threshold_millis, hostname_filter)
+        {
+            let total_elapsed: u64 = calls.iter().map(|call| call.elapsed_millis).sum();
+            println!("{}", "-".repeat(120));
+            println!("{}: {} calls, max: {} ms, avg: {} ms",
+                     remote_method,
+                     calls.len(),
+                     calls.first().map(|call| call.elapsed_millis).unwrap_or_default(),
+                     total_elapsed / calls.len() as u64,
+            );
+            for call in &calls
+            {
+                print!("{:30}{}{:30} {:>6} ms {:17} (timeout: {} ms)",
+                       call.hostname_port,
+                       call.direction,
+                       call.remote_ip,
+                       call.elapsed_millis,
+                       call.state,
+                       call.timeout_millis,
+                );
+                if call.state == RpcCallState::TIMED_OUT
+                    || call.state == RpcCallState::FINISHED_ERROR
+                {
+                    print!(" {}", call.state.to_string().red());
+                }
+                else if call.is_near_timeout()
+                {
+                    print!(" {}", "NEAR_TIMEOUT".yellow());
+                }
+                println!();
+                if let Some(slowest_step) = call.steps.iter().max_by_key(|step| step.delta_us)
+                {
+                    if *details_enable
+                    {
+                        for step in &call.steps
+                        {
+                            let line = format!("{:>12}us {:30} {}", step.delta_us, step.source, step.message);
+                            if step == slowest_step
+                            {
+                                println!("{:4}{}", "", line.yellow());
+                            }
+                            else
+                            {
+                                println!("{:4}{}", "", line);
+                            }
+                        }
+                    }
+                    else
+                    {
+                        println!("{:4}slowest step: {:>12}us {:30} {}", "", slowest_step.delta_us, slowest_step.source, slowest_step.message);
+                    }
+                }
+            }
+        }
+        println!("{}", "-".repeat(120));
+    }
+}
+
+impl SlowRpc {
+    /// Is the elapsed time of the call close to its timeout?
+    pub fn is_near_timeout(&self) -> bool
+    {
+        self.timeout_millis > 0
+            && self.elapsed_millis * 100 >= self.timeout_millis as u64 * NEAR_TIMEOUT_PERCENTAGE
+    }
+}
+
+impl fmt::Display for RemoteMethodPB {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.service_name, self.method_name)
+    }
 }
 
 // called from main
@@ -622,6 +798,34 @@ pub async fn print_rpcs(
     Ok(())
 }
 
+// called from main
+pub async fn print_slow_rpcs(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    match options.print_slow_rpcs
+        .as_ref()
+        .unwrap()
+    {
+        Some(snapshot_number) =>
+        {
+            let mut allrpcs = AllRpcs::new();
+            allrpcs.rpcs = snapshot::read_snapshot_json(snapshot_number, "rpcs")?;
+            allrpcs.print_slow(options.slow_rpc_threshold, &options.details_enable, &hostname_filter);
+        }
+        None =>
+        {
+            let allrpcs = AllRpcs::read_rpcs(&hosts, &ports, parallel).await;
+            allrpcs.print_slow(options.slow_rpc_threshold, &options.details_enable, &hostname_filter);
+        }
+    }
+    Ok(())
+}
 
 #[cfg(test)]
 mod tests {
@@ -1319,6 +1523,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unit_parse_trace_buffer() {
+        let call = RpcCallInProgressPB {
+            trace_buffer: Some("0504 10:54:31.362563 (+     0us) service_pool.cc:162] Inserting onto call queue\n0504 10:54:31.362612 (+    49us) service_pool.cc:221] Handling call\nRelated trace:\n0504 10:54:33.962612 (+2600000us) tablet_service.cc:1520] Write completed\n".to_string()),
+            ..Default::default()
+        };
+        let steps = call.parse_trace_buffer();
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].timestamp, "0504 10:54:31.362563");
+        assert_eq!(steps[0].delta_us, 0);
+        assert_eq!(steps[0].source, "service_pool.cc:162");
+        assert_eq!(steps[0].message, "Inserting onto call queue");
+        assert_eq!(steps[2].delta_us, 2600000);
+        assert_eq!(steps[2].source, "tablet_service.cc:1520");
+    }
+
+    #[test]
+    fn unit_slow_calls_grouped_by_method() {
+        let json = r#"
+{
+    "outbound_connections": [
+        {
+            "remote_ip": "192.168.66.81:9100",
+            "state": "OPEN",
+            "processed_call_count": 12,
+            "calls_in_flight": [
+                {
+                    "header": {
+                        "call_id": 1,
+                        "remote_method": {
+                            "service_name": "yb.tserver.TabletServerService",
+                            "method_name": "Write"
+                        },
+                        "timeout_millis": 3000
+                    },
+                    "elapsed_millis": 2900,
+                    "state": "SENT"
+                },
+                {
+                    "header": {
+                        "call_id": 2,
+                        "remote_method": {
+                            "service_name": "yb.tserver.TabletServerService",
+                            "method_name": "Write"
+                        },
+                        "timeout_millis": 3000
+                    },
+                    "elapsed_millis": 5,
+                    "state": "SENT"
+                },
+                {
+                    "header": {
+                        "call_id": 3,
+                        "remote_method": {
+                            "service_name": "yb.tserver.TabletServerService",
+                            "method_name": "Read"
+                        },
+                        "timeout_millis": 60000
+                    },
+                    "elapsed_millis": 1500,
+                    "state": "TIMED_OUT"
+                }
+            ],
+            "sending_bytes": 0
+        }
+    ]
+}
+        "#.to_string();
+        let mut allrpcs = AllRpcs::new();
+        allrpcs.rpcs.push(AllRpcs::parse_rpcs(json, "", ""));
+        let slow_calls = allrpcs.slow_calls(1000, &utility::set_regex(&None));
+        assert_eq!(slow_calls.len(), 2);
+        let write = &slow_calls["yb.tserver.TabletServerService.Write"];
+        assert_eq!(write.len(), 1);
+        assert_eq!(write[0].direction, "->");
+        assert!(write[0].is_near_timeout());
+        let read = &slow_calls["yb.tserver.TabletServerService.Read"];
+        assert_eq!(read[0].state, RpcCallState::TIMED_OUT);
+        assert!(!read[0].is_near_timeout());
+    }
+    #[test]
+    fn unit_slow_calls_ycql_without_header() {
+        let json = r#"
+{
+    "inbound_connections": [
+        {
+            "remote_ip": "127.0.0.1:35518",
+            "state": "OPEN",
+            "processed_call_count": 2,
+            "connection_details": {
+                "cql_connection_details": {
+                    "keyspace": "cr"
+                }
+            },
+            "calls_in_flight": [
+                {
+                    "elapsed_millis": 1250,
+                    "cql_details": {
+                        "type": "QUERY",
+                        "call_details": [
+                            {
+                                "sql_string": "select * from cr.t1;"
+                            }
+                        ]
+                    },
+                    "state": "SENT"
+                }
+            ]
+        }
+    ]
+}
+        "#.to_string();
+        let mut allrpcs = AllRpcs::new();
+        allrpcs.rpcs.push(AllRpcs::parse_rpcs(json, "", ""));
+        let slow_calls = allrpcs.slow_calls(1000, &utility::set_regex(&None));
+        assert_eq!(slow_calls.len(), 1);
+        let query = &slow_calls["CQL.QUERY"];
+        assert_eq!(query.len(), 1);
+        assert_eq!(query[0].direction, "<-");
+        assert_eq!(query[0].elapsed_millis, 1250);
+        assert_eq!(query[0].timeout_millis, 0);
+    }
+
     #[tokio::test]
     async fn integration_parse_rpcs_tserver() {
         let hostname = utility::get_hostname_tserver();
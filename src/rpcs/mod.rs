@@ -12,6 +12,7 @@
 //! The rpc functionality is called from:
 //! - [crate::snapshot::perform_snapshot] -> [crate::rpcs::AllRpcs::perform_snapshot] (general snapshot, saves rpc data)
 //! - [crate] -> [print_rpcs] (print adhoc (live) or snapshot rpc info)
+//! - [crate] -> [print_slow_rpcs] (print adhoc (live) or snapshot slow in-flight rpcs with their trace steps)
 //!
 mod structs;
 mod functions;
@@ -138,7 +138,7 @@ pub struct RemoteMethodPB {
 }
 /// `src/yb/rpc/rpc_introspection.proto`
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
 pub enum RpcCallState {
     #[default]
     READY = 0,
@@ -193,3 +193,32 @@ pub enum StateType {
     NEGOTIATING = 0,  // Connection is still being negotiated.
     OPEN = 1,         // Connection is active.
 }
+/// A single timed step parsed from the `trace_buffer` of a [RpcCallInProgressPB].
+///
+/// A trace line looks like:
+/// ```text
+/// 0504 10:54:31.362563 (+   123us) service_pool.cc:221] Handling call
+/// ```
+/// The delta (`+ 123us`) is the time spent between the previous step and this step.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TraceStep {
+    pub timestamp: String,
+    pub delta_us: u64,
+    pub source: String,
+    pub message: String,
+}
+/// An in-flight call that exceeded the slow RPC threshold.
+///
+/// This struct is created by yb_stats from [InboundConnection] and [OutboundConnection] calls in flight,
+/// and is grouped by `service_name.method_name` for reporting.
+#[derive(Debug, Default)]
+pub struct SlowRpc {
+    pub hostname_port: String,
+    pub remote_ip: String,
+    /// `<-` for inbound calls, `->` for outbound calls.
+    pub direction: String,
+    pub state: RpcCallState,
+    pub elapsed_millis: u64,
+    pub timeout_millis: u32,
+    pub steps: Vec<TraceStep>,
+}
@@ -0,0 +1,324 @@
+//! The impls and functions
+//!
+use std::{collections::{BTreeMap, BTreeSet}, sync::mpsc::channel, time::Instant};
+use regex::Regex;
+use colored::*;
+use log::*;
+use anyhow::Result;
+use crate::utility;
+use crate::snapshot;
+use crate::gflags::AllGFlags;
+use crate::cluster_config::AllSysClusterConfigEntryPB;
+use crate::security::{AllSecurity, ServerSecurity, AllWebServerSchemes, WebServerScheme};
+use crate::Opts;
+
+/// The security related gflags, with the role the gflag applies to (None for both), and the secure value.
+/// A gflag without a secure value is reported, but never considered insecure.
+const SECURITY_GFLAGS: &[(&str, Option<&str>, Option<&str>)] = &[
+    ("use_node_to_node_encryption", None, Some("true")),
+    ("use_client_to_server_encryption", None, Some("true")),
+    ("allow_insecure_connections", None, Some("false")),
+    ("ysql_enable_auth", Some("tserver"), Some("true")),
+    ("ycql_require_auth", Some("tserver"), Some("true")),
+    ("certs_dir", None, None),
+    ("certs_for_client_dir", None, None),
+    ("cert_node_filename", None, None),
+    ("webserver_certificate_file", None, None),
+];
+/// The setting for the scheme the web UI answered with when the data was read:
+/// true if the web UI answered with plain HTTP.
+const WEBSERVER_PLAIN_HTTP: &str = "webserver_plain_http";
+
+impl ServerSecurity {
+    /// True if the setting has a value that is known to be insecure.
+    pub fn is_insecure(
+        setting: &str,
+        value: &str,
+    ) -> bool
+    {
+        if setting == WEBSERVER_PLAIN_HTTP
+        {
+            return value == "true";
+        }
+        SECURITY_GFLAGS
+            .iter()
+            .find(|(name, _, _)| *name == setting)
+            .and_then(|(_, _, secure_value)| *secure_value)
+            .map(|secure_value| secure_value != value)
+            .unwrap_or(false)
+    }
+}
+
+impl AllWebServerSchemes {
+    pub fn new() -> Self { Default::default() }
+    pub async fn perform_snapshot(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        snapshot_number: i32,
+        parallel: usize,
+    ) -> Result<()>
+    {
+        info!("begin snapshot");
+        let timer = Instant::now();
+
+        let allwebserverschemes = AllWebServerSchemes::read_webserver_schemes(hosts, ports, parallel).await;
+        snapshot::save_snapshot_json(snapshot_number, "webserver-schemes", allwebserverschemes.schemes)?;
+
+        info!("end snapshot: {:?}", timer.elapsed());
+        Ok(())
+    }
+    pub async fn read_webserver_schemes(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> AllWebServerSchemes
+    {
+        info!("begin parallel http read");
+        let timer = Instant::now();
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(parallel).build().unwrap();
+        let (tx, rx) = channel();
+        pool.scope(move |s| {
+            for host in hosts {
+                for port in ports {
+                    let tx = tx.clone();
+                    s.spawn(move |_| {
+                        let scheme = utility::http_scheme(host, port)
+                            .map(|scheme| WebServerScheme { hostname_port: format!("{}:{}", host, port), scheme });
+                        tx.send(scheme).expect("error sending data via tx");
+                    });
+                }
+            }
+        });
+
+        info!("end parallel http read {:?}", timer.elapsed());
+
+        let mut allwebserverschemes = AllWebServerSchemes::new();
+        allwebserverschemes.schemes = rx.iter().flatten().collect();
+        allwebserverschemes
+    }
+}
+
+impl AllSecurity {
+    pub fn new() -> Self { Default::default() }
+    /// Take the security gflags per server for the role of the server, the scheme the web UI of the server answered with,
+    /// and the encryption at rest info of the cluster config.
+    pub fn new_from_data(
+        allgflags: &AllGFlags,
+        allwebserverschemes: &AllWebServerSchemes,
+        allsysclusterconfigentrypb: &AllSysClusterConfigEntryPB,
+    ) -> AllSecurity
+    {
+        let mut allsecurity = AllSecurity::new();
+        let roles = allgflags.roles();
+
+        for gflag in &allgflags.gflags
+        {
+            let hostname_port = gflag.hostname_port.clone().expect("hostname:port should be set");
+            let server = allsecurity.servers
+                .entry(hostname_port.clone())
+                .or_insert_with(|| ServerSecurity {
+                    role: roles.get(&hostname_port).cloned().unwrap_or_else(|| "unknown".to_string()),
+                    ..Default::default()
+                });
+            if SECURITY_GFLAGS
+                .iter()
+                .any(|(name, role, _)| *name == gflag.name && role.map(|role| role == server.role).unwrap_or(true))
+            {
+                server.settings.insert(gflag.name.clone(), gflag.value.clone());
+            }
+        }
+        // a server without a recorded scheme did not answer, and is not reported for plain HTTP.
+        for webserverscheme in &allwebserverschemes.schemes
+        {
+            allsecurity.servers
+                .entry(webserverscheme.hostname_port.clone())
+                .or_insert_with(|| ServerSecurity {
+                    role: roles.get(&webserverscheme.hostname_port).cloned().unwrap_or_else(|| "unknown".to_string()),
+                    ..Default::default()
+                })
+                .settings
+                .insert(WEBSERVER_PLAIN_HTTP.to_string(), (webserverscheme.scheme == "http").to_string());
+        }
+
+        allsecurity.encryption_at_rest = allsysclusterconfigentrypb.sysclusterconfigentrypb
+            .first()
+            .map(|cluster_config| cluster_config.encryption_info
+                .as_ref()
+                .and_then(|encryption_info| encryption_info.encryption_enabled)
+                .unwrap_or(false));
+
+        allsecurity
+    }
+    pub fn read_snapshot(
+        snapshot_number: &String,
+    ) -> Result<AllSecurity>
+    {
+        let mut allgflags = AllGFlags::new();
+        allgflags.gflags = snapshot::read_snapshot_json(snapshot_number, "gflags")?;
+        // snapshots taken before the web UI schemes were recorded do not have them.
+        let mut allwebserverschemes = AllWebServerSchemes::new();
+        allwebserverschemes.schemes = snapshot::read_snapshot_json(snapshot_number, "webserver-schemes").unwrap_or_default();
+        let mut allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::new();
+        allsysclusterconfigentrypb.sysclusterconfigentrypb = snapshot::read_snapshot_json(snapshot_number, "cluster-config")?;
+
+        Ok(AllSecurity::new_from_data(&allgflags, &allwebserverschemes, &allsysclusterconfigentrypb))
+    }
+    pub async fn read_http(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    ) -> AllSecurity
+    {
+        let allgflags = AllGFlags::read_gflags(hosts, ports, parallel).await;
+        let allwebserverschemes = AllWebServerSchemes::read_webserver_schemes(hosts, ports, parallel).await;
+        let allsysclusterconfigentrypb = AllSysClusterConfigEntryPB::read_cluster_config(hosts, ports, parallel).await;
+
+        AllSecurity::new_from_data(&allgflags, &allwebserverschemes, &allsysclusterconfigentrypb)
+    }
+    /// The settings that have more than one value between the servers of the same role.
+    pub fn inconsistent_settings(&self) -> BTreeSet<(String, String)>
+    {
+        let mut values: BTreeMap<(String, String), BTreeSet<&String>> = BTreeMap::new();
+        for server in self.servers.values()
+        {
+            for (setting, value) in &server.settings
+            {
+                values.entry((server.role.clone(), setting.clone())).or_default().insert(value);
+            }
+        }
+        values
+            .into_iter()
+            .filter(|(_, values)| values.len() > 1)
+            .map(|(role_setting, _)| role_setting)
+            .collect()
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+    )
+    {
+        match self.encryption_at_rest
+        {
+            Some(true) => println!("Encryption at rest: {}", "enabled".green()),
+            Some(false) => println!("Encryption at rest: {}", "disabled".red()),
+            None => println!("Encryption at rest: unknown (no cluster config)"),
+        }
+
+        let inconsistent_settings = self.inconsistent_settings();
+        let mut servers: Vec<_> = self.servers
+            .iter()
+            .filter(|(hostname_port, _)| hostname_filter.is_match(hostname_port))
+            .collect();
+        servers.sort_by(|(a_hostname_port, a), (b_hostname_port, b)| a.role.cmp(&b.role).then(a_hostname_port.cmp(b_hostname_port)));
+
+        let mut previous_role = String::new();
+        for (hostname_port, server) in servers
+        {
+            if server.role != previous_role
+            {
+                println!("{}", "-".repeat(120));
+                println!("Role: {}", server.role);
+                println!("{}", "-".repeat(120));
+                previous_role = server.role.clone();
+            }
+            println!("{}", hostname_port);
+            for (setting, value) in &server.settings
+            {
+                let shown_value = if value.is_empty() { "(empty)" } else { value };
+                let shown_value = if ServerSecurity::is_insecure(setting, value) {
+                    shown_value.red()
+                } else if inconsistent_settings.contains(&(server.role.clone(), setting.clone())) {
+                    shown_value.yellow()
+                } else {
+                    shown_value.normal()
+                };
+                println!("  {:40} {}", setting, shown_value);
+            }
+        }
+    }
+}
+
+pub async fn print_security_report(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    match options.security_report.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let allsecurity = AllSecurity::read_snapshot(snapshot_number)?;
+            allsecurity.print(&hostname_filter);
+        },
+        None => {
+            let allsecurity = AllSecurity::read_http(&hosts, &ports, parallel).await;
+            allsecurity.print(&hostname_filter);
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gflags::GFlag;
+
+    #[test]
+    fn unit_security_settings() {
+        let mut allgflags = AllGFlags::new();
+        for (hostname_port, name, value) in [
+            ("192.168.66.80:9000", "log_filename", "yb-tserver"),
+            ("192.168.66.80:9000", "use_node_to_node_encryption", "true"),
+            ("192.168.66.80:9000", "ysql_enable_auth", "false"),
+            ("192.168.66.80:9000", "webserver_certificate_file", "/opt/yugabyte/certs/node.crt"),
+            ("192.168.66.81:9000", "log_filename", "yb-tserver"),
+            ("192.168.66.81:9000", "use_node_to_node_encryption", "false"),
+            ("192.168.66.81:9000", "ysql_enable_auth", "false"),
+            ("192.168.66.81:9000", "max_clock_skew_usec", "500000"),
+            ("192.168.66.80:7000", "log_filename", "yb-master"),
+            ("192.168.66.80:7000", "use_node_to_node_encryption", "true"),
+            ("192.168.66.80:7000", "ysql_enable_auth", "false"),
+        ] {
+            allgflags.gflags.push(GFlag { hostname_port: Some(hostname_port.to_string()), name: name.to_string(), value: value.to_string(), ..Default::default() });
+        }
+
+        let mut allwebserverschemes = AllWebServerSchemes::new();
+        for (hostname_port, scheme) in [
+            ("192.168.66.80:9000", "https"),
+            ("192.168.66.81:9000", "http"),
+        ] {
+            allwebserverschemes.schemes.push(WebServerScheme { hostname_port: hostname_port.to_string(), scheme: scheme.to_string() });
+        }
+
+        let allsecurity = AllSecurity::new_from_data(&allgflags, &allwebserverschemes, &AllSysClusterConfigEntryPB::new());
+        assert_eq!(allsecurity.encryption_at_rest, None);
+        let server = &allsecurity.servers["192.168.66.81:9000"];
+        assert_eq!(server.role, "tserver");
+        assert_eq!(server.settings.len(), 3);
+        assert_eq!(server.settings[WEBSERVER_PLAIN_HTTP], "true");
+        assert_eq!(allsecurity.servers["192.168.66.80:9000"].settings["webserver_certificate_file"], "/opt/yugabyte/certs/node.crt");
+        assert_eq!(allsecurity.servers["192.168.66.80:9000"].settings[WEBSERVER_PLAIN_HTTP], "false");
+        // the web UI of the master did not answer: plain HTTP is not reported.
+        assert!(!allsecurity.servers["192.168.66.80:7000"].settings.contains_key(WEBSERVER_PLAIN_HTTP));
+        // the authentication gflags are only evaluated for the tablet servers.
+        let master = &allsecurity.servers["192.168.66.80:7000"];
+        assert_eq!(master.role, "master");
+        assert!(!master.settings.contains_key("ysql_enable_auth"));
+        assert!(master.settings.contains_key("use_node_to_node_encryption"));
+
+        let inconsistent_settings = allsecurity.inconsistent_settings();
+        assert!(inconsistent_settings.contains(&("tserver".to_string(), "use_node_to_node_encryption".to_string())));
+        assert!(inconsistent_settings.contains(&("tserver".to_string(), WEBSERVER_PLAIN_HTTP.to_string())));
+        assert!(!inconsistent_settings.contains(&("tserver".to_string(), "ysql_enable_auth".to_string())));
+        assert!(ServerSecurity::is_insecure(WEBSERVER_PLAIN_HTTP, "true"));
+        assert!(!ServerSecurity::is_insecure(WEBSERVER_PLAIN_HTTP, "false"));
+
+        assert!(ServerSecurity::is_insecure("ysql_enable_auth", "false"));
+        assert!(!ServerSecurity::is_insecure("use_node_to_node_encryption", "true"));
+        assert!(!ServerSecurity::is_insecure("certs_dir", ""));
+    }
+}
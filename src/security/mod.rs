@@ -0,0 +1,14 @@
+//! Module for the security posture report (`--security-report`).
+//!
+//! The report shows the encryption at rest setting of the cluster config, and per server the encryption in transit,
+//! authentication and certificate gflags, and whether the web UI answered with plain HTTP when the data was read.
+//! The scheme the web UI answered with is probed per server, and saved in the snapshot as `webserver-schemes`.
+//!
+//! The security functionality is called from:
+//! - [crate] -> [print_security_report] (report a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
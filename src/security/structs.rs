@@ -0,0 +1,33 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+
+/// The security settings of a single server.
+#[derive(Debug, Default)]
+pub struct ServerSecurity {
+    /// master or tserver
+    pub role: String,
+    /// BTreeMap with the setting (gflag) name and the value.
+    pub settings: BTreeMap<String, String>,
+}
+/// The scheme (https or http) the web UI of a server answered with.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct WebServerScheme {
+    /// yb_stats added to allow understanding the source host.
+    pub hostname_port: String,
+    /// https or http
+    pub scheme: String,
+}
+/// The wrapper struct for the vector holding the web UI schemes.
+#[derive(Debug, Default)]
+pub struct AllWebServerSchemes {
+    pub schemes: Vec<WebServerScheme>,
+}
+/// The security settings of the cluster.
+#[derive(Debug, Default)]
+pub struct AllSecurity {
+    /// BTreeMap with hostname_port and the security settings of the server.
+    pub servers: BTreeMap<String, ServerSecurity>,
+    /// The encryption at rest setting from the cluster config, None if there is no cluster config.
+    pub encryption_at_rest: Option<bool>,
+}
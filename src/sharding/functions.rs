@@ -0,0 +1,240 @@
+//! The impls and functions
+//!
+use std::collections::{BTreeMap, BTreeSet};
+use regex::Regex;
+use colored::*;
+use anyhow::{Context, Result};
+use crate::utility;
+use crate::snapshot;
+use crate::isleader::AllIsLeader;
+use crate::table_detail::AllTables;
+use crate::tablet_detail::AllTablets;
+use crate::partitions;
+use crate::sharding::{AllTableSharding, TableSharding};
+use crate::Opts;
+
+impl TableSharding {
+    pub fn total_tablet_size(&self) -> u64 { self.tablet_sizes.values().sum() }
+    pub fn average_tablet_size(&self) -> Option<u64> { self.total_tablet_size().checked_div(self.tablet_sizes.len() as u64) }
+    /// Flag the table as colocation candidate, for the number or the size of its tablets.
+    pub fn recommend(
+        &mut self,
+        min_tablet_size: u64,
+        max_tablet_size: u64,
+    )
+    {
+        let Some(average_tablet_size) = self.average_tablet_size() else { return };
+        let tablets = self.tablet_sizes.len() as u64;
+        let total_tablet_size = self.total_tablet_size();
+        let largest_tablet_size = self.tablet_sizes.values().max().copied().unwrap_or_default();
+
+        if self.is_ysql && total_tablet_size < min_tablet_size
+        {
+            self.recommendations.push(format!("colocation candidate: the total tablet size of {} is below the minimal tablet size of {}",
+                utility::bytes_to_human_readable(total_tablet_size),
+                utility::bytes_to_human_readable(min_tablet_size),
+            ));
+        }
+        if tablets > 1 && average_tablet_size < min_tablet_size
+        {
+            self.recommendations.push(format!("too many tiny tablets: {} tablets with an average size of {}, {} tablet(s) would reach the minimal tablet size of {}",
+                tablets,
+                utility::bytes_to_human_readable(average_tablet_size),
+                (total_tablet_size / min_tablet_size).max(1),
+                utility::bytes_to_human_readable(min_tablet_size),
+            ));
+        }
+        if average_tablet_size > max_tablet_size
+        {
+            self.recommendations.push(format!("too few huge tablets: {} tablet(s) with an average size of {}, {} tablets would stay below the maximal tablet size of {}",
+                tablets,
+                utility::bytes_to_human_readable(average_tablet_size),
+                total_tablet_size.div_ceil(max_tablet_size),
+                utility::bytes_to_human_readable(max_tablet_size),
+            ));
+        }
+        else if largest_tablet_size > max_tablet_size
+        {
+            self.recommendations.push(format!("split: the largest tablet of {} is above the maximal tablet size of {}",
+                utility::bytes_to_human_readable(largest_tablet_size),
+                utility::bytes_to_human_readable(max_tablet_size),
+            ));
+        }
+    }
+}
+
+impl AllTableSharding {
+    pub fn new() -> Self { Default::default() }
+    /// Take the user and index tables of the master leader, and add the tablets of the tablet servers.
+    pub fn new_from_data(
+        alltables: &AllTables,
+        alltablets: &AllTablets,
+        master_leader: &str,
+    ) -> AllTableSharding
+    {
+        let mut alltablesharding = AllTableSharding::new();
+
+        let tservers: BTreeSet<&String> = alltablets.tablet
+            .iter()
+            .filter_map(|tablet| tablet.hostname_port.as_ref())
+            .collect();
+        for tablebasic in alltables.table
+            .iter()
+            .filter(|table| table.hostname_port.as_deref() == Some(master_leader))
+            .flat_map(|table| table.tablebasic.iter())
+            .filter(|tablebasic| matches!(tablebasic.object_type.as_str(), "User tables" | "Index tables"))
+        {
+            alltablesharding.tables.push(TableSharding {
+                table: format!("{}.{}", tablebasic.keyspace, tablebasic.table_name),
+                table_id: tablebasic.uuid.clone(),
+                is_ysql: !tablebasic.ysql_oid.trim().is_empty(),
+                on_disk_size: tablebasic.total_on_disk_size_bytes(),
+                tablets_per_tserver: tservers.iter().map(|hostname_port| (hostname_port.to_string(), 0)).collect(),
+                ..Default::default()
+            });
+        }
+
+        // table_id, index in tables
+        let table_index: BTreeMap<String, usize> = alltablesharding.tables
+            .iter()
+            .enumerate()
+            .map(|(index, tablesharding)| (tablesharding.table_id.clone(), index))
+            .collect();
+        for tablet in &alltablets.tablet
+        {
+            for tabletbasic in tablet.tabletbasic
+                .iter()
+                .filter(|tabletbasic| partitions::is_active_tablet(&tabletbasic.state, &tabletbasic.hidden))
+            {
+                let Some(index) = table_index.get(&tabletbasic.table_uuid) else { continue };
+                let tablesharding = &mut alltablesharding.tables[*index];
+                let tablet_size = tablesharding.tablet_sizes.entry(tabletbasic.tablet_id.clone()).or_default();
+                *tablet_size = (*tablet_size).max(tabletbasic.total_on_disk_size_bytes().unwrap_or_default());
+                *tablesharding.tablets_per_tserver.entry(tablet.hostname_port.clone().unwrap_or_default()).or_default() += 1;
+            }
+        }
+        alltablesharding.tables.sort_by(|a, b| a.table.cmp(&b.table));
+        alltablesharding
+    }
+    pub fn recommend(
+        &mut self,
+        min_tablet_size: u64,
+        max_tablet_size: u64,
+    )
+    {
+        for tablesharding in self.tables.iter_mut()
+        {
+            tablesharding.recommend(min_tablet_size, max_tablet_size);
+        }
+    }
+    pub fn print(
+        &self,
+        table_name_filter: &Regex,
+    )
+    {
+        let size = |bytes: Option<u64>| bytes.map(utility::bytes_to_human_readable).unwrap_or_else(|| "-".to_string());
+        let mut tables = 0;
+        let mut tables_with_recommendations = 0;
+
+        println!("{:50} {:>7} {:>10} {:>10} {:>10} {:>10} {:>11}", "table", "tablets", "on disk", "avg tablet", "min tablet", "max tablet", "per tserver");
+        for tablesharding in self.tables.iter().filter(|tablesharding| table_name_filter.is_match(&tablesharding.table))
+        {
+            tables += 1;
+            println!("{:50} {:>7} {:>10} {:>10} {:>10} {:>10} {:>11}",
+                tablesharding.table,
+                tablesharding.tablet_sizes.len(),
+                size(tablesharding.on_disk_size),
+                size(tablesharding.average_tablet_size()),
+                size(tablesharding.tablet_sizes.values().min().copied()),
+                size(tablesharding.tablet_sizes.values().max().copied()),
+                format!("{}-{}",
+                    tablesharding.tablets_per_tserver.values().min().copied().unwrap_or_default(),
+                    tablesharding.tablets_per_tserver.values().max().copied().unwrap_or_default(),
+                ),
+            );
+            if !tablesharding.recommendations.is_empty()
+            {
+                tables_with_recommendations += 1;
+            }
+            for recommendation in &tablesharding.recommendations
+            {
+                println!("  {}", recommendation.yellow());
+            }
+        }
+        println!("{} of {} tables have a sharding recommendation.", tables_with_recommendations, tables);
+    }
+}
+
+pub async fn print_sharding_advice(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+    let min_tablet_size = utility::human_readable_to_bytes(&options.sharding_min_tablet_size)
+        .with_context(|| format!("Invalid size: {}", options.sharding_min_tablet_size))?;
+    let max_tablet_size = utility::human_readable_to_bytes(&options.sharding_max_tablet_size)
+        .with_context(|| format!("Invalid size: {}", options.sharding_max_tablet_size))?;
+
+    let mut alltablesharding = match options.sharding_advice.as_ref().unwrap()
+    {
+        Some(snapshot_number) => {
+            let mut alltables = AllTables::new();
+            alltables.table = snapshot::read_snapshot_json(snapshot_number, "tables")?;
+            let mut alltablets = AllTablets::new();
+            alltablets.tablet = snapshot::read_snapshot_json(snapshot_number, "tablets")?;
+            let leader_hostname = AllIsLeader::return_leader_snapshot(snapshot_number)?;
+            AllTableSharding::new_from_data(&alltables, &alltablets, &leader_hostname)
+        },
+        None => {
+            let alltables = AllTables::read_tables(&hosts, &ports, parallel, &false).await;
+            let alltablets = AllTablets::read_tablets(&hosts, &ports, parallel, &false).await;
+            let leader_hostname = AllIsLeader::return_leader_http(&hosts, &ports, parallel).await;
+            AllTableSharding::new_from_data(&alltables, &alltablets, &leader_hostname)
+        },
+    };
+    alltablesharding.recommend(min_tablet_size, max_tablet_size);
+    alltablesharding.print(&table_name_filter);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_sharding_recommend() {
+        const M: u64 = 1024 * 1024;
+        let table = |is_ysql: bool, tablet_sizes: &[u64]| -> TableSharding {
+            let mut tablesharding = TableSharding {
+                is_ysql,
+                tablet_sizes: tablet_sizes.iter().enumerate().map(|(nr, size)| (format!("tablet{}", nr), *size)).collect(),
+                ..Default::default()
+            };
+            tablesharding.recommend(512 * M, 10 * 1024 * M);
+            tablesharding
+        };
+
+        // a small YSQL table with default sharding.
+        let tablesharding = table(true, &[10 * M, 10 * M, 10 * M, 10 * M]);
+        assert_eq!(tablesharding.recommendations, vec![
+            "colocation candidate: the total tablet size of 40.00M is below the minimal tablet size of 512.00M".to_string(),
+            "too many tiny tablets: 4 tablets with an average size of 10.00M, 1 tablet(s) would reach the minimal tablet size of 512.00M".to_string(),
+        ]);
+        // a YCQL table is not a colocation candidate.
+        assert_eq!(table(false, &[10 * M, 10 * M]).recommendations.len(), 1);
+        // a large table with a single tablet.
+        assert_eq!(table(true, &[25 * 1024 * M]).recommendations, vec![
+            "too few huge tablets: 1 tablet(s) with an average size of 25.00G, 3 tablets would stay below the maximal tablet size of 10.00G".to_string(),
+        ]);
+        // a single large tablet.
+        assert_eq!(table(true, &[1024 * M, 11 * 1024 * M]).recommendations, vec![
+            "split: the largest tablet of 11.00G is above the maximal tablet size of 10.00G".to_string(),
+        ]);
+        assert!(table(true, &[1024 * M, 1024 * M]).recommendations.is_empty());
+        assert!(table(true, &[]).recommendations.is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+//! Module for advising on the number of tablets of the tables based on their size (`--sharding-advice`).
+//!
+//! A table is flagged as colocation candidate, for too many tiny tablets, too few huge tablets, or for splitting,
+//! using `--sharding-min-tablet-size` and `--sharding-max-tablet-size`.
+//!
+//! The sharding functionality is called from:
+//! - [crate] -> [print_sharding_advice] (for a snapshot, or live data)
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
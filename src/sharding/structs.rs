@@ -0,0 +1,25 @@
+//! The structs
+//!
+use std::collections::BTreeMap;
+
+/// The tablets and sizes of a table.
+#[derive(Debug, Default)]
+pub struct TableSharding {
+    /// keyspace.table_name
+    pub table: String,
+    pub table_id: String,
+    /// A YSQL table has a YSQL OID.
+    pub is_ysql: bool,
+    /// The on disk size of all replicas, from the master.
+    pub on_disk_size: Option<u64>,
+    /// tablet_id, the on disk size of the largest replica.
+    pub tablet_sizes: BTreeMap<String, u64>,
+    /// hostname_port, the number of tablet replicas.
+    pub tablets_per_tserver: BTreeMap<String, usize>,
+    pub recommendations: Vec<String>,
+}
+/// Wrapper struct for holding the tables.
+#[derive(Debug, Default)]
+pub struct AllTableSharding {
+    pub tables: Vec<TableSharding>,
+}
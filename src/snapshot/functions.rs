@@ -2,13 +2,13 @@
 //!
 use log::*;
 use std::{env, fs, io::{stdin, stdout, Write}, path::Path, sync::Arc, time::Instant};
-use chrono::Local;
+use chrono::{DateTime, Local};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use crate::{Opts, tablet_server_operations};
-use crate::{clocks, entities, gflags, isleader, loglines, masters, mems, memtrackers, metrics, node_exporter, pprof, rpcs, statements, threads, tablet_servers, utility, vars, versions, cluster_config, health_check, table_detail, tablet_detail, tasks, tablet_replication, drives};
-use crate::snapshot::Snapshot;
+use crate::{clocks, entities, gflags, isleader, loglines, masters, mems, memtrackers, metrics, node_exporter, pprof, rpcs, statements, threads, tablet_servers, utility, vars, versions, cluster_config, health_check, table_detail, tablet_detail, tasks, tablet_replication, drives, security};
+use crate::snapshot::{Snapshot, SnapshotType};
 
 impl Snapshot {
     /// This is a public function to:
@@ -22,6 +22,20 @@ impl Snapshot {
     pub fn insert_new_snapshot_number(
         snapshot_comment: &Option<String>
     ) -> Result<i32>
+    {
+        Snapshot::insert_new_snapshot_number_with_type(snapshot_comment, SnapshotType::Regular)
+    }
+    /// This is a public function to insert a new snapshot that only holds a profile, see [Snapshot::insert_new_snapshot_number].
+    pub fn insert_new_profile_snapshot_number(
+        snapshot_comment: &Option<String>
+    ) -> Result<i32>
+    {
+        Snapshot::insert_new_snapshot_number_with_type(snapshot_comment, SnapshotType::Profile)
+    }
+    fn insert_new_snapshot_number_with_type(
+        snapshot_comment: &Option<String>,
+        snapshot_type: SnapshotType,
+    ) -> Result<i32>
     {
         info!("read_snapshot_number");
         let mut snapshots: Vec<Snapshot> = Vec::new();
@@ -45,7 +59,7 @@ impl Snapshot {
             snapshot_number = record_with_highest_snapshot_number.number + 1;
         }
         // create a new snapshot vector and assign it the new_snapshot, and add it to the snapshots vector.
-        let new_snapshot: Snapshot = Snapshot { number: snapshot_number, timestamp: Local::now(), comment: snapshot_comment.clone().unwrap_or_default() };
+        let new_snapshot: Snapshot = Snapshot { number: snapshot_number, timestamp: Local::now(), comment: snapshot_comment.clone().unwrap_or_default(), snapshot_type };
         snapshots.push(new_snapshot);
         Snapshot::write_snapshot_index(snapshots)?;
         // Create the snapshot number directory in the &yb_stats_directory
@@ -165,6 +179,39 @@ impl Snapshot {
         // all information is found and collected, return it to the caller.
         Ok((begin_snapshot.to_string(), end_snapshot.to_string(), begin_snapshot_row.clone()))
     }
+    /// This is a public function that returns the snapshot numbers from the begin snapshot up to and including the end snapshot,
+    /// in the order of the snapshot numbers.
+    /// This is used for the reports that look at all the snapshots in a range instead of the begin and end snapshot only.
+    /// Profile snapshots are skipped, because these do not contain the regular snapshot data.
+    pub fn read_snapshot_range(
+        begin_snapshot: &str,
+        end_snapshot: &str,
+    ) -> Result<Vec<String>>
+    {
+        let begin: i32 = begin_snapshot.parse()
+            .with_context(|| format!("Invalid begin snapshot number: {}", begin_snapshot))?;
+        let end: i32 = end_snapshot.parse()
+            .with_context(|| format!("Invalid end snapshot number: {}", end_snapshot))?;
+        let mut snapshot_numbers: Vec<i32> = Snapshot::read_snapshot_index()?
+            .iter()
+            .filter(|row| row.snapshot_type == SnapshotType::Regular)
+            .map(|row| row.number)
+            .filter(|number| *number >= begin && *number <= end)
+            .collect();
+        snapshot_numbers.sort();
+        Ok(snapshot_numbers.iter().map(|number| number.to_string()).collect())
+    }
+    /// This is a public function that returns the timestamp of a snapshot.
+    pub fn read_snapshot_timestamp(
+        snapshot_number: &str,
+    ) -> Result<DateTime<Local>>
+    {
+        Snapshot::read_snapshot_index()?
+            .iter()
+            .find(|row| row.number.to_string() == snapshot_number)
+            .map(|row| row.timestamp)
+            .with_context(|| format!("Unable to find snapshot number: {}", snapshot_number))
+    }
 }
 /// This is the general yb_stat wide save_snapshot_json function.
 pub fn save_snapshot_json<T: Serialize>(
@@ -199,6 +246,38 @@ pub fn read_snapshot_json<T: for<'de> Deserialize<'de>>(
     };
     Ok(vector)
 }
+/// This is the general yb_stats wide function to save folded stacks into a snapshot.
+///
+/// The stacks are saved in Brendan Gregg's folded stack format as `<filename>.folded`,
+/// and are rendered into a flamegraph as `<filename>.svg`.
+pub fn save_snapshot_flamegraph(
+    snapshot_number: i32,
+    filename: &str,
+    title: &str,
+    folded_stacks: &[String],
+) -> Result<()>
+{
+    let current_directory = env::current_dir()?;
+    let current_snapshot_directory = current_directory.join("yb_stats.snapshots").join(snapshot_number.to_string());
+
+    let filepath = &current_snapshot_directory.join(format!("{}.{}", filename, "folded"));
+    fs::write(filepath, folded_stacks.join("\n"))
+        .with_context(|| format!("Error saving snapshot: {}", filepath.display()))?;
+
+    // an empty set of stacks cannot be rendered into a flamegraph.
+    if folded_stacks.is_empty()
+    {
+        return Ok(());
+    }
+    let filepath = &current_snapshot_directory.join(format!("{}.{}", filename, "svg"));
+    let file = fs::File::create(filepath)
+        .with_context(|| format!("Cannot create file: {}", filepath.display()))?;
+    let mut flamegraph_options = inferno::flamegraph::Options::default();
+    flamegraph_options.title = title.to_string();
+    inferno::flamegraph::from_lines(&mut flamegraph_options, folded_stacks.iter().map(|line| line.as_str()), file)
+        .with_context(|| format!("Error saving flamegraph: {}", filepath.display()))?;
+    Ok(())
+}
 /// The function to perform a general snapshot resulting in JSON files.
 pub async fn perform_snapshot(
     hosts: Vec<&'static str>,
@@ -306,8 +385,9 @@ pub async fn perform_snapshot(
 
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
+    let ysql_log_line_prefix = options.ysql_log_line_prefix.clone();
     let handle = tokio::spawn(async move {
-        loglines::AllLogLines::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+        loglines::AllLogLines::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel, &ysql_log_line_prefix).await.unwrap();
     });
     handles.push(handle);
 
@@ -321,14 +401,14 @@ pub async fn perform_snapshot(
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
     let handle = tokio::spawn(async move {
-        pprof::Pprof::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+        pprof::AllPprofGrowth::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
     });
     handles.push(handle);
 
     let arc_hosts_clone = arc_hosts.clone();
     let arc_ports_clone = arc_ports.clone();
     let handle = tokio::spawn(async move {
-        mems::Mems::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+        mems::AllMems::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
     });
     handles.push(handle);
 
@@ -397,6 +477,13 @@ pub async fn perform_snapshot(
     });
     handles.push(handle);
 
+    let arc_hosts_clone = arc_hosts.clone();
+    let arc_ports_clone = arc_ports.clone();
+    let handle = tokio::spawn(async move {
+        security::AllWebServerSchemes::perform_snapshot(&arc_hosts_clone, &arc_ports_clone, snapshot_number, parallel).await.unwrap();
+    });
+    handles.push(handle);
+
     for handle in handles {
         handle.await.unwrap();
     }
@@ -657,6 +744,145 @@ pub async fn adhoc_node_exporter_diff(
     Ok(())
 }
 
+/// Take "adhoc" (in memory) snapshots of threads only:
+///
+/// The idea here is to see the CPU usage of the threads and thread pools in between the snapshots.
+pub async fn adhoc_threads_diff(
+    hosts: Vec<&'static str>,
+    ports: Vec<&'static str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    info!("ad-hoc threads diff first snapshot begin");
+    let timer = Instant::now();
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let first_snapshot_time = Local::now();
+
+    let threads = Arc::new(Mutex::new(threads::ThreadsDiff::new()));
+
+    let hosts = Arc::new(hosts);
+    let ports = Arc::new(ports);
+
+    let mut handles = vec![];
+
+    let clone_threads = threads.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_threads.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    info!("ad-hoc threads diff first snapshot end: {:?}", timer.elapsed());
+
+    println!("Begin ad-hoc in-memory snapshot created, press enter to create end snapshot for difference calculation.");
+    let mut input = String::new();
+    stdin().read_line(&mut input).expect("failed");
+
+    info!("ad-hoc threads diff second snapshot begin");
+    let timer = Instant::now();
+
+    let second_snapshot_time = Local::now();
+
+    let mut handles = vec![];
+
+    let clone_threads = threads.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let handle = tokio::spawn(async move {
+        clone_threads.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel).await;
+    });
+    handles.push(handle);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    info!("ad-hoc threads diff second snapshot end: {:?}", timer.elapsed());
+
+    println!("Time between snapshots: {:8.3} seconds", (second_snapshot_time - first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
+    threads.lock().await.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+/// Take "adhoc" (in memory) snapshots of log lines only:
+///
+/// The idea here is to see which log message templates occurred in between the snapshots.
+pub async fn adhoc_log_templates_diff(
+    hosts: Vec<&'static str>,
+    ports: Vec<&'static str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    info!("ad-hoc log templates diff first snapshot begin");
+    let timer = Instant::now();
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let stat_name_filter = utility::set_regex(&options.stat_name_match);
+
+    let first_snapshot_time = Local::now();
+
+    let loglines = Arc::new(Mutex::new(loglines::LogTemplatesDiff::new()));
+
+    let hosts = Arc::new(hosts);
+    let ports = Arc::new(ports);
+
+    let mut handles = vec![];
+
+    let clone_loglines = loglines.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let ysql_log_line_prefix = options.ysql_log_line_prefix.clone();
+    let handle = tokio::spawn(async move {
+        clone_loglines.lock().await.adhoc_read_first_snapshot(&clone_hosts, &clone_ports, parallel, &ysql_log_line_prefix).await;
+    });
+    handles.push(handle);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    info!("ad-hoc log templates diff first snapshot end: {:?}", timer.elapsed());
+
+    println!("Begin ad-hoc in-memory snapshot created, press enter to create end snapshot for difference calculation.");
+    let mut input = String::new();
+    stdin().read_line(&mut input).expect("failed");
+
+    info!("ad-hoc log templates diff second snapshot begin");
+    let timer = Instant::now();
+
+    let second_snapshot_time = Local::now();
+
+    let mut handles = vec![];
+
+    let clone_loglines = loglines.clone();
+    let clone_hosts = hosts.clone();
+    let clone_ports = ports.clone();
+    let ysql_log_line_prefix = options.ysql_log_line_prefix.clone();
+    let handle = tokio::spawn(async move {
+        clone_loglines.lock().await.adhoc_read_second_snapshot(&clone_hosts, &clone_ports, parallel, &ysql_log_line_prefix).await;
+    });
+    handles.push(handle);
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    info!("ad-hoc log templates diff second snapshot end: {:?}", timer.elapsed());
+
+    println!("Time between snapshots: {:8.3} seconds", (second_snapshot_time - first_snapshot_time).num_milliseconds() as f64 / 1000_f64);
+    loglines.lock().await.print(&hostname_filter, &stat_name_filter, &options.log_severity);
+
+    Ok(())
+}
+
 pub async fn adhoc_nonmetrics_diff(
     hosts: Vec<&'static str>,
     ports: Vec<&'static str>,
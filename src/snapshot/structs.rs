@@ -1,9 +1,21 @@
 use chrono::{DateTime, Local};
 /// Struct to represent the snapshots metadata in yb_stats in a vector as well as on disk as CSV using serde.
 /// The comment can be empty, unless a snapshot is made with the `--snapshot-comment` flag and a comment.
+/// The snapshot type is absent in a snapshot.index written by an older version, which are all regular snapshots.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Snapshot {
     pub number: i32,
     pub timestamp: DateTime<Local>,
     pub comment: String,
+    #[serde(default)]
+    pub snapshot_type: SnapshotType,
+}
+/// A regular snapshot contains the data of `--snapshot`.
+/// A profile snapshot only contains a profile (`--threads-profile`, `--pprof-profile`),
+/// and is skipped by the reports that read all the snapshots in a range.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotType {
+    #[default]
+    Regular,
+    Profile,
 }
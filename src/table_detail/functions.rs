@@ -15,6 +15,10 @@ use crate::Opts;
 impl Table {
     pub fn new() -> Self { Default::default() }
 }
+impl TableBasic {
+    /// The on disk size of all replicas is shown as: `Total: 3.00M WAL Files: 3.00M SST Files: 0B SST Files Uncompressed: 0B`.
+    pub fn total_on_disk_size_bytes(&self) -> Option<u64> { utility::human_readable_to_bytes(self.on_disk_size.strip_prefix("Total: ").unwrap_or(&self.on_disk_size)) }
+}
 impl TableDetail {
     pub fn new() -> Self { Default::default() }
 }
@@ -673,6 +677,7 @@ mod tests {
         assert_eq!(result.tablebasic[0].ysql_oid, "16384");
         assert_eq!(result.tablebasic[0].hidden, "false");
         assert_eq!(result.tablebasic[0].on_disk_size, "Total: 3.00M WAL Files: 3.00M SST Files: 0B SST Files Uncompressed: 0B");
+        assert_eq!(result.tablebasic[0].total_on_disk_size_bytes(), Some(3145728));
         assert_eq!(result.tablebasic[0].object_type, "User tables");
         assert_eq!(result.tablebasic[1].keyspace, "template1");
         assert_eq!(result.tablebasic[1].table_name, "pg_user_mapping_user_server_index");
@@ -13,6 +13,10 @@ use crate::Opts;
 impl Tablet {
     pub fn new() -> Self { Default::default() }
 }
+impl TabletBasic {
+    /// The on disk size is shown as: `Total: 1.00M Consensus Metadata: 1.5K WAL Files: 1.00M SST Files: 0B SST Files Uncompressed: 0B`.
+    pub fn total_on_disk_size_bytes(&self) -> Option<u64> { utility::human_readable_to_bytes(self.on_disk_size.strip_prefix("Total: ").unwrap_or(&self.on_disk_size)) }
+}
 impl TabletDetail {
     pub fn new() -> Self { Default::default() }
 }
@@ -702,6 +706,7 @@ mod tests {
         assert_eq!(result.tabletbasic[0].state, "RUNNING");
         assert_eq!(result.tabletbasic[0].hidden, "false");
         assert_eq!(result.tabletbasic[0].on_disk_size, "Total: 1.00M Consensus Metadata: 1.5K WAL Files: 1.00M SST Files: 0B SST Files Uncompressed: 0B");
+        assert_eq!(result.tabletbasic[0].total_on_disk_size_bytes(), Some(1048576));
         assert_eq!(result.tabletbasic[0].raftconfig, "LEADER: yb-2.local FOLLOWER: yb-1.local FOLLOWER: yb-3.local");
         assert_eq!(result.tabletbasic[0].last_status, "transactions0");
     }
@@ -1,16 +1,44 @@
 //! The impls and functions
 //!
 use chrono::Local;
-use std::{sync::mpsc::channel, time::Instant};
+use std::{collections::BTreeMap, sync::mpsc::channel, time::{Duration, Instant}};
 use regex::Regex;
 use scraper::{Html, Selector};
 use log::*;
 use anyhow::Result;
 use crate::utility;
 use crate::snapshot;
-use crate::threads::{Threads, AllThreads};
+use crate::threads::{Threads, AllThreads, ThreadsProfile, ThreadsDiff, ThreadsDiffFields, ThreadPoolDiff};
 use crate::Opts;
 
+/// The number of stacks per host that is printed without details_enable.
+const TOP_STACKS: usize = 10;
+
+/// Parse the cumulative CPU time as shown in `/threadz`, such as `2.960s`, into seconds.
+fn parse_cpu_seconds(
+    cpu_time: &str,
+) -> f64
+{
+    cpu_time.trim().trim_end_matches('s').parse::<f64>().unwrap_or_default()
+}
+
+/// Roll up a thread name into the name of its thread pool.
+///
+/// Thread names are suffixed with the thread id, and threads in a pool are numbered.
+/// For example: `rpc_tp_TabletServer_11-24567` is part of the pool `rpc_tp_TabletServer`,
+/// `Master_reactorx-6127` is part of `Master_reactorx` and `rocksdb:low0-1234` is part of `rocksdb:low`.
+pub fn thread_pool_name(
+    thread_name: &str,
+) -> String
+{
+    let regular_expression = Regex::new(r"^(.*?)[-_]?[0-9]*(-[0-9]+)?$").unwrap();
+    match regular_expression.captures(thread_name.trim())
+    {
+        Some(captures) if !captures[1].is_empty() => captures[1].to_string(),
+        _ => thread_name.trim().to_string(),
+    }
+}
+
 impl AllThreads {
     pub fn new() -> Self { Default::default() }
     pub async fn perform_snapshot(
@@ -214,6 +242,365 @@ impl AllThreads {
     }
 }
 
+impl ThreadsProfile {
+    pub fn new() -> Self { Default::default() }
+    /// Sample `/threadz` for the duration with the interval in between the samples.
+    pub async fn sample(
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+        duration: Duration,
+        interval: Duration,
+    ) -> ThreadsProfile
+    {
+        info!("begin sampling threads");
+        let timer = Instant::now();
+
+        let mut threadsprofile = ThreadsProfile::new();
+        while threadsprofile.samples == 0
+            || timer.elapsed() < duration
+        {
+            let allthreads = AllThreads::read_threads(hosts, ports, parallel).await;
+            threadsprofile.add_sample(&allthreads);
+            tokio::time::sleep(interval).await;
+        }
+
+        info!("end sampling threads: {} samples, {:?}", threadsprofile.samples, timer.elapsed());
+        threadsprofile
+    }
+    fn add_sample(
+        &mut self,
+        allthreads: &AllThreads,
+    )
+    {
+        self.samples += 1;
+        for thread in &allthreads.threads
+        {
+            *self.stack_samples
+                .entry((thread.hostname_port.clone(), thread_pool_name(&thread.thread_name), thread.stack.clone()))
+                .or_default() += 1;
+        }
+    }
+    /// Produce the samples in folded stack format: `hostname_port;pool;frame;frame count`.
+    pub fn folded_stacks(
+        &self,
+    ) -> Vec<String>
+    {
+        self.stack_samples
+            .iter()
+            .map(|((hostname_port, pool, stack), count)| {
+                if stack.is_empty()
+                {
+                    format!("{};{} {}", hostname_port, pool, count)
+                }
+                else
+                {
+                    format!("{};{};{} {}", hostname_port, pool, stack, count)
+                }
+            })
+            .collect()
+    }
+    /// Print the most sampled stacks per hostname_port.
+    /// Without details_enable, the top [TOP_STACKS] stacks are printed.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        let mut previous_hostname_port = String::new();
+        let mut stacks: Vec<_> = self.stack_samples
+            .iter()
+            .filter(|((hostname_port, _, _), _)| hostname_filter.is_match(hostname_port))
+            .collect();
+        // order by hostname_port, and then by number of samples, highest first.
+        stacks.sort_by(|((a_hostname_port, _, _), a_count), ((b_hostname_port, _, _), b_count)| a_hostname_port.cmp(b_hostname_port).then(b_count.cmp(a_count)));
+        let mut printed = 0;
+        for ((hostname_port, pool, stack), count) in stacks
+        {
+            if *hostname_port != previous_hostname_port
+            {
+                println!("{}", "-".repeat(120));
+                println!("Host: {}, samples: {}", hostname_port, self.samples);
+                println!("{}", "-".repeat(120));
+                previous_hostname_port = hostname_port.to_string();
+                printed = 0;
+            }
+            if printed >= TOP_STACKS && !*details_enable
+            {
+                continue;
+            }
+            println!("{:>8} {:30} {}", count, pool, stack);
+            printed += 1;
+        }
+    }
+}
+
+impl ThreadsDiff {
+    pub fn new() -> Self { Default::default() }
+    pub fn snapshot_diff(
+        begin_snapshot: &String,
+        end_snapshot: &String,
+    ) -> Result<ThreadsDiff>
+    {
+        let mut threadsdiff = ThreadsDiff::new();
+
+        let mut allthreads = AllThreads::new();
+        allthreads.threads = snapshot::read_snapshot_json(begin_snapshot, "threads")?;
+        threadsdiff.first_snapshot(allthreads);
+
+        let mut allthreads = AllThreads::new();
+        allthreads.threads = snapshot::read_snapshot_json(end_snapshot, "threads")?;
+        threadsdiff.second_snapshot(allthreads);
+
+        Ok(threadsdiff)
+    }
+    fn first_snapshot(
+        &mut self,
+        allthreads: AllThreads,
+    )
+    {
+        for thread in allthreads.threads
+        {
+            self.btreethreadsdiff
+                .entry((thread.hostname_port.clone(), thread.thread_name.clone()))
+                .and_modify(|_| error!("Duplicate hostname:port + thread name entry: {}, {}", thread.hostname_port, thread.thread_name))
+                .or_insert(ThreadsDiffFields {
+                    first_timestamp: Some(thread.timestamp),
+                    first_user_cpu_s: parse_cpu_seconds(&thread.cumulative_user_cpu_s),
+                    first_kernel_cpu_s: parse_cpu_seconds(&thread.cumulative_kernel_cpu_s),
+                    first_iowait_cpu_s: parse_cpu_seconds(&thread.cumulative_iowait_cpu_s),
+                    ..Default::default()
+                });
+        }
+    }
+    fn second_snapshot(
+        &mut self,
+        allthreads: AllThreads,
+    )
+    {
+        for thread in allthreads.threads
+        {
+            self.btreethreadsdiff
+                .entry((thread.hostname_port.clone(), thread.thread_name.clone()))
+                .and_modify(|threadsdifffields| {
+                    threadsdifffields.second_timestamp = Some(thread.timestamp);
+                    threadsdifffields.second_user_cpu_s = parse_cpu_seconds(&thread.cumulative_user_cpu_s);
+                    threadsdifffields.second_kernel_cpu_s = parse_cpu_seconds(&thread.cumulative_kernel_cpu_s);
+                    threadsdifffields.second_iowait_cpu_s = parse_cpu_seconds(&thread.cumulative_iowait_cpu_s);
+                })
+                .or_insert(ThreadsDiffFields {
+                    second_timestamp: Some(thread.timestamp),
+                    second_user_cpu_s: parse_cpu_seconds(&thread.cumulative_user_cpu_s),
+                    second_kernel_cpu_s: parse_cpu_seconds(&thread.cumulative_kernel_cpu_s),
+                    second_iowait_cpu_s: parse_cpu_seconds(&thread.cumulative_iowait_cpu_s),
+                    ..Default::default()
+                });
+        }
+    }
+    /// Roll up the threads into thread pools per hostname_port.
+    ///
+    /// A thread that appeared used all its CPU time in between the snapshots.
+    /// A thread that disappeared is only counted in the first number of threads, its CPU time after the first snapshot is unknown.
+    pub fn thread_pools(
+        &self,
+    ) -> BTreeMap<(String, String), ThreadPoolDiff>
+    {
+        let mut thread_pools: BTreeMap<(String, String), ThreadPoolDiff> = BTreeMap::new();
+        for ((hostname_port, thread_name), row) in &self.btreethreadsdiff
+        {
+            let thread_pool = thread_pools
+                .entry((hostname_port.to_string(), thread_pool_name(thread_name)))
+                .or_default();
+            if row.first_timestamp.is_some()
+            {
+                thread_pool.first_threads += 1;
+            }
+            if row.second_timestamp.is_some()
+            {
+                thread_pool.second_threads += 1;
+                thread_pool.user_cpu_s += row.second_user_cpu_s - row.first_user_cpu_s;
+                thread_pool.kernel_cpu_s += row.second_kernel_cpu_s - row.first_kernel_cpu_s;
+                thread_pool.iowait_cpu_s += row.second_iowait_cpu_s - row.first_iowait_cpu_s;
+            }
+        }
+        thread_pools
+    }
+    /// The time in seconds between the first and second snapshot per hostname_port.
+    fn elapsed_seconds(
+        &self,
+    ) -> BTreeMap<String, f64>
+    {
+        let mut elapsed_seconds: BTreeMap<String, f64> = BTreeMap::new();
+        for ((hostname_port, _), row) in &self.btreethreadsdiff
+        {
+            if let (Some(first_timestamp), Some(second_timestamp)) = (row.first_timestamp, row.second_timestamp)
+            {
+                elapsed_seconds.insert(hostname_port.to_string(), (second_timestamp - first_timestamp).num_milliseconds() as f64 / 1000_f64);
+            }
+        }
+        elapsed_seconds
+    }
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        details_enable: &bool,
+    )
+    {
+        let elapsed_seconds = self.elapsed_seconds();
+        let mut thread_pools: Vec<_> = self.thread_pools()
+            .into_iter()
+            .filter(|((hostname_port, _), _)| hostname_filter.is_match(hostname_port))
+            .collect();
+        // order by hostname_port, and then by CPU time, highest first.
+        thread_pools.sort_by(|((a_hostname_port, _), a), ((b_hostname_port, _), b)| {
+            a_hostname_port.cmp(b_hostname_port)
+                .then((b.user_cpu_s + b.kernel_cpu_s).total_cmp(&(a.user_cpu_s + a.kernel_cpu_s)))
+        });
+        let mut previous_hostname_port = String::new();
+        for ((hostname_port, pool), row) in &thread_pools
+        {
+            let cpu_s = row.user_cpu_s + row.kernel_cpu_s;
+            if cpu_s <= 0_f64
+                && row.first_threads == row.second_threads
+            {
+                continue;
+            }
+            let elapsed = elapsed_seconds.get(hostname_port).copied().unwrap_or_default();
+            if *hostname_port != previous_hostname_port
+            {
+                println!("{}", "-".repeat(120));
+                println!("Host: {}, time between snapshots: {:8.3} seconds", hostname_port, elapsed);
+                println!("{}", "-".repeat(120));
+                println!("{:40} {:>15} {:>10} {:>10} {:>10} {:>8}", "thread pool", "threads", "user_s", "kernel_s", "iowait_s", "cpu%");
+                previous_hostname_port = hostname_port.to_string();
+            }
+            let threads = if row.first_threads == row.second_threads
+            {
+                row.second_threads.to_string()
+            }
+            else
+            {
+                format!("{}->{} ({:+})", row.first_threads, row.second_threads, row.second_threads as i64 - row.first_threads as i64)
+            };
+            println!("{:40} {:>15} {:10.3} {:10.3} {:10.3} {:8.1}",
+                     pool,
+                     threads,
+                     row.user_cpu_s,
+                     row.kernel_cpu_s,
+                     row.iowait_cpu_s,
+                     if elapsed > 0_f64 { cpu_s / elapsed * 100_f64 } else { 0_f64 },
+            );
+            if *details_enable
+            {
+                self.print_threads(hostname_port, pool, elapsed);
+            }
+        }
+    }
+    /// Print the individual threads for a thread pool, highest CPU time first.
+    fn print_threads(
+        &self,
+        hostname_port: &str,
+        pool: &str,
+        elapsed: f64,
+    )
+    {
+        let mut threads: Vec<_> = self.btreethreadsdiff
+            .iter()
+            .filter(|((thread_hostname_port, thread_name), _)| thread_hostname_port == hostname_port && thread_pool_name(thread_name) == pool)
+            .collect();
+        threads.sort_by(|(_, a), (_, b)| {
+            (b.second_user_cpu_s + b.second_kernel_cpu_s - b.first_user_cpu_s - b.first_kernel_cpu_s)
+                .total_cmp(&(a.second_user_cpu_s + a.second_kernel_cpu_s - a.first_user_cpu_s - a.first_kernel_cpu_s))
+        });
+        for ((_, thread_name), row) in threads
+        {
+            if row.second_timestamp.is_none()
+            {
+                println!("  {:38} {:>15}", thread_name, "disappeared");
+                continue;
+            }
+            let cpu_s = row.second_user_cpu_s + row.second_kernel_cpu_s - row.first_user_cpu_s - row.first_kernel_cpu_s;
+            println!("  {:38} {:>15} {:10.3} {:10.3} {:10.3} {:8.1}",
+                     thread_name,
+                     if row.first_timestamp.is_none() { "appeared" } else { "" },
+                     row.second_user_cpu_s - row.first_user_cpu_s,
+                     row.second_kernel_cpu_s - row.first_kernel_cpu_s,
+                     row.second_iowait_cpu_s - row.first_iowait_cpu_s,
+                     if elapsed > 0_f64 { cpu_s / elapsed * 100_f64 } else { 0_f64 },
+            );
+        }
+    }
+    pub async fn adhoc_read_first_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    )
+    {
+        let allthreads = AllThreads::read_threads(hosts, ports, parallel).await;
+        self.first_snapshot(allthreads);
+    }
+    pub async fn adhoc_read_second_snapshot(
+        &mut self,
+        hosts: &Vec<&str>,
+        ports: &Vec<&str>,
+        parallel: usize,
+    )
+    {
+        let allthreads = AllThreads::read_threads(hosts, ports, parallel).await;
+        self.second_snapshot(allthreads);
+    }
+}
+
+pub async fn threads_diff(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+
+    let threadsdiff = ThreadsDiff::snapshot_diff(&begin_snapshot, &end_snapshot)?;
+    threadsdiff.print(&hostname_filter, &options.details_enable);
+
+    Ok(())
+}
+
+/// Sample `/threadz`, and write the folded stacks and the flamegraph into a new snapshot.
+pub async fn threads_profile(
+    hosts: Vec<&str>,
+    ports: Vec<&str>,
+    parallel: usize,
+    options: &Opts,
+) -> Result<()>
+{
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+
+    let threadsprofile = ThreadsProfile::sample(
+        &hosts,
+        &ports,
+        parallel,
+        Duration::from_secs(options.profile_duration),
+        Duration::from_millis(options.threads_profile_interval),
+    ).await;
+
+    let snapshot_comment = Some(options.snapshot_comment.clone().unwrap_or_else(|| "threads profile".to_string()));
+    let snapshot_number = snapshot::Snapshot::insert_new_profile_snapshot_number(&snapshot_comment)?;
+    snapshot::save_snapshot_flamegraph(snapshot_number, "threads_profile", "Threads profile", &threadsprofile.folded_stacks())?;
+
+    threadsprofile.print(&hostname_filter, &options.details_enable);
+    if !options.silent {
+        println!("snapshot number {}", snapshot_number);
+    }
+    Ok(())
+}
+
 pub async fn print_threads(
     hosts: Vec<&str>,
     ports: Vec<&str>,
@@ -379,6 +766,64 @@ server uuid 4ce571a18f8c4a9a8b35246222d12025 local time 2022-03-16 12:33:37.6344
         assert_eq!(result[0].stack, "__clone;start_thread;yb::Thread::SuperviseThread();yb::rpc::Reactor::RunThread();ev_run;epoll_poll;__GI_epoll_wait");
     }
 
+    #[test]
+    fn unit_thread_pool_name() {
+        assert_eq!(thread_pool_name("Master_reactorx-6127"), "Master_reactorx");
+        assert_eq!(thread_pool_name("rpc_tp_TabletServer_11-24567"), "rpc_tp_TabletServer");
+        assert_eq!(thread_pool_name("rocksdb:low0-1234"), "rocksdb:low");
+        assert_eq!(thread_pool_name("maintenance_scheduler-3071"), "maintenance_scheduler");
+        assert_eq!(thread_pool_name("12345"), "12345");
+    }
+
+    #[test]
+    fn unit_threads_profile_folded_stacks() {
+        let mut allthreads = AllThreads::new();
+        for thread_name in ["rpc_tp_TabletServer_1-101", "rpc_tp_TabletServer_2-102"] {
+            allthreads.threads.push(Threads {
+                hostname_port: "192.168.66.80:9000".to_string(),
+                thread_name: thread_name.to_string(),
+                stack: "__clone;start_thread;yb::Thread::SuperviseThread()".to_string(),
+                ..Default::default()
+            });
+        }
+        let mut threadsprofile = ThreadsProfile::new();
+        threadsprofile.add_sample(&allthreads);
+        threadsprofile.add_sample(&allthreads);
+        assert_eq!(threadsprofile.samples, 2);
+        assert_eq!(threadsprofile.folded_stacks(), vec!["192.168.66.80:9000;rpc_tp_TabletServer;__clone;start_thread;yb::Thread::SuperviseThread() 4"]);
+    }
+
+    #[test]
+    fn unit_threads_diff_thread_pools() {
+        let first_timestamp = Local::now();
+        let second_timestamp = first_timestamp + chrono::Duration::seconds(10);
+        let thread = |timestamp, thread_name: &str, user_cpu_s: &str| Threads {
+            hostname_port: "192.168.66.80:9000".to_string(),
+            timestamp,
+            thread_name: thread_name.to_string(),
+            cumulative_user_cpu_s: user_cpu_s.to_string(),
+            cumulative_kernel_cpu_s: "0.000s".to_string(),
+            cumulative_iowait_cpu_s: "0.000s".to_string(),
+            ..Default::default()
+        };
+        let mut threadsdiff = ThreadsDiff::new();
+        let mut allthreads = AllThreads::new();
+        allthreads.threads.push(thread(first_timestamp, "rpc_tp_TabletServer_1-101", "1.000s"));
+        allthreads.threads.push(thread(first_timestamp, "rpc_tp_TabletServer_2-102", "2.000s"));
+        threadsdiff.first_snapshot(allthreads);
+        let mut allthreads = AllThreads::new();
+        allthreads.threads.push(thread(second_timestamp, "rpc_tp_TabletServer_1-101", "3.500s"));
+        allthreads.threads.push(thread(second_timestamp, "rpc_tp_TabletServer_3-103", "0.500s"));
+        threadsdiff.second_snapshot(allthreads);
+
+        let thread_pools = threadsdiff.thread_pools();
+        let pool = &thread_pools[&("192.168.66.80:9000".to_string(), "rpc_tp_TabletServer".to_string())];
+        assert_eq!(pool.first_threads, 2);
+        assert_eq!(pool.second_threads, 2);
+        assert!((pool.user_cpu_s - 3.0).abs() < 0.0001);
+        assert_eq!(threadsdiff.elapsed_seconds()["192.168.66.80:9000"], 10.0);
+    }
+
     #[tokio::test]
     async fn integration_parse_threadsdata_master() {
         let hostname = utility::get_hostname_master();
@@ -9,6 +9,10 @@
 //! - the masters, default port 7000
 //! - the tablet servers, default port 9000
 //!
+//! Besides a single read of `/threadz`, the threads module can sample `/threadz` repeatedly (`--threads-profile`),
+//! and aggregates identical stacks per host and thread pool.
+//! The result is saved in a new snapshot as `threads_profile.folded` (folded stack format) and `threads_profile.svg` (flamegraph).
+//!
 mod structs;
 mod functions;
 
@@ -1,5 +1,6 @@
 //! The structs
 //!
+use std::collections::BTreeMap;
 use chrono::{DateTime, Local};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -16,4 +17,48 @@ pub struct Threads {
 #[derive(Debug, Default)]
 pub struct AllThreads {
     pub threads: Vec<Threads>,
-}
\ No newline at end of file
+}
+/// BTreeMap for counting samples per hostname_port, thread pool and stack.
+type BTreeStackSamples = BTreeMap<(String, String, String), usize>;
+/// The result of sampling `/threadz` repeatedly ("poor man's profiler").
+///
+/// Every sample adds one to the count of the stack of every thread.
+/// Threads are aggregated into their thread pool, see [crate::threads::thread_pool_name].
+#[derive(Debug, Default)]
+pub struct ThreadsProfile {
+    pub samples: usize,
+    pub stack_samples: BTreeStackSamples,
+}
+// diff
+/// BTreeMap for storing a threads diff struct per hostname_port, thread_name
+type BTreeThreadsDiff = BTreeMap<(String, String), ThreadsDiffFields>;
+/// Wrapper struct for holding the btreemap
+#[derive(Debug, Default)]
+pub struct ThreadsDiff {
+    pub btreethreadsdiff: BTreeThreadsDiff,
+}
+/// The threads diff struct.
+///
+/// The hostname:port and thread name is the key of the btreemap,
+/// This struct holds the first and second timestamp and cumulative CPU seconds.
+/// A thread that is only found in the first snapshot has disappeared, a thread that is only found in the second snapshot has appeared.
+#[derive(Debug, Default)]
+pub struct ThreadsDiffFields {
+    pub first_timestamp: Option<DateTime<Local>>,
+    pub first_user_cpu_s: f64,
+    pub first_kernel_cpu_s: f64,
+    pub first_iowait_cpu_s: f64,
+    pub second_timestamp: Option<DateTime<Local>>,
+    pub second_user_cpu_s: f64,
+    pub second_kernel_cpu_s: f64,
+    pub second_iowait_cpu_s: f64,
+}
+/// The CPU usage of the threads in a thread pool between two snapshots.
+#[derive(Debug, Default)]
+pub struct ThreadPoolDiff {
+    pub first_threads: usize,
+    pub second_threads: usize,
+    pub user_cpu_s: f64,
+    pub kernel_cpu_s: f64,
+    pub iowait_cpu_s: f64,
+}
@@ -0,0 +1,448 @@
+//! The impls and functions
+//!
+use std::{collections::{BTreeMap, BTreeSet}, sync::OnceLock};
+use chrono::{DateTime, Duration, Local, Utc};
+use regex::Regex;
+use colored::*;
+use log::*;
+use anyhow::Result;
+use crate::utility;
+use crate::snapshot;
+use crate::entities::AllEntities;
+use crate::isleader::AllIsLeader;
+use crate::loglines::{AllLogLines, LogLine};
+use crate::tasks::AllTasks;
+use crate::timeline::{ClusterTimeline, EventType, TimelineEvent};
+use crate::Opts;
+
+/// The tablet id of the sys catalog tablet of the masters: a leader election of this tablet is a master failover.
+const SYS_CATALOG_TABLET_ID: &str = "00000000000000000000000000000000";
+
+/// The known log messages and the event type they are turned into.
+/// The regexes are anchored to the start of the message (after the raft prefix), so a message that only mentions an event is not an event.
+/// A named group 'term', 'peer' or 'reason' in the regex is used for the event.
+fn log_event_regexes() -> Vec<(EventType, Regex)>
+{
+    [
+        (EventType::LeaderElection, r"^Leader election won for term (?P<term>\d+)$"),
+        (EventType::LeaderElection, r"^Leader election lost for term (?P<term>\d+)\. Reason: (?P<reason>.*)$"),
+        (EventType::LeaderStepDown, r"^Stepping down as leader of term (?P<term>\d+)"),
+        (EventType::LeaderStepDown, r"^Transferring leadership to (?P<peer>[0-9a-f]{32})"),
+        (EventType::TabletSplit, r"^(?:Starting tablet split|Processing split tablet request|Tablet split done)\b"),
+        (EventType::RemoteBootstrap, r"^(?:Beginning (?:new )?remote bootstrap session|Remote bootstrap complete)\b"),
+    ]
+        .iter()
+        .map(|(event_type, regex)| (*event_type, Regex::new(regex).unwrap()))
+        .collect()
+}
+
+/// The prefix of the raft log lines: the tablet, the peer, and optionally the term and role.
+fn raft_prefix_regex() -> Regex
+{
+    Regex::new(r"^T (?P<tablet>[0-9a-f]{32}) P (?P<peer>[0-9a-f]{32})(?: \[term (?P<term>\d+) [A-Z_]+\])?: (?P<message>.*)").unwrap()
+}
+
+/// Turn a log line into an event, if the log message is a known message.
+/// The raft log lines start with the tablet, the peer, and optionally the term and role:
+/// `T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42 [term 3 FOLLOWER]: Leader election won for term 4`
+pub fn log_event(
+    logline: &LogLine,
+    raft_prefix: &Regex,
+    log_event_regexes: &[(EventType, Regex)],
+) -> Option<TimelineEvent>
+{
+    let message = logline.message.lines().next().unwrap_or_default().trim();
+    let (tablet, peer, term, message) = match raft_prefix.captures(message)
+    {
+        Some(captures) => (
+            captures.name("tablet").map(|m| m.as_str().to_string()),
+            captures.name("peer").map(|m| m.as_str().to_string()),
+            captures.name("term").and_then(|m| m.as_str().parse().ok()),
+            captures.name("message").map(|m| m.as_str()).unwrap_or_default(),
+        ),
+        None => (None, None, None, message),
+    };
+    let (event_type, captures) = log_event_regexes
+        .iter()
+        .find_map(|(event_type, regex)| regex.captures(message).map(|captures| (*event_type, captures)))?;
+    static TABLET_ID: OnceLock<Regex> = OnceLock::new();
+    // a log line without the raft prefix, such as a tablet split on the master, can still mention the tablet.
+    let tablet = tablet.or_else(|| TABLET_ID.get_or_init(|| Regex::new(r"tablet[^0-9a-f]{0,10}([0-9a-f]{32})").unwrap())
+        .captures(message)
+        .map(|captures| captures[1].to_string()));
+
+    let event_type = if event_type == EventType::LeaderElection
+        && tablet.as_deref() == Some(SYS_CATALOG_TABLET_ID)
+        && message.starts_with("Leader election won")
+    {
+        EventType::MasterFailover
+    } else {
+        event_type
+    };
+
+    Some(TimelineEvent {
+        timestamp: logline.timestamp,
+        event_type,
+        source: logline.hostname_port.clone().unwrap_or_default(),
+        table: None,
+        tablet,
+        peer: captures.name("peer").map(|m| m.as_str().to_string()).or(peer),
+        term: captures.name("term").and_then(|m| m.as_str().parse().ok()).or(term),
+        reason: captures.name("reason").map(|m| m.as_str()).unwrap_or(message).to_string(),
+    })
+}
+
+/// The start time of a master task, which `/tasks` shows relative to the time it was read: `7.77 s ago`.
+pub fn task_start_time(
+    read_time: DateTime<Utc>,
+    start_time: &str,
+) -> Option<DateTime<Utc>>
+{
+    static START_TIME: OnceLock<Regex> = OnceLock::new();
+    let captures = START_TIME.get_or_init(|| Regex::new(r"^([0-9.]+) (ms|s|min|h|d) ago$").unwrap()).captures(start_time.trim())?;
+    let amount: f64 = captures[1].parse().ok()?;
+    let milliseconds = match &captures[2]
+    {
+        "ms" => amount,
+        "s" => amount * 1_000.,
+        "min" => amount * 60_000.,
+        "h" => amount * 3_600_000.,
+        _ => amount * 86_400_000.,
+    };
+    Some(read_time - Duration::milliseconds(milliseconds as i64))
+}
+
+impl ClusterTimeline {
+    pub fn new() -> Self { Default::default() }
+    /// Add the events from known log messages.
+    /// The same log line in multiple snapshots or the log archive is only used once.
+    pub fn add_log_events(
+        &mut self,
+        allloglines: &AllLogLines,
+    )
+    {
+        let raft_prefix = raft_prefix_regex();
+        let log_event_regexes = log_event_regexes();
+        let mut seen: BTreeSet<(DateTime<Utc>, &String, &String, &String)> = BTreeSet::new();
+
+        for logline in allloglines.loglines
+            .iter()
+            .filter(|logline| logline.ysql.is_none())
+        {
+            if !seen.insert((logline.timestamp, logline.hostname_port.as_ref().expect("no hostname:port set"), &logline.sourcefile_nr, &logline.message))
+            {
+                continue;
+            }
+            if let Some(event) = log_event(logline, &raft_prefix, &log_event_regexes)
+            {
+                self.events.push(event);
+            }
+        }
+    }
+    /// Add the tablet to table name mapping, and the tablet leader changes between two snapshots.
+    pub fn add_leader_changes(
+        &mut self,
+        second_snapshot: &str,
+        timestamp: DateTime<Utc>,
+        first: &AllEntities,
+        second: &AllEntities,
+    )
+    {
+        let tablet_leaders = |allentities: &AllEntities| -> BTreeMap<String, String> {
+            allentities.entities
+                .iter()
+                .flat_map(|entities| entities.tablets.iter())
+                .filter_map(|tablet| tablet.leader.as_ref().filter(|leader| !leader.is_empty()).map(|leader| (tablet.tablet_id.clone(), leader.clone())))
+                .collect()
+        };
+        for entities in &second.entities
+        {
+            let table_names: BTreeMap<&String, &String> = entities.tables.iter().map(|table| (&table.table_id, &table.table_name)).collect();
+            for tablet in &entities.tablets
+            {
+                if let Some(table_name) = table_names.get(&tablet.table_id)
+                {
+                    self.tablet_tables.insert(tablet.tablet_id.clone(), table_name.to_string());
+                }
+            }
+        }
+
+        let first_leaders = tablet_leaders(first);
+        for (tablet_id, second_leader) in tablet_leaders(second)
+        {
+            match first_leaders.get(&tablet_id)
+            {
+                Some(first_leader) if *first_leader != second_leader => {
+                    self.events.push(TimelineEvent {
+                        timestamp,
+                        event_type: EventType::LeaderChange,
+                        source: format!("snapshot {}", second_snapshot),
+                        table: None,
+                        tablet: Some(tablet_id.clone()),
+                        peer: Some(second_leader.clone()),
+                        term: None,
+                        reason: format!("leader changed from {} to {}", first_leader, second_leader),
+                    });
+                },
+                _ => {},
+            }
+        }
+    }
+    /// Add the master leader change between two snapshots.
+    pub fn add_master_leader_change(
+        &mut self,
+        second_snapshot: &str,
+        timestamp: DateTime<Utc>,
+        first_leader: &str,
+        second_leader: &str,
+    )
+    {
+        if !first_leader.is_empty()
+            && !second_leader.is_empty()
+            && first_leader != second_leader
+        {
+            self.events.push(TimelineEvent {
+                timestamp,
+                event_type: EventType::MasterFailover,
+                source: format!("snapshot {}", second_snapshot),
+                table: None,
+                tablet: None,
+                peer: None,
+                term: None,
+                reason: format!("master leader changed from {} to {}", first_leader, second_leader),
+            });
+        }
+    }
+    /// Add the master tasks. A task that is in multiple snapshots is only added once.
+    pub fn add_master_tasks(
+        &mut self,
+        alltasks: &AllTasks,
+        snapshot_timestamp: DateTime<Utc>,
+        seen_tasks: &mut BTreeSet<(String, String)>,
+    )
+    {
+        // DeleteTablet RPC for tablet d7dac11732064102b56bbe90edb3230a (t [id=000033e8000030008000000000005403]) on TS=214398b20f3b432ebbe4d1e82a395bd5
+        let task_tablet = Regex::new(r"tablet ([0-9a-f]{32})").unwrap();
+        let task_table = Regex::new(r"\((\S+) \[id=[0-9a-f]{32}\]\)").unwrap();
+
+        for tasks in &alltasks.tasks
+        {
+            let read_time = tasks.timestamp.map(|timestamp| timestamp.with_timezone(&Utc)).unwrap_or(snapshot_timestamp);
+            for task in tasks.tasks.iter().flatten()
+            {
+                if !seen_tasks.insert((task.name.clone(), task.description.clone()))
+                {
+                    continue;
+                }
+                self.events.push(TimelineEvent {
+                    timestamp: task_start_time(read_time, &task.start_time).unwrap_or(read_time),
+                    event_type: EventType::MasterTask,
+                    source: tasks.hostname_port.clone().unwrap_or_default(),
+                    table: task_table.captures(&task.description).map(|captures| captures[1].to_string()),
+                    tablet: task_tablet.captures(&task.description).map(|captures| captures[1].to_string()),
+                    peer: None,
+                    term: None,
+                    reason: format!("{} {}: {}", task.name, task.state, task.description),
+                });
+            }
+        }
+    }
+    /// Read the events from the snapshots between begin and end, and the log archive.
+    /// Only the log events between the begin and end snapshot times are used.
+    pub fn read_snapshots(
+        begin_snapshot: &str,
+        end_snapshot: &str,
+    ) -> Result<ClusterTimeline>
+    {
+        let mut clustertimeline = ClusterTimeline::new();
+        let begin_timestamp = snapshot::Snapshot::read_snapshot_timestamp(begin_snapshot)?.with_timezone(&Utc);
+        let end_timestamp = snapshot::Snapshot::read_snapshot_timestamp(end_snapshot)?.with_timezone(&Utc);
+
+        let mut allloglines = AllLogLines::read_log_archive(begin_timestamp, end_timestamp, &utility::set_regex(&None), &utility::set_regex(&None), "IWEF")?;
+        let mut seen_tasks: BTreeSet<(String, String)> = BTreeSet::new();
+        let mut previous: Option<(AllEntities, String)> = None;
+
+        for snapshot_number in snapshot::Snapshot::read_snapshot_range(begin_snapshot, end_snapshot)?
+        {
+            info!("timeline: snapshot {}", snapshot_number);
+            let snapshot_timestamp: DateTime<Utc> = snapshot::Snapshot::read_snapshot_timestamp(&snapshot_number)?.with_timezone(&Utc);
+
+            // a snapshot can be taken without some data, such as a snapshot with imported log lines.
+            let loglines: Vec<LogLine> = snapshot::read_snapshot_json(&snapshot_number, "loglines").unwrap_or_default();
+            allloglines.loglines.extend(loglines.into_iter().filter(|logline| logline.timestamp >= begin_timestamp && logline.timestamp <= end_timestamp));
+
+            let mut alltasks = AllTasks::new();
+            alltasks.tasks = snapshot::read_snapshot_json(&snapshot_number, "tasks").unwrap_or_default();
+            clustertimeline.add_master_tasks(&alltasks, snapshot_timestamp, &mut seen_tasks);
+
+            let mut allentities = AllEntities::new();
+            allentities.entities = snapshot::read_snapshot_json(&snapshot_number, "entities").unwrap_or_default();
+            let master_leader = AllIsLeader::return_leader_snapshot(&snapshot_number).unwrap_or_default();
+            // only the master leader has the tablet leaders.
+            allentities.entities.retain(|entities| entities.hostname_port.as_deref() == Some(master_leader.as_str()));
+
+            match &previous
+            {
+                Some((previous_entities, previous_master_leader)) => {
+                    clustertimeline.add_leader_changes(&snapshot_number, snapshot_timestamp, previous_entities, &allentities);
+                    clustertimeline.add_master_leader_change(&snapshot_number, snapshot_timestamp, previous_master_leader, &master_leader);
+                },
+                None => clustertimeline.add_leader_changes(&snapshot_number, snapshot_timestamp, &AllEntities::new(), &allentities),
+            }
+            previous = Some((allentities, master_leader));
+        }
+        clustertimeline.add_log_events(&allloglines);
+
+        clustertimeline.events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.event_type.cmp(&b.event_type)));
+        Ok(clustertimeline)
+    }
+    /// The table name of an event, directly from the event, or by the tablet of the event.
+    fn table_name(
+        &self,
+        event: &TimelineEvent,
+    ) -> String
+    {
+        event.table.clone()
+            .or_else(|| event.tablet.as_ref().and_then(|tablet| self.tablet_tables.get(tablet).cloned()))
+            .unwrap_or_default()
+    }
+    /// Print the timeline.
+    /// The table_name_filter is matched with the table name and the tablet id of the event.
+    pub fn print(
+        &self,
+        hostname_filter: &Regex,
+        table_name_filter: &Regex,
+    )
+    {
+        println!("{:30} {:17} {:20} {:30} {:32} {:>5} reason", "timestamp", "event", "source", "table", "tablet", "term");
+        for event in &self.events
+        {
+            let table_name = self.table_name(event);
+            let tablet = event.tablet.clone().unwrap_or_default();
+            if !hostname_filter.is_match(&event.source)
+                || !(table_name_filter.is_match(&table_name) || table_name_filter.is_match(&tablet))
+            {
+                continue;
+            }
+            let event_type = match event.event_type
+            {
+                EventType::MasterFailover => event.event_type.to_string().red(),
+                EventType::LeaderElection | EventType::LeaderStepDown | EventType::LeaderChange => event.event_type.to_string().yellow(),
+                _ => event.event_type.to_string().normal(),
+            };
+            println!("{:30} {:17} {:20} {:30} {:32} {:>5} {}",
+                     event.timestamp.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S%.6f %z").to_string(),
+                     event_type,
+                     event.source,
+                     table_name,
+                     tablet,
+                     event.term.map(|term| term.to_string()).unwrap_or_default(),
+                     event.reason,
+            );
+        }
+    }
+}
+
+pub async fn cluster_timeline(
+    options: &Opts,
+) -> Result<()>
+{
+    if options.begin.is_none() || options.end.is_none() {
+        snapshot::Snapshot::print()?;
+    }
+    if options.snapshot_list { return Ok(()) };
+
+    let hostname_filter = utility::set_regex(&options.hostname_match);
+    let table_name_filter = utility::set_regex(&options.table_name_match);
+
+    let (begin_snapshot, end_snapshot, _begin_snapshot_row) = snapshot::Snapshot::read_begin_end_snapshot_from_user(options.begin, options.end)?;
+    let clustertimeline = ClusterTimeline::read_snapshots(&begin_snapshot, &end_snapshot)?;
+    clustertimeline.print(&hostname_filter, &table_name_filter);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::{Tasks, TaskDetail};
+
+    #[test]
+    fn unit_timeline_log_events() {
+        let logline = |hostname_port: &str, message: &str| LogLine {
+            hostname_port: Some(hostname_port.to_string()),
+            severity: "I".to_string(),
+            timestamp: "2023-02-03T11:13:22Z".parse().unwrap(),
+            sourcefile_nr: "raft_consensus.cc:1234".to_string(),
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let mut allloglines = AllLogLines::new();
+        allloglines.loglines.push(logline("yb-1.local:9000", "T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42 [term 3 FOLLOWER]: Leader election lost for term 4. Reason: could not achieve majority"));
+        // the same log line from another snapshot is only used once.
+        allloglines.loglines.push(logline("yb-1.local:9000", "T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42 [term 3 FOLLOWER]: Leader election lost for term 4. Reason: could not achieve majority"));
+        allloglines.loglines.push(logline("yb-1.local:7000", "T 00000000000000000000000000000000 P 6ab5bdd8d4d44e6e8b8f6f5a4e3b2c1d [term 7 FOLLOWER]: Leader election won for term 8"));
+        allloglines.loglines.push(logline("yb-1.local:9000", "Starting bootstrap"));
+
+        let mut clustertimeline = ClusterTimeline::new();
+        clustertimeline.add_log_events(&allloglines);
+        assert_eq!(clustertimeline.events.len(), 2);
+
+        // messages that mention an event, but are not the event.
+        let log_event_regexes = log_event_regexes();
+        let raft_prefix = raft_prefix_regex();
+        for message in [
+            "T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42 [term 3 FOLLOWER]: Ignoring vote request, Leader election won for term 4 by another peer",
+            "Not stepping down as leader: no eligible followers",
+            "Leader step down not needed",
+            "Tablet split is disabled for tablet 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c",
+            "Skipping tablet split, tablet 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c is too small",
+            "Flag remote_bootstrap_rate_limit_bytes_per_sec set to 268435456",
+            "Rejecting remote bootstrap request: tablet is already bootstrapping",
+        ] {
+            assert!(log_event(&logline("yb-1.local:9000", message), &raft_prefix, &log_event_regexes).is_none(), "{}", message);
+        }
+        for (message, event_type) in [
+            ("T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42 [term 4 LEADER]: Transferring leadership to 6ab5bdd8d4d44e6e8b8f6f5a4e3b2c1d", EventType::LeaderStepDown),
+            ("Starting tablet split: 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c", EventType::TabletSplit),
+            ("T 1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c P 05b8d17620eb4cd79eddaddb2fbcbb42: Beginning remote bootstrap session from remote peer at address yb-2.local:9100", EventType::RemoteBootstrap),
+        ] {
+            assert_eq!(log_event(&logline("yb-1.local:9000", message), &raft_prefix, &log_event_regexes).map(|event| event.event_type), Some(event_type), "{}", message);
+        }
+        assert_eq!(clustertimeline.events[0].event_type, EventType::LeaderElection);
+        assert_eq!(clustertimeline.events[0].tablet, Some("1d2e4bb8a4f84b2c9b6e2b6e4b5b6a7c".to_string()));
+        assert_eq!(clustertimeline.events[0].peer, Some("05b8d17620eb4cd79eddaddb2fbcbb42".to_string()));
+        assert_eq!(clustertimeline.events[0].term, Some(4));
+        assert_eq!(clustertimeline.events[0].reason, "could not achieve majority");
+        assert_eq!(clustertimeline.events[1].event_type, EventType::MasterFailover);
+        assert_eq!(clustertimeline.events[1].term, Some(8));
+    }
+
+    #[test]
+    fn unit_timeline_master_tasks() {
+        let mut alltasks = AllTasks::new();
+        alltasks.tasks.push(Tasks {
+            hostname_port: Some("yb-1.local:7000".to_string()),
+            timestamp: None,
+            tasks: vec![Some(TaskDetail {
+                task_type: "task".to_string(),
+                status: "done".to_string(),
+                name: "Delete Tablet".to_string(),
+                state: "kComplete".to_string(),
+                start_time: "7.77 s ago".to_string(),
+                duration: "41.3 ms".to_string(),
+                description: "DeleteTablet RPC for tablet d7dac11732064102b56bbe90edb3230a (t [id=000033e8000030008000000000005403]) on TS=214398b20f3b432ebbe4d1e82a395bd5".to_string(),
+            })],
+        });
+        let snapshot_timestamp: DateTime<Utc> = "2023-02-03T11:13:30Z".parse().unwrap();
+        let mut seen_tasks = BTreeSet::new();
+        let mut clustertimeline = ClusterTimeline::new();
+        clustertimeline.add_master_tasks(&alltasks, snapshot_timestamp, &mut seen_tasks);
+        // the same task in the next snapshot is only added once.
+        clustertimeline.add_master_tasks(&alltasks, snapshot_timestamp, &mut seen_tasks);
+        assert_eq!(clustertimeline.events.len(), 1);
+        assert_eq!(clustertimeline.events[0].timestamp.to_rfc3339(), "2023-02-03T11:13:22.230+00:00");
+        assert_eq!(clustertimeline.events[0].table, Some("t".to_string()));
+        assert_eq!(clustertimeline.events[0].tablet, Some("d7dac11732064102b56bbe90edb3230a".to_string()));
+    }
+}
@@ -0,0 +1,15 @@
+//! Module for creating a chronological cluster timeline of raft and tablet lifecycle events (`--cluster-timeline`).
+//!
+//! The events are taken from the snapshots between a begin and end snapshot:
+//! - known log messages in the loglines and the log archive (`--collect-log`): leader elections, step downs, splits, remote bootstraps and master failovers.
+//! - tablet leader and master leader changes between two snapshots.
+//! - the master tasks.
+//!
+//! The timeline functionality is called from:
+//! - [crate] -> [cluster_timeline]
+//!
+mod structs;
+mod functions;
+
+pub use structs::*;
+pub use functions::*;
@@ -0,0 +1,57 @@
+//! The structs
+//!
+use std::{fmt, collections::BTreeMap};
+use chrono::{DateTime, Utc};
+
+/// The type of a timeline event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventType {
+    /// A leader election that was won or lost, from the log.
+    LeaderElection,
+    /// A leader stepping down or transferring leadership, from the log.
+    LeaderStepDown,
+    /// A tablet split, from the log.
+    TabletSplit,
+    /// A remote bootstrap of a tablet peer, from the log.
+    RemoteBootstrap,
+    /// A master leader change, from the log (sys catalog leader election) or between snapshots (isleader).
+    MasterFailover,
+    /// A tablet leader that changed between snapshots (entities).
+    LeaderChange,
+    /// A master task.
+    MasterTask,
+}
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventType::LeaderElection => write!(f, "leader election"),
+            EventType::LeaderStepDown => write!(f, "leader step down"),
+            EventType::TabletSplit => write!(f, "tablet split"),
+            EventType::RemoteBootstrap => write!(f, "remote bootstrap"),
+            EventType::MasterFailover => write!(f, "master failover"),
+            EventType::LeaderChange => write!(f, "leader change"),
+            EventType::MasterTask => write!(f, "master task"),
+        }
+    }
+}
+/// A single event in the cluster timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    pub timestamp: DateTime<Utc>,
+    pub event_type: EventType,
+    /// The hostname:port of the log line or task, or the snapshot number for a change between snapshots.
+    pub source: String,
+    pub table: Option<String>,
+    pub tablet: Option<String>,
+    /// The server uuid of the tablet peer.
+    pub peer: Option<String>,
+    pub term: Option<u64>,
+    pub reason: String,
+}
+/// The cluster timeline: the events, and the tablet to table name mapping to resolve the table of an event.
+#[derive(Debug, Default)]
+pub struct ClusterTimeline {
+    pub events: Vec<TimelineEvent>,
+    /// tablet_id, table_name
+    pub tablet_tables: BTreeMap<String, String>,
+}
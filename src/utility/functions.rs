@@ -86,6 +86,86 @@ pub fn http_get(
     }
 }
 
+/// Returns the scheme (https or http) the http endpoint answers with, None if it answers with neither.
+/// The certificate is not validated, because only the scheme is determined.
+pub fn http_scheme(
+    host: &str,
+    port: &str,
+) -> Option<String>
+{
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_millis(200))
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    ["https", "http"]
+        .into_iter()
+        .find(|scheme| client.get(format!("{}://{}:{}/", scheme, host, port)).send().is_ok())
+        .map(|scheme| scheme.to_string())
+}
+
+/// Posts the body to the http endpoint as specified by the caller, and returns the result as String.
+pub fn http_post(
+    host: &str,
+    port: &str,
+    url: &str,
+    body: String,
+) -> String
+{
+    if let Ok(data_from_web_request) = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_millis(200))
+        .danger_accept_invalid_certs(ACCEPT_INVALID_CERTS)
+        .build()
+        .unwrap()
+        .post(format!("http://{}:{}/{}", host, port, url))
+        .body(body)
+        .send()
+    {
+        if ! &data_from_web_request.status().is_success()
+        {
+            debug!("Non success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+        }
+        else
+        {
+           debug!("Success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+        }
+        data_from_web_request.text().unwrap()
+    } else {
+        debug!("Non-Ok success response: {}:{}/{}", host, port, url);
+        String::new()
+    }
+}
+
+/// Perform a HTTP GET for binary data, such as a CPU profile.
+/// The timeout is the total time allowed for the request, which must include the time the server takes to produce the data.
+pub fn http_get_bytes(
+    host: &str,
+    port: &str,
+    url: &str,
+    timeout: Duration,
+) -> Vec<u8>
+{
+    if let Ok(data_from_web_request) = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_millis(200))
+        .timeout(timeout)
+        .danger_accept_invalid_certs(ACCEPT_INVALID_CERTS)
+        .build()
+        .unwrap()
+        .get(format!("http://{}:{}/{}", host, port, url))
+        .send()
+    {
+        if ! &data_from_web_request.status().is_success()
+        {
+            debug!("Non success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+            return Vec::new();
+        }
+        debug!("Success response: {}:{}/{} = {}", host, port, url, &data_from_web_request.status());
+        data_from_web_request.bytes().map(|bytes| bytes.to_vec()).unwrap_or_default()
+    } else {
+        debug!("Non-Ok success response: {}:{}/{}", host, port, url);
+        Vec::new()
+    }
+}
 /// Take the hosts from the [Option] struct, and:
 /// - adds it to the changed_options hashmap if necessary.
 /// - returns a Vec<&str>.
@@ -204,6 +284,51 @@ pub fn set_regex(
     }
 }
 
+/// Convert a size as displayed by the YugabyteDB web UI, such as `1.23G`, `512.00K` or `0B`, into bytes.
+///
+/// Only the first word is used, so `43.15M (58.42M)` results in the bytes for `43.15M`.
+/// A size that cannot be converted, such as `none`, results in [None].
+pub fn human_readable_to_bytes(
+    size: &str,
+) -> Option<u64>
+{
+    let size = size.split_whitespace().next()?;
+    let (number, multiplier) = match size.chars().last()? {
+        'B' => (&size[..size.len() - 1], 1_f64),
+        'K' => (&size[..size.len() - 1], 1024_f64),
+        'M' => (&size[..size.len() - 1], 1024_f64.powi(2)),
+        'G' => (&size[..size.len() - 1], 1024_f64.powi(3)),
+        'T' => (&size[..size.len() - 1], 1024_f64.powi(4)),
+        'P' => (&size[..size.len() - 1], 1024_f64.powi(5)),
+        _ => (size, 1_f64),
+    };
+    number.parse::<f64>().ok().map(|number| (number * multiplier).round() as u64)
+}
+
+/// Convert a number of bytes into the same human-readable format as the YugabyteDB web UI uses.
+pub fn bytes_to_human_readable(
+    bytes: u64,
+) -> String
+{
+    let units = ["K", "M", "G", "T", "P"];
+    if bytes < 1024
+    {
+        return format!("{}B", bytes);
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next_unit in units
+    {
+        if size < 1024_f64
+        {
+            break;
+        }
+        size /= 1024_f64;
+        unit = next_unit;
+    }
+    format!("{:.2}{}", size, unit)
+}
+
 /// If writing the '.env' file is allowed via write_dotenv,
 /// take the changed_options hashmap, and write it.
 pub fn dotenv_writer(
@@ -323,4 +448,20 @@ pub fn get_port_node_exporter() -> String {
         Ok(value) => value,
         Err(_e) => { panic!("The environment variable PORT_NODE_EXPORTER should be set") },
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_human_readable_to_bytes() {
+        assert_eq!(human_readable_to_bytes("0B"), Some(0));
+        assert_eq!(human_readable_to_bytes("1.5K"), Some(1536));
+        assert_eq!(human_readable_to_bytes("43.15M (58.42M)"), Some(45246054));
+        assert_eq!(human_readable_to_bytes("1.00G"), Some(1073741824));
+        assert_eq!(human_readable_to_bytes("none"), None);
+        assert_eq!(bytes_to_human_readable(1073741824), "1.00G");
+        assert_eq!(bytes_to_human_readable(100), "100B");
+    }
+}